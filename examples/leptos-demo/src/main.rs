@@ -1,44 +1,36 @@
-use contract_signature::{signature_pad_leptos, LProps};
-use leptos::*;
+use contract_signature::{signature_pad_leptos, ConsentStep, LProps, LSigned, ReadGate};
+use leptos::prelude::*;
 
-/// Small Leptos CSR example that enables signing after "read" checkbox.
+/// Small Leptos CSR example: signing unlocks once the contract is scrolled
+/// to the end (built-in read gate).
 #[component]
 fn App() -> impl IntoView {
-    // We only toggle UI affordance; the actual "Sign" button is inside the lib
-    // component.
     view! {
         <div class="signature-area">
             { signature_pad_leptos(LProps {
-                contract_container_id: "contract-root".into(),
-                place_x: 120,
-                place_y: 350,
-                place_width: 220,
+                read_gate: ReadGate::ScrollToEnd,
+                consent: Some(ConsentStep::new(
+                    "Согласен подписать договор простой электронной подписью"
+                )),
+                // Согласие фиксируется в момент подписи, вместе с временем
+                on_signed: Some(Callback::new(|signed: LSigned| {
+                    if let Some(consent) = signed.consent {
+                        leptos::logging::log!("согласие дано {}", consent.given_at);
+                    }
+                })),
+                // Подпись снята с договора: ранее полученный LSigned недействителен
+                on_removed: Some(Callback::new(|_| {
+                    leptos::logging::log!("подпись удалена");
+                })),
+                ..LProps::new("contract-root", 120, 350, 220)
             }) }
             <style>{"
               #sign-btn:disabled { opacity: 0.5; cursor: not-allowed; }
             "}</style>
         </div>
-
-        // Disable/enable the internal sign button based on checkbox state.
-        <script>
-            {"
-              (function(){
-                var cb = document.getElementById('read-checkbox');
-                function sync(){
-                  var btn = document.getElementById('sign-btn');
-                  if (!btn) return;
-                  btn.disabled = !cb.checked;
-                }
-                if (cb) {
-                  cb.addEventListener('change', sync);
-                  setTimeout(sync, 100);
-                }
-              })();
-            "}
-        </script>
     }
 }
 
 pub fn main() {
-    mount_to_body(|| view! { <App/> });
+    leptos::mount::mount_to_body(App);
 }
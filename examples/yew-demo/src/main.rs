@@ -1,68 +1,165 @@
-use contract_signature::{SignaturePadYew, SignatureStyle};
-use wasm_bindgen::{prelude::*, JsCast};
+use contract_signature::{
+    Certificate, ConsentLink, ConsentStep, Decorations, DomBindings, DraftStore, MobileMode,
+    PageSetup, PdfSignature, PenStyle, ProfileStore, ReadGate, ReadProgress, RestoredEnvelope,
+    SigError, SignaturePadYew, SignatureStyle, SignedEnvelope, SignerMetadata, TrueTypeFont
+};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
 use yew::prelude::*;
 
-// Call global JS function window.exportContractToPdf(containerId, filename)
-#[wasm_bindgen]
-extern "C" {
-    #[wasm_bindgen(js_name = exportContractToPdf)]
-    fn export_contract_to_pdf(container_id: &str, filename: &str);
+// TrueType-шрифт с кириллицей для PDF: DejaVu Sans (лицензия Bitstream Vera,
+// см. fonts/LICENSE-DejaVu.txt; исходник — https://dejavu-fonts.github.io/).
+// Trunk копирует каталог fonts через `<link data-trunk rel="copy-dir">`.
+// Без шрифта текст пишется встроенным Helvetica, и кириллица станет «?».
+const PDF_FONT_URL: &str = "fonts/DejaVuSans.ttf";
+
+async fn fetch_font(url: &str) -> Option<TrueTypeFont> {
+    let window = web_sys::window()?;
+    let resp: web_sys::Response = JsFuture::from(window.fetch_with_str(url))
+        .await
+        .ok()?
+        .dyn_into()
+        .ok()?;
+    if !resp.ok() {
+        return None;
+    }
+    let buf = JsFuture::from(resp.array_buffer().ok()?).await.ok()?;
+    TrueTypeFont::parse(js_sys::Uint8Array::new(&buf).to_vec()).ok()
+}
+
+// Собираем PDF из текста договора и подписи (векторно, из штрихов)
+async fn export_contract_pdf(envelope: SignedEnvelope, filename: &str) -> Result<(), SigError> {
+    let mut pdf = DomBindings::contract_pdf("contract-root")?;
+    pdf.font = fetch_font(PDF_FONT_URL).await;
+    pdf.signatures.push(PdfSignature::from_envelope(&envelope)?);
+    download(&pdf.to_bytes()?, filename)
+}
+
+// Сертификат о подписании: подписант, хэши, устройство, журнал аудита
+async fn export_certificate_pdf(envelope: SignedEnvelope, filename: &str) -> Result<(), SigError> {
+    let mut certificate = Certificate::new(envelope);
+    certificate.title = "Сертификат о подписании".into();
+    let bytes = certificate.to_pdf(PageSetup::default(), fetch_font(PDF_FONT_URL).await)?;
+    download(&bytes, filename)
+}
+
+// Отдаём файл через Blob + <a download>
+fn download(bytes: &[u8], filename: &str) -> Result<(), SigError> {
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+    let opts = web_sys::BlobPropertyBag::new();
+    opts.set_type("application/pdf");
+    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &opts)
+        .map_err(|_| SigError::OpFailed("new Blob".into()))?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob)
+        .map_err(|_| SigError::OpFailed("createObjectURL".into()))?;
+    let document = web_sys::window()
+        .and_then(|w| w.document())
+        .ok_or(SigError::DomUnavailable)?;
+    let link: web_sys::HtmlAnchorElement = document
+        .create_element("a")
+        .map_err(|_| SigError::OpFailed("createElement(a)".into()))?
+        .unchecked_into();
+    link.set_href(&url);
+    link.set_download(filename);
+    link.click();
+    let _ = web_sys::Url::revoke_object_url(&url);
+    Ok(())
 }
 
 #[function_component(App)]
 fn app() -> Html {
-    let can_sign = use_state(|| false);
     let has_signed = use_state(|| false);
+    let envelope = use_state(|| None::<SignedEnvelope>);
+    // Черновик подписи переживает закрытие окна и перезагрузку (сутки)
+    let drafts = use_state(|| {
+        let mut store = DraftStore::browser();
+        store.ttl_ms = Some(24.0 * 60.0 * 60.0 * 1000.0);
+        store
+    });
+    // Сохранённая подпись подписанта (с его согласия), общая для договоров
+    let profile = use_state(|| ProfileStore::browser("customer"));
 
-    // Подхватываем чекбокс из DOM (как и делали)
-    {
-        let can_sign = can_sign.clone();
-        use_effect_with((), move |_| {
-            if let Some(doc) = web_sys::window().and_then(|w| w.document()) {
-                if let Some(cb) = doc
-                    .get_element_by_id("read-checkbox")
-                    .and_then(|e| e.dyn_into::<web_sys::HtmlInputElement>().ok())
-                {
-                    can_sign.set(cb.checked());
-                    let state = can_sign.clone();
-                    let cb_for_closure = cb.clone();
-                    let closure =
-                        wasm_bindgen::closure::Closure::wrap(Box::new(move |_e: web_sys::Event| {
-                            state.set(cb_for_closure.checked());
-                        })
-                            as Box<dyn FnMut(_)>);
-                    let _ = cb.add_event_listener_with_callback(
-                        "change",
-                        closure.as_ref().unchecked_ref()
-                    );
-                    closure.forget();
-                }
-            }
-            || ()
-        });
-    }
+    // Подпись разблокируется, когда договор прокручен до конца
+    let read_progress = use_state(ReadProgress::default);
+    let on_read_progress = {
+        let read_progress = read_progress.clone();
+        Callback::from(move |p: ReadProgress| read_progress.set(p))
+    };
 
     // Колбэк от компонента подписи: отметим, что подпись сделана
     let on_signed = {
         let has_signed = has_signed.clone();
-        Callback::from(move |_data_url: String| {
+        let envelope = envelope.clone();
+        Callback::from(move |signed: SignedEnvelope| {
+            has_signed.set(true);
+            envelope.set(Some(signed));
+        })
+    };
+
+    // Подпись из черновика после перезагрузки. Хранилище браузера может
+    // изменить любой скрипт страницы: в настоящем приложении такой конверт
+    // проверяют на сервере, демо просто снова открывает экспорт
+    let on_restored = {
+        let has_signed = has_signed.clone();
+        let envelope = envelope.clone();
+        Callback::from(move |restored: RestoredEnvelope| {
             has_signed.set(true);
+            envelope.set(Some(restored.envelope));
+        })
+    };
+
+    // Подпись удалили — PDF снова недоступен
+    let on_removed = {
+        let has_signed = has_signed.clone();
+        let envelope = envelope.clone();
+        Callback::from(move |()| {
+            has_signed.set(false);
+            envelope.set(None);
         })
     };
 
     // Клик «Скачать PDF»
     let on_export_click = {
-        let has_signed = *has_signed;
+        let envelope = (*envelope).clone();
         Callback::from(move |_e: web_sys::MouseEvent| {
             // Можно не запрещать, но UX логичнее — только после подписи
-            if has_signed {
-                export_contract_to_pdf("paper", "signed-contract.pdf");
+            if let Some(envelope) = envelope.clone() {
+                wasm_bindgen_futures::spawn_local(async move {
+                    if let Err(e) = export_contract_pdf(envelope, "signed-contract.pdf").await {
+                        web_sys::console::error_1(&e.to_string().into());
+                    }
+                });
+            }
+        })
+    };
+
+    // Клик «Сертификат»
+    let on_certificate_click = {
+        let envelope = (*envelope).clone();
+        Callback::from(move |_e: web_sys::MouseEvent| {
+            if let Some(envelope) = envelope.clone() {
+                wasm_bindgen_futures::spawn_local(async move {
+                    if let Err(e) = export_certificate_pdf(envelope, "certificate.pdf").await {
+                        web_sys::console::error_1(&e.to_string().into());
+                    }
+                });
             }
         })
     };
 
     html! {
         <>
+          {
+            if !read_progress.complete {
+              html! {
+                <span class="gate__text">
+                  {format!("Прочитано: {:.0}%", read_progress.fraction * 100.0)}
+                </span>
+              }
+            } else {
+              Html::default()
+            }
+          }
           <SignaturePadYew
             contract_container_id={"contract-root".to_string()}
             // fallback, если вдруг якорь не найдётся
@@ -70,7 +167,20 @@ fn app() -> Html {
             place_y={80}
             place_width={220}
             anchor_id={Some("signature-anchor".to_string())}
-            enabled = {*can_sign}
+            read_gate={ReadGate::ScrollToEnd}
+            consent={Some(ConsentStep {
+                text: "Согласен подписать договор простой электронной подписью".into(),
+                link: Some(ConsentLink {
+                    label: "Условия ЭП".into(),
+                    url: "https://example.com/e-signature-terms".into()
+                }),
+                required: true
+            })}
+            on_read_progress={on_read_progress}
+            signer = { SignerMetadata {
+                name: "Заказчик".into(),
+                ..Default::default()
+            }}
             style = { Some(SignatureStyle{
                 border: "2px dashed #2b8a3e".into(),
                 background: "#ffffff".into(),
@@ -78,7 +188,21 @@ fn app() -> Html {
                 height: 220,
                 line_width: 2.0
             })}
+            // Синие чернила: оригиналы для части клиентов подписываются синим
+            pen={Some(PenStyle::solid("#1a3d9c", 2.0))}
+            pen_picker=true
+            // На телефонах — полноэкранная подпись в альбомной ориентации
+            mobile={MobileMode::Auto}
+            // Линия с «✕» и подсказка, в экспорт не попадают
+            decorations={Some(Decorations {
+                placeholder: Some("Подпишите здесь".into()),
+                ..Default::default()
+            })}
             on_signed={on_signed}
+            on_removed={on_removed}
+            drafts={Some((*drafts).clone())}
+            on_restored={on_restored}
+            profile={Some((*profile).clone())}
           />
 
           // Кнопка экспорта, активна только после подписи
@@ -88,6 +212,11 @@ fn app() -> Html {
                     disabled={!*has_signed}>
               {"Скачать PDF"}
             </button>
+            <button class="secondary"
+                    onclick={on_certificate_click}
+                    disabled={!*has_signed}>
+              {"Сертификат"}
+            </button>
             {
               if !*has_signed {
                 html! { <span class="export__hint">{"Подпишитесь, чтобы скачать PDF"}</span> }
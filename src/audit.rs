@@ -0,0 +1,359 @@
+// src/audit.rs
+
+use serde::{Deserialize, Serialize};
+
+use crate::{envelope::sha256_hex, error::SigError};
+
+/// `prev_hash` of the first entry in a chain.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// What happened during the signing session.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AuditEvent {
+    ModalOpened,
+    StrokeStarted {
+        x: f64,
+        y: f64
+    },
+    StrokeEnded {
+        points: usize
+    },
+    /// A second finger turned the stroke in progress into a pinch/pan; the
+    /// stroke was dropped.
+    StrokeCancelled,
+    Cleared,
+    Undo,
+    /// Eraser gesture cut `points` points out of the strokes.
+    Erased {
+        points: usize
+    },
+    Confirmed,
+    Cancelled,
+    /// Placed signature taken off the contract (remove / re-sign).
+    Removed,
+    AnchorResolved {
+        anchor_id: String,
+        x:         i32,
+        y:         i32
+    },
+    Placed {
+        x:     i32,
+        y:     i32,
+        width: i32
+    },
+    InitialsStamped {
+        anchors: Vec<String>
+    },
+    /// Signer dragged/resized the image (interactive placement).
+    PlacementAdjusted {
+        x:     i32,
+        y:     i32,
+        width: i32
+    },
+    PlacementLocked {
+        x:     i32,
+        y:     i32,
+        width: i32
+    },
+    /// Saved draft loaded on mount (`placed`: the signature went back onto
+    /// the contract).
+    DraftRestored {
+        strokes: usize,
+        placed:  bool
+    },
+    /// Saved draft dropped on mount instead of restored (it was made for
+    /// different contract text, or its audit trail doesn't verify).
+    DraftDiscarded {
+        reason: String
+    },
+    /// Signer consented to storing the signature in their profile.
+    ProfileSaved {
+        profile_id: String
+    },
+    /// Saved profile signature loaded into the pad.
+    ProfileUsed {
+        profile_id: String
+    },
+    ProfileDeleted {
+        profile_id: String
+    },
+    /// Read gate satisfied; `sections` is `0` for scroll-to-end.
+    ReadingCompleted {
+        sections:   usize,
+        elapsed_ms: u64
+    },
+    /// Consent checkbox ticked; `text` is the wording agreed to.
+    ConsentGiven {
+        text: String
+    },
+    /// Confirmation blocked: the signature didn't match the enrolled
+    /// reference closely enough (`score`, see `SimilarityReport`).
+    SimilarityRejected {
+        score: f64
+    }
+}
+
+/// Environment the event was recorded in.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AuditContext {
+    pub user_agent:      String,
+    pub viewport_width:  f64,
+    pub viewport_height: f64,
+    /// Pointer types (`mouse`, `pen`, `touch`) seen so far in the session.
+    pub pointer_types:   Vec<String>
+}
+
+/// One link of the hash chain.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub seq:       u64,
+    /// ISO 8601 UTC.
+    pub timestamp: String,
+    pub event:     AuditEvent,
+    pub context:   AuditContext,
+    /// `hash` of the previous entry, `GENESIS_HASH` for the first one.
+    pub prev_hash: String,
+    /// SHA-256 (hex) over `prev_hash` and the JSON of the fields above.
+    pub hash:      String
+}
+
+/// Fields covered by `AuditEntry::hash`.
+#[derive(Serialize)]
+struct HashedFields<'a> {
+    seq:       u64,
+    timestamp: &'a str,
+    event:     &'a AuditEvent,
+    context:   &'a AuditContext
+}
+
+impl AuditEntry {
+    fn compute_hash(&self) -> Result<String, SigError> {
+        let fields = HashedFields {
+            seq:       self.seq,
+            timestamp: &self.timestamp,
+            event:     &self.event,
+            context:   &self.context
+        };
+        let json = serde_json::to_string(&fields)
+            .map_err(|e| SigError::OpFailed(format!("serialize audit entry: {e}")))?;
+        Ok(sha256_hex(format!("{}{json}", self.prev_hash).as_bytes()))
+    }
+}
+
+/// Append-only, hash-chained log of a signing session.
+#[derive(Clone, Debug, Default)]
+pub struct AuditLog {
+    entries:       Vec<AuditEntry>,
+    pointer_types: Vec<String>
+}
+
+impl AuditLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Continue a chain recorded earlier (e.g. the trail of a restored
+    /// draft). Fails with the index of the first broken entry.
+    pub fn resume(entries: Vec<AuditEntry>) -> Result<Self, usize> {
+        if let Some(broken) = verify_audit_chain(&entries) {
+            return Err(broken);
+        }
+        let pointer_types = entries
+            .last()
+            .map(|e| e.context.pointer_types.clone())
+            .unwrap_or_default();
+        Ok(Self {
+            entries,
+            pointer_types
+        })
+    }
+
+    /// Append an event stamped with the current time and environment.
+    /// `pointer_type` is the type of the pointer that caused it, if any.
+    pub fn record(
+        &mut self,
+        event: AuditEvent,
+        pointer_type: Option<&str>
+    ) -> Result<&AuditEntry, SigError> {
+        if let Some(pt) = pointer_type
+            && !pt.is_empty()
+            && !self.pointer_types.iter().any(|p| p == pt)
+        {
+            self.pointer_types.push(pt.to_string());
+        }
+        let timestamp = String::from(js_sys::Date::new_0().to_iso_string());
+        let context = current_context(self.pointer_types.clone());
+        self.push(timestamp, event, context)
+    }
+
+    /// Append an event with explicit timestamp and context (e.g. replaying a
+    /// log recorded elsewhere). Nothing is appended if the entry can't be
+    /// hashed.
+    pub fn push(
+        &mut self,
+        timestamp: String,
+        event: AuditEvent,
+        context: AuditContext
+    ) -> Result<&AuditEntry, SigError> {
+        let prev_hash = self
+            .entries
+            .last()
+            .map(|e| e.hash.clone())
+            .unwrap_or_else(|| GENESIS_HASH.to_string());
+        let mut entry = AuditEntry {
+            seq: self.entries.len() as u64,
+            timestamp,
+            event,
+            context,
+            prev_hash,
+            hash: String::new()
+        };
+        entry.hash = entry.compute_hash()?;
+        self.entries.push(entry);
+        Ok(&self.entries[self.entries.len() - 1])
+    }
+
+    pub fn entries(&self) -> &[AuditEntry] {
+        &self.entries
+    }
+
+    /// Hash of the latest entry (`GENESIS_HASH` if empty).
+    pub fn head_hash(&self) -> &str {
+        self.entries
+            .last()
+            .map(|e| e.hash.as_str())
+            .unwrap_or(GENESIS_HASH)
+    }
+}
+
+/// Check sequence numbers, links and hashes of a serialized log. Returns the
+/// index of the first broken entry (or one that can't be hashed), or `None`
+/// if the chain is intact.
+///
+/// Entries dropped from the end are not detected: any prefix of a chain is
+/// itself a valid chain. Compare the last `hash` with a head recorded
+/// elsewhere (`AuditLog::head_hash`, kept e.g. on the server), or check the
+/// trail as part of a sealed envelope (see `verify_envelope`).
+pub fn verify_audit_chain(entries: &[AuditEntry]) -> Option<usize> {
+    let mut prev = GENESIS_HASH;
+    for (i, entry) in entries.iter().enumerate() {
+        let hashed = entry.compute_hash().is_ok_and(|hash| hash == entry.hash);
+        if entry.seq != i as u64 || entry.prev_hash != prev || !hashed {
+            return Some(i);
+        }
+        prev = &entry.hash;
+    }
+    None
+}
+
+fn current_context(pointer_types: Vec<String>) -> AuditContext {
+    let Some(window) = web_sys::window() else {
+        return AuditContext {
+            pointer_types,
+            ..Default::default()
+        };
+    };
+    let dim = |v: Result<wasm_bindgen::JsValue, _>| v.ok().and_then(|v| v.as_f64()).unwrap_or(0.0);
+    AuditContext {
+        user_agent: window.navigator().user_agent().unwrap_or_default(),
+        viewport_width: dim(window.inner_width()),
+        viewport_height: dim(window.inner_height()),
+        pointer_types
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(events: usize) -> AuditLog {
+        let mut log = AuditLog::new();
+        for i in 0..events {
+            log.push(
+                format!("2026-01-01T00:00:0{i}.000Z"),
+                AuditEvent::StrokeEnded {
+                    points: i
+                },
+                AuditContext {
+                    pointer_types: vec!["pen".into()],
+                    ..Default::default()
+                }
+            )
+            .unwrap();
+        }
+        log
+    }
+
+    #[test]
+    fn resume_continues_the_chain() {
+        let earlier = log(3);
+        let mut resumed = AuditLog::resume(earlier.entries().to_vec()).unwrap();
+        assert_eq!(resumed.head_hash(), earlier.head_hash());
+        resumed
+            .push(
+                String::new(),
+                AuditEvent::Confirmed,
+                AuditContext::default()
+            )
+            .unwrap();
+        assert_eq!(resumed.entries().len(), 4);
+        assert_eq!(verify_audit_chain(resumed.entries()), None);
+        assert_eq!(resumed.pointer_types, vec!["pen".to_string()]);
+
+        let mut broken = earlier.entries().to_vec();
+        broken[1].timestamp.push('Z');
+        assert_eq!(AuditLog::resume(broken).unwrap_err(), 1);
+    }
+
+    #[test]
+    fn intact_chain_verifies() {
+        assert_eq!(verify_audit_chain(log(5).entries()), None);
+        assert_eq!(verify_audit_chain(&[]), None);
+    }
+
+    #[test]
+    fn tampered_entry_is_detected() {
+        let mut entries = log(4).entries().to_vec();
+        entries[2].event = AuditEvent::StrokeEnded {
+            points: 99
+        };
+        assert_eq!(verify_audit_chain(&entries), Some(2));
+
+        // Re-hashing the edited entry breaks the link of the next one
+        entries[2].hash = entries[2].compute_hash().unwrap();
+        assert_eq!(verify_audit_chain(&entries), Some(3));
+
+        let mut entries = log(4).entries().to_vec();
+        entries[1].context.user_agent = "forged".into();
+        assert_eq!(verify_audit_chain(&entries), Some(1));
+    }
+
+    #[test]
+    fn reordered_entries_are_detected() {
+        let mut entries = log(4).entries().to_vec();
+        entries.swap(1, 2);
+        assert_eq!(verify_audit_chain(&entries), Some(1));
+
+        // Renumbering doesn't help: the links still point elsewhere
+        entries[1].seq = 1;
+        entries[2].seq = 2;
+        assert_eq!(verify_audit_chain(&entries), Some(1));
+    }
+
+    #[test]
+    fn tail_truncation_needs_the_head_hash() {
+        let full = log(5);
+        let entries = full.entries();
+        // Dropping from the front or the middle breaks the chain
+        assert_eq!(verify_audit_chain(&entries[1..]), Some(0));
+        let mut gap = entries.to_vec();
+        gap.remove(2);
+        assert_eq!(verify_audit_chain(&gap), Some(2));
+        // Dropping the tail leaves a valid prefix (documented limitation);
+        // only a head hash recorded elsewhere gives it away
+        let prefix = &entries[..3];
+        assert_eq!(verify_audit_chain(prefix), None);
+        assert_ne!(prefix.last().unwrap().hash, full.head_hash());
+    }
+}
@@ -0,0 +1,483 @@
+// src/certificate.rs
+
+use std::fmt::Write as _;
+
+use crate::{
+    audit::{verify_audit_chain, AuditEvent},
+    envelope::SignedEnvelope,
+    error::SigError,
+    pdf::{ContractPdf, PageSetup, PdfSignature, SignatureImage, TextRun, TrueTypeFont},
+    placement::PlacementGeometry,
+    profile::SignatureOrigin
+};
+
+/// Average Helvetica glyph width, in units of the font size (used for line
+/// breaking when no font is embedded).
+const BUILTIN_CHAR_WIDTH: f64 = 0.6;
+/// Line height, in units of the font size.
+const LINE_HEIGHT: f64 = 1.4;
+const TITLE_SIZE: f64 = 18.0;
+const HEADING_SIZE: f64 = 12.0;
+const BODY_SIZE: f64 = 9.0;
+const TABLE_SIZE: f64 = 7.5;
+/// Width of the label column, pt.
+const LABEL_WIDTH: f64 = 150.0;
+/// Height of the signature box on the PDF page, pt.
+const SIGNATURE_HEIGHT: f64 = 90.0;
+/// Hex digits of an audit entry hash shown in the trail.
+const HASH_PREFIX: usize = 16;
+
+/// Human-readable certificate of completion for a signed envelope: signer,
+/// timestamps, hashes, the signature itself, device details and the audit
+/// trail, as HTML or a PDF.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Certificate {
+    pub envelope:      SignedEnvelope,
+    pub title:         String,
+    /// Shown above the hashes, e.g. the contract number.
+    pub document_name: Option<String>
+}
+
+/// Label/value rows under a heading.
+type Section = (&'static str, Vec<(&'static str, String)>);
+
+impl Certificate {
+    pub fn new(envelope: SignedEnvelope) -> Self {
+        Self {
+            envelope,
+            title: "Certificate of completion".into(),
+            document_name: None
+        }
+    }
+
+    /// Standalone HTML document (inline styles, the signature embedded as
+    /// its data URL).
+    pub fn to_html(&self) -> String {
+        let mut out = String::from(
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\">\n<style>\
+             body{font-family:sans-serif;font-size:14px;color:#222;max-width:800px;margin:24px auto}\
+             h1{font-size:24px}h2{font-size:16px;border-bottom:1px solid #ccc;padding-bottom:4px}\
+             th{text-align:left;font-weight:normal;color:#666;padding-right:16px;vertical-align:top}\
+             td{word-break:break-all;vertical-align:top}\
+             .cs-trail td,.cs-trail th{font-size:12px;padding:2px 8px 2px 0}\
+             .cs-signature{max-width:320px;max-height:140px;border:1px solid #ccc}\
+             </style>\n"
+        );
+        let _ = writeln!(out, "<title>{}</title></head><body>", escape(&self.title));
+        let _ = writeln!(out, "<h1>{}</h1>", escape(&self.title));
+
+        for (heading, rows) in self.sections() {
+            let _ = writeln!(out, "<h2>{heading}</h2>\n<table>");
+            for (label, value) in rows {
+                let _ = writeln!(out, "<tr><th>{label}</th><td>{}</td></tr>", escape(&value));
+            }
+            out.push_str("</table>\n");
+            if heading == "Signer" {
+                let _ = writeln!(
+                    out,
+                    "<h2>Signature</h2>\n<img class=\"cs-signature\" alt=\"signature\" src=\"{}\">",
+                    escape(&self.envelope.data_url)
+                );
+            }
+        }
+
+        let _ = writeln!(
+            out,
+            "<h2>Audit trail</h2>\n<p>{}</p>\n<table class=\"cs-trail\">\n\
+             <tr><th>#</th><th>Time</th><th>Event</th><th>Hash</th></tr>",
+            escape(&self.chain_status())
+        );
+        for entry in &self.envelope.audit_trail {
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                entry.seq,
+                escape(&entry.timestamp),
+                escape(&describe(&entry.event)),
+                escape(hash_prefix(&entry.hash))
+            );
+        }
+        out.push_str("</table>\n</body></html>\n");
+        out
+    }
+
+    /// The certificate as a PDF. `font` is embedded as in `ContractPdf`;
+    /// without one, text outside Latin-1 is lost.
+    pub fn to_pdf(
+        &self,
+        page: PageSetup,
+        font: Option<TrueTypeFont>
+    ) -> Result<Vec<u8>, SigError> {
+        // Content width in pt, so the contract px space maps 1:1 to points
+        let width = page.width - 2.0 * page.margin;
+        let mut pdf = ContractPdf::new(width);
+        pdf.page = page;
+        let mut layout = Layout {
+            pdf:  &mut pdf,
+            font: font.as_ref(),
+            y:    0.0
+        };
+
+        layout.line(&self.title, 0.0, width, TITLE_SIZE);
+        for (heading, rows) in self.sections() {
+            layout.heading(heading);
+            for (label, value) in rows {
+                layout.row(
+                    &[
+                        (label, 0.0, LABEL_WIDTH),
+                        (&value, LABEL_WIDTH, width - LABEL_WIDTH)
+                    ],
+                    BODY_SIZE
+                );
+            }
+            if heading == "Signer" {
+                layout.heading("Signature");
+                let signature = self.pdf_signature(layout.y, width)?;
+                layout.y += signature.geometry.height + BODY_SIZE;
+                layout.pdf.signatures.push(signature);
+            }
+        }
+
+        layout.heading("Audit trail");
+        layout.row(&[(&self.chain_status(), 0.0, width)], BODY_SIZE);
+        let cols = [0.0, 24.0, 130.0, width - 80.0, width];
+        layout.row(
+            &[
+                ("#", cols[0], cols[1] - cols[0]),
+                ("Time", cols[1], cols[2] - cols[1]),
+                ("Event", cols[2], cols[3] - cols[2]),
+                ("Hash", cols[3], cols[4] - cols[3])
+            ],
+            TABLE_SIZE
+        );
+        for entry in &self.envelope.audit_trail {
+            layout.row(
+                &[
+                    (&entry.seq.to_string(), cols[0], cols[1] - cols[0]),
+                    (&entry.timestamp, cols[1], cols[2] - cols[1]),
+                    (&describe(&entry.event), cols[2], cols[3] - cols[2]),
+                    (hash_prefix(&entry.hash), cols[3], cols[4] - cols[3])
+                ],
+                TABLE_SIZE
+            );
+        }
+
+        pdf.font = font;
+        pdf.to_bytes()
+    }
+
+    /// Everything but the signature image and the audit trail.
+    fn sections(&self) -> Vec<Section> {
+        let env = &self.envelope;
+        let mut signer = vec![("Name", env.signer.name.clone())];
+        if let Some(email) = &env.signer.email {
+            signer.push(("Email", email.clone()));
+        }
+        if let Some(id) = &env.signer.id {
+            signer.push(("ID", id.clone()));
+        }
+
+        let mut timeline = Vec::new();
+        if let Some(first) = env.audit_trail.first() {
+            timeline.push(("Session started", first.timestamp.clone()));
+        }
+        if let Some(consent) = &env.consent {
+            timeline.push(("Consent given at", consent.given_at.clone()));
+        }
+        timeline.push(("Signed at", env.signed_at.clone()));
+
+        let mut document = Vec::new();
+        if let Some(name) = &self.document_name {
+            document.push(("Document", name.clone()));
+        }
+        document.push(("Document hash (SHA-256)", env.document_hash.clone()));
+        document.push(("Signature image hash", env.signature_image_hash.clone()));
+        document.push(("Stroke data hash", env.stroke_data_hash.clone()));
+        if let Some(field) = &env.field_id {
+            document.push(("Field", field.clone()));
+        }
+        if let Some(g) = env.placement {
+            document.push(("Placement", format_geometry(g)));
+        }
+        if !env.initials_anchors.is_empty() {
+            document.push(("Initials", env.initials_anchors.join(", ")));
+        }
+        if let Some(consent) = &env.consent {
+            let text = match &consent.link {
+                Some(link) => format!("{} ({}: {})", consent.text, link.label, link.url),
+                None => consent.text.clone()
+            };
+            document.push(("Consent", text));
+        }
+        document.push((
+            "Signature origin",
+            match &env.origin {
+                SignatureOrigin::Drawn => "Drawn for this document".into(),
+                SignatureOrigin::Reused {
+                    profile_id,
+                    consented_at
+                } => format!(
+                    "Reused from saved profile '{profile_id}' (stored with consent {consented_at})"
+                )
+            }
+        ));
+
+        // Latest context has every pointer type seen in the session
+        let mut device = Vec::new();
+        if let Some(last) = env.audit_trail.last() {
+            let ctx = &last.context;
+            device.push(("User agent", ctx.user_agent.clone()));
+            device.push((
+                "Viewport",
+                format!("{} x {}", ctx.viewport_width, ctx.viewport_height)
+            ));
+            device.push(("Pointer types", ctx.pointer_types.join(", ")));
+        }
+        let points: usize = env.strokes.iter().map(|s| s.points.len()).sum();
+        device.push((
+            "Strokes",
+            format!("{} ({points} points)", env.strokes.len())
+        ));
+
+        let mut assessment = vec![("Risk score", format!("{:.2}", env.risk.score))];
+        if !env.risk.flags.is_empty() {
+            let flags: Vec<String> = env.risk.flags.iter().map(|f| format!("{f:?}")).collect();
+            assessment.push(("Risk flags", flags.join(", ")));
+        }
+        if env.risk.untrusted_events > 0 {
+            assessment.push(("Untrusted events", env.risk.untrusted_events.to_string()));
+        }
+        if let Some(sim) = &env.similarity {
+            assessment.push(("Similarity to reference", format!("{:.2}", sim.score)));
+        }
+
+        vec![
+            ("Signer", signer),
+            ("Timeline", timeline),
+            ("Document", document),
+            ("Device", device),
+            ("Assessment", assessment),
+        ]
+    }
+
+    fn chain_status(&self) -> String {
+        let trail = &self.envelope.audit_trail;
+        match verify_audit_chain(trail) {
+            None => format!("Hash chain intact, {} entries.", trail.len()),
+            Some(i) => format!("Hash chain BROKEN at entry {i}.")
+        }
+    }
+
+    /// Signature box at `y`, scaled to fit `SIGNATURE_HEIGHT`.
+    fn pdf_signature(&self, y: f64, width: f64) -> Result<PdfSignature, SigError> {
+        let image = SignatureImage::from_png(&self.envelope.png_bytes()?)?;
+        let (cw, ch) = (image.width.max(1) as f64, image.height.max(1) as f64);
+        let h = SIGNATURE_HEIGHT.min(ch);
+        let w = (h * cw / ch).min(width);
+        Ok(PdfSignature {
+            geometry:      PlacementGeometry {
+                x: 0.0,
+                y,
+                width: w,
+                height: w * ch / cw
+            },
+            strokes:       if self.envelope.composed {
+                Vec::new()
+            } else {
+                self.envelope.strokes.clone()
+            },
+            canvas_width:  cw,
+            canvas_height: ch,
+            line_width:    2.0,
+            image:         Some(image)
+        })
+    }
+}
+
+/// Running text cursor over a `ContractPdf` (y in pt from the top).
+struct Layout<'a> {
+    pdf:  &'a mut ContractPdf,
+    font: Option<&'a TrueTypeFont>,
+    y:    f64
+}
+
+impl Layout<'_> {
+    fn heading(&mut self, text: &str) {
+        self.y += HEADING_SIZE * 0.5;
+        let width = self.pdf.width;
+        self.line(text, 0.0, width, HEADING_SIZE);
+    }
+
+    fn line(&mut self, text: &str, x: f64, width: f64, size: f64) {
+        self.row(&[(text, x, width)], size);
+    }
+
+    /// One table row: each cell `(text, x, width)` wraps inside its column;
+    /// the row is as tall as its tallest cell.
+    fn row(&mut self, cells: &[(&str, f64, f64)], size: f64) {
+        let mut lines_max = 1;
+        for &(text, x, width) in cells {
+            let lines = self.wrap(text, width, size);
+            lines_max = lines_max.max(lines.len());
+            for (i, line) in lines.into_iter().enumerate() {
+                self.pdf.texts.push(TextRun {
+                    text: line,
+                    x,
+                    y: self.y + i as f64 * size * LINE_HEIGHT,
+                    width: 0.0,
+                    size
+                });
+            }
+        }
+        self.y += lines_max as f64 * size * LINE_HEIGHT;
+    }
+
+    fn measure(&self, text: &str, size: f64) -> f64 {
+        match self.font {
+            Some(font) => font.text_width(text, size),
+            None => text.chars().count() as f64 * BUILTIN_CHAR_WIDTH * size
+        }
+    }
+
+    /// Greedy line breaking at spaces; words wider than the column (hashes,
+    /// user agents) are broken anywhere.
+    fn wrap(&self, text: &str, width: f64, size: f64) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        for word in text.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{line} {word}")
+            };
+            if self.measure(&candidate, size) <= width {
+                line = candidate;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            for c in word.chars() {
+                line.push(c);
+                if self.measure(&line, size) > width && line.chars().count() > 1 {
+                    line.pop();
+                    lines.push(std::mem::replace(&mut line, c.to_string()));
+                }
+            }
+        }
+        if !line.is_empty() || lines.is_empty() {
+            lines.push(line);
+        }
+        lines
+    }
+}
+
+fn describe(event: &AuditEvent) -> String {
+    match event {
+        AuditEvent::ModalOpened => "Signing window opened".into(),
+        AuditEvent::StrokeStarted {
+            x,
+            y
+        } => format!("Stroke started at ({x:.0}, {y:.0})"),
+        AuditEvent::StrokeEnded {
+            points
+        } => format!("Stroke ended, {points} points"),
+        AuditEvent::StrokeCancelled => "Stroke dropped (pinch/pan started)".into(),
+        AuditEvent::Cleared => "Pad cleared".into(),
+        AuditEvent::Undo => "Last stroke undone".into(),
+        AuditEvent::Erased {
+            points
+        } => format!("Erased {points} points"),
+        AuditEvent::Confirmed => "Signature confirmed".into(),
+        AuditEvent::Cancelled => "Signing cancelled".into(),
+        AuditEvent::Removed => "Signature removed".into(),
+        AuditEvent::AnchorResolved {
+            anchor_id,
+            x,
+            y
+        } => format!("Anchor '{anchor_id}' found at ({x}, {y})"),
+        AuditEvent::Placed {
+            x,
+            y,
+            width
+        } => format!("Placed at ({x}, {y}), width {width}"),
+        AuditEvent::InitialsStamped {
+            anchors
+        } => format!("Initials stamped: {}", anchors.join(", ")),
+        AuditEvent::PlacementAdjusted {
+            x,
+            y,
+            width
+        } => format!("Moved to ({x}, {y}), width {width}"),
+        AuditEvent::PlacementLocked {
+            x,
+            y,
+            width
+        } => format!("Placement locked at ({x}, {y}), width {width}"),
+        AuditEvent::DraftRestored {
+            strokes,
+            placed
+        } => {
+            if *placed {
+                format!("Draft restored, {strokes} strokes, signature placed")
+            } else {
+                format!("Draft restored, {strokes} strokes")
+            }
+        }
+        AuditEvent::DraftDiscarded {
+            reason
+        } => format!("Draft discarded: {reason}"),
+        AuditEvent::ProfileSaved {
+            profile_id
+        } => format!("Signature saved to profile '{profile_id}'"),
+        AuditEvent::ProfileUsed {
+            profile_id
+        } => format!("Saved signature of profile '{profile_id}' used"),
+        AuditEvent::ProfileDeleted {
+            profile_id
+        } => format!("Saved signature of profile '{profile_id}' deleted"),
+        AuditEvent::ReadingCompleted {
+            sections,
+            elapsed_ms
+        } => {
+            let secs = *elapsed_ms as f64 / 1000.0;
+            if *sections > 0 {
+                format!("Contract read: {sections} sections, {secs:.1} s")
+            } else {
+                format!("Contract read to the end, {secs:.1} s")
+            }
+        }
+        AuditEvent::ConsentGiven {
+            text
+        } => format!("Consent given: \"{text}\""),
+        AuditEvent::SimilarityRejected {
+            score
+        } => format!("Confirmation blocked: similarity {score:.2} to the reference")
+    }
+}
+
+fn format_geometry(g: PlacementGeometry) -> String {
+    format!(
+        "x {:.0}, y {:.0}, {:.0} x {:.0} px",
+        g.x, g.y, g.width, g.height
+    )
+}
+
+fn hash_prefix(hash: &str) -> &str {
+    hash.get(..HASH_PREFIX).unwrap_or(hash)
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c)
+        }
+    }
+    out
+}
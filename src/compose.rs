@@ -0,0 +1,612 @@
+// src/compose.rs
+
+//! Composition of the exported signature: the trimmed ink with text blocks
+//! (printed name, date, ...) and stamp images laid out around it, rendered
+//! to a PNG or SVG data URL that `DomBindings::place_signature_img` takes
+//! like the pad's own export.
+
+use std::fmt::Write as _;
+
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{CanvasRenderingContext2d, HtmlImageElement};
+
+use crate::{
+    envelope::SignedEnvelope,
+    error::SigError,
+    format::num,
+    placement::Align,
+    signature_core::{draw_stroke, offscreen_canvas, svg_escape, svg_strokes, Stroke}
+};
+
+/// Line height, in units of the font size.
+const LINE_HEIGHT: f64 = 1.25;
+/// Baseline below the top of a line, in units of the font size.
+const ASCENT: f64 = 0.95;
+/// Largest output side, px (canvas limits).
+const MAX_SIDE: f64 = 8192.0;
+
+/// Where a block goes relative to the ink.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Side {
+    Top,
+    #[default]
+    Bottom,
+    Left,
+    Right,
+    /// On top of the ink (e.g. a seal overlapping the signature).
+    Over
+}
+
+/// Font of a text block.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextStyle {
+    /// CSS font family, e.g. `"Georgia, serif"`.
+    pub family: String,
+    /// Font size, canvas px.
+    pub size:   f64,
+    /// CSS weight, 100..=900.
+    pub weight: u16,
+    pub italic: bool,
+    /// CSS colour.
+    pub color:  String
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self {
+            family: "sans-serif".into(),
+            size:   14.0,
+            weight: 400,
+            italic: false,
+            color:  "#000000".into()
+        }
+    }
+}
+
+impl TextStyle {
+    fn css(&self) -> String {
+        format!(
+            "{}{} {}px {}",
+            if self.italic { "italic " } else { "" },
+            self.weight,
+            num(self.size),
+            self.family
+        )
+    }
+}
+
+/// What a block shows.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BlockContent {
+    /// Text; `\n` starts a new line.
+    Text { text: String, style: TextStyle },
+    /// Image (data URL or same-origin/CORS URL), stretched to
+    /// `width` x `height` canvas px.
+    Image {
+        src:     String,
+        width:   f64,
+        height:  f64,
+        opacity: f64
+    }
+}
+
+/// One text block or stamp image of a composition.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Block {
+    pub content:  BlockContent,
+    pub side:     Side,
+    /// Along the ink's edge (left..right for `Top`/`Bottom`, top..bottom
+    /// for `Left`/`Right`, both axes for `Over`); also aligns text lines.
+    pub align:    Align,
+    /// Clockwise, degrees, around the block's centre.
+    pub rotation: f64
+}
+
+impl Block {
+    /// Text centred under the ink.
+    pub fn text(text: impl Into<String>, style: TextStyle) -> Self {
+        Self {
+            content:  BlockContent::Text {
+                text: text.into(),
+                style
+            },
+            side:     Side::default(),
+            align:    Align::Center,
+            rotation: 0.0
+        }
+    }
+
+    /// Opaque image centred under the ink.
+    pub fn image(src: impl Into<String>, width: f64, height: f64) -> Self {
+        Self {
+            content:  BlockContent::Image {
+                src: src.into(),
+                width,
+                height,
+                opacity: 1.0
+            },
+            side:     Side::default(),
+            align:    Align::Center,
+            rotation: 0.0
+        }
+    }
+}
+
+/// Trimmed signature plus blocks, stacked outwards from the ink on each
+/// side in order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Composition {
+    pub blocks:     Vec<Block>,
+    /// Space around everything, canvas px.
+    pub padding:    f64,
+    /// Space between the ink and blocks and between blocks, canvas px.
+    pub gap:        f64,
+    /// Output px per canvas px (2.0 for sharp prints).
+    pub scale:      f64,
+    /// CSS background colour; `None` is transparent.
+    pub background: Option<String>
+}
+
+impl Default for Composition {
+    fn default() -> Self {
+        Self {
+            blocks:     Vec::new(),
+            padding:    8.0,
+            gap:        4.0,
+            scale:      1.0,
+            background: None
+        }
+    }
+}
+
+/// A block with its size and the centre of its (rotated) box, in
+/// composition units.
+struct Laid<'a> {
+    block:  &'a Block,
+    lines:  Vec<&'a str>,
+    width:  f64,
+    height: f64,
+    cx:     f64,
+    cy:     f64
+}
+
+/// Result of `Composition::layout`.
+struct Layout<'a> {
+    /// Top-left of the ink's bounding box, canvas px.
+    ink_origin: (f64, f64),
+    /// Shift from layout space (ink box at 0,0) into the output.
+    offset:     (f64, f64),
+    /// Output size, composition units.
+    size:       (f64, f64),
+    blocks:     Vec<Laid<'a>>
+}
+
+impl Composition {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Signer's printed name and the signing date (`YYYY-MM-DD`, UTC) under
+    /// the ink.
+    pub fn name_and_date(envelope: &SignedEnvelope) -> Self {
+        let date = envelope.signed_at.get(..10).unwrap_or(&envelope.signed_at);
+        let mut composition = Self::default();
+        if !envelope.signer.name.is_empty() {
+            composition.blocks.push(Block::text(
+                envelope.signer.name.clone(),
+                TextStyle::default()
+            ));
+        }
+        composition.blocks.push(Block::text(
+            date,
+            TextStyle {
+                size: 12.0,
+                color: "#444444".into(),
+                ..Default::default()
+            }
+        ));
+        composition
+    }
+
+    /// Render to a PNG data URL. Async: stamp images are loaded first
+    /// (cross-origin ones need CORS, or the canvas can't be exported).
+    pub async fn to_png_data_url(&self, strokes: &[Stroke]) -> Result<String, SigError> {
+        let mut images = Vec::new();
+        for block in &self.blocks {
+            if let BlockContent::Image {
+                src, ..
+            } = &block.content
+            {
+                images.push(Some(load_image(src).await?));
+            } else {
+                images.push(None);
+            }
+        }
+
+        let (_, measure) = offscreen_canvas(1, 1)?;
+        let layout = self.layout(strokes, &measure)?;
+        let (w, h) = self.pixel_size(layout.size);
+        let scale = self.output_scale(layout.size);
+        let (canvas, ctx) = offscreen_canvas(w, h)?;
+        ctx.scale(scale, scale)
+            .map_err(|_| SigError::OpFailed("scale".into()))?;
+        if let Some(bg) = &self.background {
+            ctx.set_fill_style_str(bg);
+            ctx.fill_rect(0.0, 0.0, layout.size.0, layout.size.1);
+        }
+
+        // Ink first, from canvas px into the output
+        ctx.save();
+        let _ = ctx.translate(
+            layout.offset.0 - layout.ink_origin.0,
+            layout.offset.1 - layout.ink_origin.1
+        );
+        for stroke in strokes {
+            draw_stroke(&ctx, stroke);
+        }
+        ctx.restore();
+
+        for (laid, image) in layout.blocks.iter().zip(&images) {
+            ctx.save();
+            let _ = ctx.translate(laid.cx, laid.cy);
+            let _ = ctx.rotate(laid.block.rotation.to_radians());
+            let (left, top) = (-laid.width / 2.0, -laid.height / 2.0);
+            match (&laid.block.content, image) {
+                (
+                    BlockContent::Text {
+                        style, ..
+                    },
+                    _
+                ) => {
+                    ctx.set_font(&style.css());
+                    ctx.set_fill_style_str(&style.color);
+                    ctx.set_text_baseline("alphabetic");
+                    let (x, anchor) = line_anchor(laid.block.align, laid.width);
+                    ctx.set_text_align(anchor);
+                    for (i, line) in laid.lines.iter().enumerate() {
+                        let y = top + (i as f64 * LINE_HEIGHT + ASCENT) * style.size;
+                        let _ = ctx.fill_text(line, x, y);
+                    }
+                }
+                (
+                    BlockContent::Image {
+                        opacity, ..
+                    },
+                    Some(img)
+                ) => {
+                    ctx.set_global_alpha(opacity.clamp(0.0, 1.0));
+                    let _ = ctx.draw_image_with_html_image_element_and_dw_and_dh(
+                        img,
+                        left,
+                        top,
+                        laid.width,
+                        laid.height
+                    );
+                }
+                _ => {}
+            }
+            ctx.restore();
+        }
+
+        canvas
+            .to_data_url()
+            .map_err(|_| SigError::OpFailed("to_data_url (tainted by a stamp image?)".into()))
+    }
+
+    /// Render to a standalone SVG document. Images are referenced by `src`
+    /// (use data URLs for a self-contained file).
+    pub fn to_svg(&self, strokes: &[Stroke]) -> Result<String, SigError> {
+        let (_, measure) = offscreen_canvas(1, 1)?;
+        let layout = self.layout(strokes, &measure)?;
+        let (w, h) = self.pixel_size(layout.size);
+        let mut out = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 \
+             {} {}\">\n",
+            num(layout.size.0),
+            num(layout.size.1)
+        );
+        if let Some(bg) = &self.background {
+            let _ = writeln!(
+                out,
+                "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>",
+                svg_escape(bg)
+            );
+        }
+        let _ = write!(
+            out,
+            "<g transform=\"translate({} {})\">\n{}</g>\n",
+            num(layout.offset.0 - layout.ink_origin.0),
+            num(layout.offset.1 - layout.ink_origin.1),
+            svg_strokes(strokes)
+        );
+        for laid in &layout.blocks {
+            let _ = writeln!(
+                out,
+                "<g transform=\"translate({} {}) rotate({})\">",
+                num(laid.cx),
+                num(laid.cy),
+                num(laid.block.rotation)
+            );
+            let (left, top) = (-laid.width / 2.0, -laid.height / 2.0);
+            match &laid.block.content {
+                BlockContent::Text {
+                    style, ..
+                } => {
+                    let (x, anchor) = line_anchor(laid.block.align, laid.width);
+                    let anchor = match anchor {
+                        "left" => "start",
+                        "right" => "end",
+                        _ => "middle"
+                    };
+                    for (i, line) in laid.lines.iter().enumerate() {
+                        let y = top + (i as f64 * LINE_HEIGHT + ASCENT) * style.size;
+                        let _ = writeln!(
+                            out,
+                            "<text x=\"{}\" y=\"{}\" font-family=\"{}\" font-size=\"{}\" \
+                             font-weight=\"{}\" font-style=\"{}\" fill=\"{}\" \
+                             text-anchor=\"{anchor}\">{}</text>",
+                            num(x),
+                            num(y),
+                            svg_escape(&style.family),
+                            num(style.size),
+                            style.weight,
+                            if style.italic { "italic" } else { "normal" },
+                            svg_escape(&style.color),
+                            svg_escape(line)
+                        );
+                    }
+                }
+                BlockContent::Image {
+                    src,
+                    opacity,
+                    ..
+                } => {
+                    let _ = writeln!(
+                        out,
+                        "<image href=\"{}\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" \
+                         opacity=\"{}\" preserveAspectRatio=\"none\"/>",
+                        svg_escape(src),
+                        num(left),
+                        num(top),
+                        num(laid.width),
+                        num(laid.height),
+                        num(opacity.clamp(0.0, 1.0))
+                    );
+                }
+            }
+            out.push_str("</g>\n");
+        }
+        out.push_str("</svg>\n");
+        Ok(out)
+    }
+
+    /// `to_svg` as a `data:image/svg+xml` URL.
+    pub fn to_svg_data_url(&self, strokes: &[Stroke]) -> Result<String, SigError> {
+        let svg = self.to_svg(strokes)?;
+        let mut url = String::from("data:image/svg+xml;charset=utf-8,");
+        for b in svg.bytes() {
+            if b.is_ascii_alphanumeric() || b"-_.~".contains(&b) {
+                url.push(b as char);
+            } else {
+                let _ = write!(url, "%{b:02X}");
+            }
+        }
+        Ok(url)
+    }
+
+    /// Output px per composition unit: `scale`, reduced so that a
+    /// composition of `size` fits within `MAX_SIDE` instead of being cut off.
+    fn output_scale(&self, size: (f64, f64)) -> f64 {
+        let largest = size.0.max(size.1);
+        if largest * self.scale > MAX_SIDE {
+            MAX_SIDE / largest
+        } else {
+            self.scale
+        }
+    }
+
+    /// Output size of a composition of `size`, px.
+    fn pixel_size(&self, size: (f64, f64)) -> (u32, u32) {
+        let scale = self.output_scale(size);
+        let px = |v: f64| (v * scale).ceil().clamp(1.0, MAX_SIDE) as u32;
+        (px(size.0), px(size.1))
+    }
+
+    /// Trim the ink and place the blocks around it; `measure` sizes text.
+    fn layout<'a>(
+        &'a self,
+        strokes: &[Stroke],
+        measure: &CanvasRenderingContext2d
+    ) -> Result<Layout<'a>, SigError> {
+        let (ink_origin, ink_w, ink_h) = ink_bounds(strokes)
+            .ok_or_else(|| SigError::InvalidState("nothing to compose".into()))?;
+
+        // Cursors: next free coordinate outwards from each side of the ink
+        let (mut top, mut bottom) = (-self.gap, ink_h + self.gap);
+        let (mut left, mut right) = (-self.gap, ink_w + self.gap);
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (0.0_f64, 0.0_f64, ink_w, ink_h);
+        let along = |align: Align, span: f64, size: f64| match align {
+            Align::Start => 0.0,
+            Align::Center => (span - size) / 2.0,
+            Align::End => span - size
+        };
+
+        let mut blocks = Vec::with_capacity(self.blocks.len());
+        for block in &self.blocks {
+            let (lines, width, height) = match &block.content {
+                BlockContent::Text {
+                    text,
+                    style
+                } => {
+                    measure.set_font(&style.css());
+                    let lines: Vec<&str> = text.lines().collect();
+                    let width = lines
+                        .iter()
+                        .filter_map(|l| measure.measure_text(l).ok())
+                        .map(|m| m.width())
+                        .fold(0.0, f64::max);
+                    let height = lines.len().max(1) as f64 * LINE_HEIGHT * style.size;
+                    (lines, width, height)
+                }
+                BlockContent::Image {
+                    width,
+                    height,
+                    ..
+                } => (Vec::new(), width.max(0.0), height.max(0.0))
+            };
+            let (sin, cos) = block.rotation.to_radians().sin_cos();
+            let bw = (width * cos).abs() + (height * sin).abs();
+            let bh = (width * sin).abs() + (height * cos).abs();
+            let (x, y) = match block.side {
+                Side::Top => {
+                    top -= bh;
+                    let y = top;
+                    top -= self.gap;
+                    (along(block.align, ink_w, bw), y)
+                }
+                Side::Bottom => {
+                    let y = bottom;
+                    bottom += bh + self.gap;
+                    (along(block.align, ink_w, bw), y)
+                }
+                Side::Left => {
+                    left -= bw;
+                    let x = left;
+                    left -= self.gap;
+                    (x, along(block.align, ink_h, bh))
+                }
+                Side::Right => {
+                    let x = right;
+                    right += bw + self.gap;
+                    (x, along(block.align, ink_h, bh))
+                }
+                Side::Over => (along(block.align, ink_w, bw), along(block.align, ink_h, bh))
+            };
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x + bw);
+            max_y = max_y.max(y + bh);
+            blocks.push(Laid {
+                block,
+                lines,
+                width,
+                height,
+                cx: x + bw / 2.0,
+                cy: y + bh / 2.0
+            });
+        }
+
+        let offset = (self.padding - min_x, self.padding - min_y);
+        for laid in &mut blocks {
+            laid.cx += offset.0;
+            laid.cy += offset.1;
+        }
+        Ok(Layout {
+            ink_origin,
+            offset,
+            size: (
+                max_x - min_x + 2.0 * self.padding,
+                max_y - min_y + 2.0 * self.padding
+            ),
+            blocks
+        })
+    }
+}
+
+/// Top-left and size of the ink, pen width included; `None` if empty.
+fn ink_bounds(strokes: &[Stroke]) -> Option<((f64, f64), f64, f64)> {
+    let mut bounds: Option<(f64, f64, f64, f64)> = None;
+    for stroke in strokes {
+        let half = stroke.pen.clone().unwrap_or_default().max_width / 2.0;
+        for p in &stroke.points {
+            let (x0, y0, x1, y1) = bounds.unwrap_or((p.x, p.y, p.x, p.y));
+            bounds = Some((
+                x0.min(p.x - half),
+                y0.min(p.y - half),
+                x1.max(p.x + half),
+                y1.max(p.y + half)
+            ));
+        }
+    }
+    bounds.map(|(x0, y0, x1, y1)| ((x0, y0), x1 - x0, y1 - y0))
+}
+
+/// X of the text anchor within a block of `width` centred on 0, and the
+/// canvas `textAlign` for it.
+fn line_anchor(align: Align, width: f64) -> (f64, &'static str) {
+    match align {
+        Align::Start => (-width / 2.0, "left"),
+        Align::Center => (0.0, "center"),
+        Align::End => (width / 2.0, "right")
+    }
+}
+
+/// Load `src` into an `<img>` and wait for it.
+async fn load_image(src: &str) -> Result<HtmlImageElement, SigError> {
+    let img = HtmlImageElement::new().map_err(|_| SigError::OpFailed("new Image".into()))?;
+    img.set_cross_origin(Some("anonymous"));
+    let loaded = js_sys::Promise::new(&mut |resolve, reject| {
+        img.set_onload(Some(&resolve));
+        img.set_onerror(Some(&reject));
+    });
+    img.set_src(src);
+    JsFuture::from(loaded)
+        .await
+        .map_err(|_| SigError::OpFailed(format!("load image {src}")))?;
+    img.set_onload(None);
+    img.set_onerror(None);
+    Ok(img)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signature_core::{PenStyle, StrokePoint};
+
+    fn stroke(points: &[(f64, f64)]) -> Stroke {
+        Stroke {
+            points: points
+                .iter()
+                .map(|&(x, y)| StrokePoint {
+                    x,
+                    y,
+                    t: 0.0,
+                    pressure: 0.5
+                })
+                .collect(),
+            pen:    None
+        }
+    }
+
+    #[test]
+    fn scale_is_kept_when_the_output_fits() {
+        let composition = Composition {
+            scale: 2.0,
+            ..Default::default()
+        };
+        assert_eq!(composition.output_scale((300.0, 120.0)), 2.0);
+        assert_eq!(composition.pixel_size((300.0, 120.5)), (600, 241));
+    }
+
+    #[test]
+    fn oversized_output_is_scaled_down_not_cropped() {
+        let composition = Composition {
+            scale: 4.0,
+            ..Default::default()
+        };
+        // 4000 x 1000 at 4x would be 16000 px wide
+        let (w, h) = composition.pixel_size((4000.0, 1000.0));
+        assert_eq!((w, h), (8192, 2048));
+        assert_eq!(composition.output_scale((4000.0, 1000.0)), 8192.0 / 4000.0);
+    }
+
+    #[test]
+    fn ink_bounds_include_the_pen_width() {
+        let strokes = [stroke(&[(10.0, 20.0), (50.0, 40.0)]), stroke(&[])];
+        let half = PenStyle::default().max_width / 2.0;
+        let ((x, y), w, h) = ink_bounds(&strokes).unwrap();
+        assert_eq!((x, y), (10.0 - half, 20.0 - half));
+        assert_eq!((w, h), (40.0 + 2.0 * half, 20.0 + 2.0 * half));
+        assert!(ink_bounds(&[stroke(&[])]).is_none());
+    }
+}
@@ -0,0 +1,52 @@
+// src/consent.rs
+
+//! Explicit consent to sign electronically: the checkbox shown before
+//! signing and the record of what was agreed to, kept in the envelope.
+
+use serde::{Deserialize, Serialize};
+
+/// Link shown after the consent text (terms, e-signature disclosure, ...).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ConsentLink {
+    pub label: String,
+    pub url:   String
+}
+
+/// Consent checkbox in the signing modal.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConsentStep {
+    /// Checkbox label, e.g. "I agree to sign this contract electronically".
+    pub text:     String,
+    pub link:     Option<ConsentLink>,
+    /// Confirm stays disabled until the box is ticked. If `false`, the box is
+    /// optional and recorded only when ticked.
+    pub required: bool
+}
+
+impl ConsentStep {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text:     text.into(),
+            link:     None,
+            required: true
+        }
+    }
+
+    /// What the signer agreed to, stamped with the current time.
+    pub fn record(&self) -> ConsentRecord {
+        ConsentRecord {
+            text:     self.text.clone(),
+            link:     self.link.clone(),
+            given_at: String::from(js_sys::Date::new_0().to_iso_string())
+        }
+    }
+}
+
+/// Consent as given, carried in the signed envelope.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ConsentRecord {
+    pub text:     String,
+    pub link:     Option<ConsentLink>,
+    /// ISO 8601 UTC.
+    pub given_at: String
+}
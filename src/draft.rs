@@ -0,0 +1,403 @@
+// src/draft.rs
+
+//! Drafts: in-progress strokes and the placed signature, persisted so a
+//! closed modal or a page reload doesn't lose them.
+//!
+//! Storage is pluggable through [`DraftStorage`]; [`DraftStore::browser`]
+//! uses IndexedDB and falls back to `localStorage` where IndexedDB is
+//! unavailable (e.g. some private modes).
+
+use std::{cell::RefCell, future::Future, pin::Pin, rc::Rc};
+
+use js_sys::{Array, Promise};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{IdbDatabase, IdbOpenDbRequest, IdbRequest, IdbTransactionMode, Storage};
+
+use crate::{envelope::SignedEnvelope, error::SigError, signature_core::Stroke};
+
+/// Prefix of every draft key.
+const KEY_PREFIX: &str = "contract-signature:draft:";
+const DB_NAME: &str = "contract-signature";
+const DB_STORE: &str = "drafts";
+
+/// Future returned by [`DraftStorage`] methods.
+pub type StorageFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, SigError>> + 'a>>;
+
+/// String key/value backend for drafts.
+pub trait DraftStorage {
+    fn get<'a>(&'a self, key: &'a str) -> StorageFuture<'a, Option<String>>;
+
+    fn set<'a>(&'a self, key: &'a str, value: String) -> StorageFuture<'a, ()>;
+
+    fn remove<'a>(&'a self, key: &'a str) -> StorageFuture<'a, ()>;
+
+    /// All stored keys (including ones not written by this crate).
+    fn keys(&self) -> StorageFuture<'_, Vec<String>>;
+}
+
+/// Saved state of one signature field.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Draft {
+    /// Strokes drawn so far (restored into the pad when it opens).
+    pub strokes:  Vec<Stroke>,
+    /// Confirmed envelope, if the signature was already placed; its
+    /// `placement` is where the image goes back on restore.
+    pub envelope: Option<SignedEnvelope>,
+    /// Milliseconds since the Unix epoch.
+    pub saved_at: f64
+}
+
+/// Placed signature brought back from a draft. Drafts live in storage any
+/// script on the origin can write, so the image, placement and audit trail
+/// are unverified: not a fresh signature, and not proof of one until checked
+/// (e.g. against a seal) server-side.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RestoredEnvelope {
+    pub envelope: SignedEnvelope,
+    /// When the draft was saved, ms since the Unix epoch.
+    pub saved_at: f64
+}
+
+impl Draft {
+    pub fn is_empty(&self) -> bool {
+        self.envelope.is_none() && self.strokes.iter().all(|s| s.points.is_empty())
+    }
+}
+
+/// Drafts keyed by contract container and field id, over a `DraftStorage`.
+#[derive(Clone)]
+pub struct DraftStore {
+    backend:    Rc<dyn DraftStorage>,
+    /// Drafts older than this (ms) are dropped on load; `None` keeps them.
+    pub ttl_ms: Option<f64>
+}
+
+impl PartialEq for DraftStore {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.backend, &other.backend) && self.ttl_ms == other.ttl_ms
+    }
+}
+
+impl DraftStore {
+    pub fn new(backend: Rc<dyn DraftStorage>) -> Self {
+        Self {
+            backend,
+            ttl_ms: None
+        }
+    }
+
+    /// IndexedDB, falling back to `localStorage`.
+    pub fn browser() -> Self {
+        Self::new(Rc::new(BrowserStorage::default()))
+    }
+
+    /// Storage key of a field's draft.
+    pub fn key(contract_id: &str, field_id: Option<&str>) -> String {
+        format!("{KEY_PREFIX}{contract_id}:{}", field_id.unwrap_or(""))
+    }
+
+    /// Store `draft` (stamped with the current time); an empty draft
+    /// removes the stored one instead.
+    pub async fn save(
+        &self,
+        contract_id: &str,
+        field_id: Option<&str>,
+        mut draft: Draft
+    ) -> Result<(), SigError> {
+        let key = Self::key(contract_id, field_id);
+        if draft.is_empty() {
+            return self.backend.remove(&key).await;
+        }
+        draft.saved_at = js_sys::Date::now();
+        let json = serde_json::to_string(&draft)
+            .map_err(|e| SigError::OpFailed(format!("serialize draft: {e}")))?;
+        self.backend.set(&key, json).await
+    }
+
+    /// Stored draft, `None` if there is none, it can't be parsed or it has
+    /// expired (expired and broken drafts are removed).
+    pub async fn load(
+        &self,
+        contract_id: &str,
+        field_id: Option<&str>
+    ) -> Result<Option<Draft>, SigError> {
+        let key = Self::key(contract_id, field_id);
+        let Some(json) = self.backend.get(&key).await? else {
+            return Ok(None);
+        };
+        match serde_json::from_str::<Draft>(&json) {
+            Ok(draft) if !self.is_expired(&draft) => Ok(Some(draft)),
+            _ => {
+                self.backend.remove(&key).await?;
+                Ok(None)
+            }
+        }
+    }
+
+    pub async fn clear(&self, contract_id: &str, field_id: Option<&str>) -> Result<(), SigError> {
+        self.backend.remove(&Self::key(contract_id, field_id)).await
+    }
+
+    /// Remove every draft of this crate. Returns how many were removed.
+    pub async fn clear_all(&self) -> Result<usize, SigError> {
+        let keys = self.draft_keys().await?;
+        for key in &keys {
+            self.backend.remove(key).await?;
+        }
+        Ok(keys.len())
+    }
+
+    /// Remove drafts past `ttl_ms`. Returns how many were removed.
+    pub async fn clear_expired(&self) -> Result<usize, SigError> {
+        let mut removed = 0;
+        for key in self.draft_keys().await? {
+            let expired = match self.backend.get(&key).await? {
+                Some(json) => serde_json::from_str::<Draft>(&json)
+                    .map_or(true, |draft| self.is_expired(&draft)),
+                None => false
+            };
+            if expired {
+                self.backend.remove(&key).await?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    fn is_expired(&self, draft: &Draft) -> bool {
+        self.ttl_ms
+            .is_some_and(|ttl| js_sys::Date::now() - draft.saved_at > ttl)
+    }
+
+    async fn draft_keys(&self) -> Result<Vec<String>, SigError> {
+        let mut keys = self.backend.keys().await?;
+        keys.retain(|k| k.starts_with(KEY_PREFIX));
+        Ok(keys)
+    }
+}
+
+/// `window.localStorage`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LocalStorage;
+
+impl LocalStorage {
+    fn storage() -> Result<Storage, SigError> {
+        web_sys::window()
+            .ok_or(SigError::DomUnavailable)?
+            .local_storage()
+            .ok()
+            .flatten()
+            .ok_or_else(|| SigError::OpFailed("localStorage unavailable".into()))
+    }
+
+    fn get_now(key: &str) -> Result<Option<String>, SigError> {
+        Self::storage()?
+            .get_item(key)
+            .map_err(|_| SigError::OpFailed("localStorage.getItem".into()))
+    }
+
+    fn set_now(key: &str, value: &str) -> Result<(), SigError> {
+        Self::storage()?
+            .set_item(key, value)
+            .map_err(|_| SigError::OpFailed("localStorage.setItem (quota?)".into()))
+    }
+
+    fn remove_now(key: &str) -> Result<(), SigError> {
+        Self::storage()?
+            .remove_item(key)
+            .map_err(|_| SigError::OpFailed("localStorage.removeItem".into()))
+    }
+
+    fn keys_now() -> Result<Vec<String>, SigError> {
+        let storage = Self::storage()?;
+        let len = storage.length().unwrap_or(0);
+        Ok((0..len)
+            .filter_map(|i| storage.key(i).ok().flatten())
+            .collect())
+    }
+}
+
+impl DraftStorage for LocalStorage {
+    fn get<'a>(&'a self, key: &'a str) -> StorageFuture<'a, Option<String>> {
+        Box::pin(async move { Self::get_now(key) })
+    }
+
+    fn set<'a>(&'a self, key: &'a str, value: String) -> StorageFuture<'a, ()> {
+        Box::pin(async move { Self::set_now(key, &value) })
+    }
+
+    fn remove<'a>(&'a self, key: &'a str) -> StorageFuture<'a, ()> {
+        Box::pin(async move { Self::remove_now(key) })
+    }
+
+    fn keys(&self) -> StorageFuture<'_, Vec<String>> {
+        Box::pin(async move { Self::keys_now() })
+    }
+}
+
+/// IndexedDB database `contract-signature`, object store `drafts`.
+#[derive(Default)]
+pub struct IndexedDb {
+    db: RefCell<Option<IdbDatabase>>
+}
+
+impl IndexedDb {
+    async fn database(&self) -> Result<IdbDatabase, SigError> {
+        if let Some(db) = self.db.borrow().clone() {
+            return Ok(db);
+        }
+        let factory = web_sys::window()
+            .ok_or(SigError::DomUnavailable)?
+            .indexed_db()
+            .ok()
+            .flatten()
+            .ok_or_else(|| SigError::OpFailed("indexedDB unavailable".into()))?;
+        let request: IdbOpenDbRequest = factory
+            .open_with_u32(DB_NAME, 1)
+            .map_err(|_| SigError::OpFailed("indexedDB.open".into()))?;
+
+        // First open: create the store; kept alive until the open settles
+        let upgrade = {
+            let request = request.clone();
+            Closure::<dyn FnMut(web_sys::Event)>::new(move |_| {
+                if let Ok(db) = request.result().and_then(|r| r.dyn_into::<IdbDatabase>()) {
+                    let _ = db.create_object_store(DB_STORE);
+                }
+            })
+        };
+        request.set_onupgradeneeded(Some(upgrade.as_ref().unchecked_ref()));
+        let result = settle(&request).await;
+        request.set_onupgradeneeded(None);
+        drop(upgrade);
+
+        let db: IdbDatabase = result?
+            .dyn_into()
+            .map_err(|_| SigError::OpFailed("indexedDB.open result".into()))?;
+        *self.db.borrow_mut() = Some(db.clone());
+        Ok(db)
+    }
+
+    async fn run(
+        &self,
+        mode: IdbTransactionMode,
+        op: impl FnOnce(&web_sys::IdbObjectStore) -> Result<IdbRequest, JsValue>
+    ) -> Result<JsValue, SigError> {
+        let db = self.database().await?;
+        let store = db
+            .transaction_with_str_and_mode(DB_STORE, mode)
+            .and_then(|tx| tx.object_store(DB_STORE))
+            .map_err(|_| SigError::OpFailed("indexedDB transaction".into()))?;
+        let request = op(&store).map_err(|_| SigError::OpFailed("indexedDB request".into()))?;
+        settle(&request).await
+    }
+}
+
+impl DraftStorage for IndexedDb {
+    fn get<'a>(&'a self, key: &'a str) -> StorageFuture<'a, Option<String>> {
+        Box::pin(async move {
+            let value = self
+                .run(IdbTransactionMode::Readonly, |s| {
+                    s.get(&JsValue::from_str(key))
+                })
+                .await?;
+            Ok(value.as_string())
+        })
+    }
+
+    fn set<'a>(&'a self, key: &'a str, value: String) -> StorageFuture<'a, ()> {
+        Box::pin(async move {
+            self.run(IdbTransactionMode::Readwrite, |s| {
+                s.put_with_key(&JsValue::from_str(&value), &JsValue::from_str(key))
+            })
+            .await
+            .map(|_| ())
+        })
+    }
+
+    fn remove<'a>(&'a self, key: &'a str) -> StorageFuture<'a, ()> {
+        Box::pin(async move {
+            self.run(IdbTransactionMode::Readwrite, |s| {
+                s.delete(&JsValue::from_str(key))
+            })
+            .await
+            .map(|_| ())
+        })
+    }
+
+    fn keys(&self) -> StorageFuture<'_, Vec<String>> {
+        Box::pin(async move {
+            let keys = self
+                .run(IdbTransactionMode::Readonly, |s| s.get_all_keys())
+                .await?;
+            Ok(keys
+                .dyn_into::<Array>()
+                .map(|a| a.iter().filter_map(|k| k.as_string()).collect())
+                .unwrap_or_default())
+        })
+    }
+}
+
+/// IndexedDB until it fails once, `localStorage` from then on.
+#[derive(Default)]
+pub struct BrowserStorage {
+    indexed_db: IndexedDb,
+    fallback:   std::cell::Cell<bool>
+}
+
+impl BrowserStorage {
+    async fn with_fallback<T>(
+        &self,
+        primary: StorageFuture<'_, T>,
+        fallback: impl FnOnce() -> Result<T, SigError>
+    ) -> Result<T, SigError> {
+        if !self.fallback.get() {
+            match primary.await {
+                Ok(v) => return Ok(v),
+                Err(_) => self.fallback.set(true)
+            }
+        }
+        fallback()
+    }
+}
+
+impl DraftStorage for BrowserStorage {
+    fn get<'a>(&'a self, key: &'a str) -> StorageFuture<'a, Option<String>> {
+        Box::pin(self.with_fallback(self.indexed_db.get(key), || LocalStorage::get_now(key)))
+    }
+
+    fn set<'a>(&'a self, key: &'a str, value: String) -> StorageFuture<'a, ()> {
+        let copy = value.clone();
+        Box::pin(
+            self.with_fallback(self.indexed_db.set(key, value), move || {
+                LocalStorage::set_now(key, &copy)
+            })
+        )
+    }
+
+    fn remove<'a>(&'a self, key: &'a str) -> StorageFuture<'a, ()> {
+        Box::pin(self.with_fallback(self.indexed_db.remove(key), || {
+            LocalStorage::remove_now(key)
+        }))
+    }
+
+    fn keys(&self) -> StorageFuture<'_, Vec<String>> {
+        Box::pin(self.with_fallback(self.indexed_db.keys(), LocalStorage::keys_now))
+    }
+}
+
+/// Wait for an IndexedDB request to succeed; resolves to its `result`.
+async fn settle(request: &IdbRequest) -> Result<JsValue, SigError> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        request.set_onsuccess(Some(&resolve));
+        request.set_onerror(Some(&reject));
+    });
+    let outcome = JsFuture::from(promise).await;
+    request.set_onsuccess(None);
+    request.set_onerror(None);
+    outcome.map_err(|_| SigError::OpFailed("indexedDB request failed".into()))?;
+    request
+        .result()
+        .map_err(|_| SigError::OpFailed("indexedDB result".into()))
+}
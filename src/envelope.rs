@@ -0,0 +1,174 @@
+// src/envelope.rs
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    audit::AuditEntry,
+    compose::Composition,
+    consent::ConsentRecord,
+    error::SigError,
+    pdf::SignatureImage,
+    placement::PlacementGeometry,
+    profile::SignatureOrigin,
+    risk::{assess_strokes, RiskAssessment},
+    signature_core::{base64_decode, SignaturePad, Stroke},
+    similarity::SimilarityReport,
+    ui_common::DomBindings
+};
+
+/// Who signed. Carried verbatim into the envelope.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SignerMetadata {
+    pub name:  String,
+    pub email: Option<String>,
+    /// Application-level identifier (account id, employee number, ...).
+    pub id:    Option<String>
+}
+
+/// Evidence of what was signed: binds the signature to the exact contract
+/// text that was shown to the signer.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SignedEnvelope {
+    /// SHA-256 (hex) of the canonicalized contract text.
+    pub document_hash:        String,
+    /// SHA-256 (hex) of the exported PNG bytes.
+    pub signature_image_hash: String,
+    /// SHA-256 (hex) of the JSON-serialized strokes.
+    pub stroke_data_hash:     String,
+    /// ISO 8601 UTC timestamp of confirmation.
+    pub signed_at:            String,
+    pub signer:               SignerMetadata,
+    /// Field the signature was placed into (`None` for single-field pages).
+    #[serde(default)]
+    pub field_id:             Option<String>,
+    /// Where the image ended up inside the contract container.
+    #[serde(default)]
+    pub placement:            Option<PlacementGeometry>,
+    /// Initials anchors the signature was stamped into (initials mode).
+    #[serde(default)]
+    pub initials_anchors:     Vec<String>,
+    /// PNG data URL of the signature (for `<img src="...">`).
+    pub data_url:             String,
+    /// `data_url` is a `Composition` render (ink plus text blocks and
+    /// stamps), not the bare pad export; PDFs embed it instead of redrawing
+    /// `strokes`.
+    #[serde(default)]
+    pub composed:             bool,
+    /// Raw stroke data the `stroke_data_hash` was computed from.
+    pub strokes:              Vec<Stroke>,
+    /// Session audit log up to confirmation (see `verify_audit_chain`).
+    #[serde(default)]
+    pub audit_trail:          Vec<AuditEntry>,
+    /// Comparison against the signer's enrolled reference, if one was given.
+    #[serde(default)]
+    pub similarity:           Option<SimilarityReport>,
+    /// Synthetic-input heuristics over the strokes.
+    #[serde(default)]
+    pub risk:                 RiskAssessment,
+    /// Drawn for this contract or reused from a saved profile.
+    #[serde(default)]
+    pub origin:               SignatureOrigin,
+    /// Consent to sign electronically, if the modal asked for it.
+    #[serde(default)]
+    pub consent:              Option<ConsentRecord>
+}
+
+impl SignedEnvelope {
+    /// Build an envelope from the current pad state and the text of
+    /// `container_id`.
+    pub fn capture(
+        pad: &SignaturePad,
+        container_id: &str,
+        signer: SignerMetadata
+    ) -> Result<Self, SigError> {
+        let text = DomBindings::contract_text(container_id)?;
+        let data_url = pad.to_png_data_url()?;
+        let png = pad.to_png_bytes()?;
+        let strokes = pad.strokes().to_vec();
+        let risk = assess_strokes(&strokes, pad.untrusted_events());
+
+        Ok(Self {
+            document_hash: sha256_hex(canonicalize_text(&text).as_bytes()),
+            signature_image_hash: sha256_hex(&png),
+            stroke_data_hash: hash_strokes(&strokes)?,
+            signed_at: String::from(js_sys::Date::new_0().to_iso_string()),
+            signer,
+            field_id: None,
+            placement: None,
+            initials_anchors: Vec::new(),
+            data_url,
+            composed: false,
+            strokes,
+            audit_trail: Vec::new(),
+            similarity: None,
+            risk,
+            origin: SignatureOrigin::Drawn,
+            consent: None
+        })
+    }
+
+    /// Replace the pad export with `composition` rendered around the
+    /// strokes and re-hash it, so the placed image, PDFs and
+    /// `signature_image_hash` all show the composed signature.
+    pub async fn compose(&mut self, composition: &Composition) -> Result<(), SigError> {
+        let data_url = composition.to_png_data_url(&self.strokes).await?;
+        self.data_url = data_url;
+        self.signature_image_hash = sha256_hex(&self.png_bytes()?);
+        self.composed = true;
+        Ok(())
+    }
+
+    /// Width and height of the `data_url` image, px.
+    pub fn image_size(&self) -> Result<(u32, u32), SigError> {
+        let image = SignatureImage::from_png(&self.png_bytes()?)?;
+        Ok((image.width, image.height))
+    }
+
+    /// Decoded `data_url`.
+    pub(crate) fn png_bytes(&self) -> Result<Vec<u8>, SigError> {
+        let b64 = self
+            .data_url
+            .strip_prefix("data:image/png;base64,")
+            .ok_or_else(|| SigError::OpFailed("unexpected data URL".into()))?;
+        base64_decode(b64)
+    }
+
+    /// Byte serialization used for detached seals. Seals keep these bytes
+    /// (`EnvelopeSeal::payload`), since later versions may serialize more
+    /// fields.
+    pub fn to_canonical_json(&self) -> Result<Vec<u8>, SigError> {
+        serde_json::to_vec(self)
+            .map_err(|e| SigError::OpFailed(format!("serialize envelope: {e}")))
+    }
+
+    /// Does `document_hash` match the given contract text?
+    pub fn matches_document(&self, text: &str) -> bool {
+        self.document_hash == sha256_hex(canonicalize_text(text).as_bytes())
+    }
+}
+
+/// Canonical form of contract text used for hashing: every run of
+/// whitespace (including line breaks and NBSP) collapses to a single space,
+/// leading/trailing whitespace is dropped. Makes the hash independent of
+/// markup indentation and line endings.
+pub fn canonicalize_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Lowercase hex SHA-256 digest.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    to_hex(&Sha256::digest(bytes))
+}
+
+/// Lowercase hex encoding.
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// SHA-256 (hex) of strokes serialized as JSON.
+pub fn hash_strokes(strokes: &[Stroke]) -> Result<String, SigError> {
+    let json = serde_json::to_vec(strokes)
+        .map_err(|e| SigError::OpFailed(format!("serialize strokes: {e}")))?;
+    Ok(sha256_hex(&json))
+}
@@ -9,5 +9,9 @@ pub enum SigError {
     #[error("Canvas context unavailable")]
     NoContext2d,
     #[error("Operation failed: {0}")]
-    OpFailed(String)
+    OpFailed(String),
+    #[error("Crypto operation failed: {0}")]
+    Crypto(String),
+    #[error("Invalid state: {0}")]
+    InvalidState(String)
 }
@@ -0,0 +1,30 @@
+// src/format.rs
+
+//! Number formatting shared by the PDF, SVG and composition writers.
+
+/// Compact decimal for content streams and SVG attributes: at most three
+/// fractional digits, no trailing zeros, no `-0`.
+pub(crate) fn num(v: f64) -> String {
+    let s = format!("{v:.3}");
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" {
+        "0".into()
+    } else {
+        s.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn num_is_compact() {
+        assert_eq!(num(12.0), "12");
+        assert_eq!(num(0.5), "0.5");
+        assert_eq!(num(1.23456), "1.235");
+        assert_eq!(num(-2.25), "-2.25");
+        assert_eq!(num(-0.0001), "0");
+        assert_eq!(num(100.0), "100");
+    }
+}
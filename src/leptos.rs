@@ -1,51 +1,293 @@
 #[cfg(feature = "leptos")]
-use leptos::{
-    ev, html::Canvas, node_ref::NodeRef, IntoView, RwSignal, SignalGet, SignalSet, View
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc
 };
 
 #[cfg(feature = "leptos")]
-use crate::error::SigError;
+use leptos::{html::Canvas, prelude::*};
 #[cfg(feature = "leptos")]
-use crate::signature_core::SignaturePad;
+use send_wrapper::SendWrapper;
 
+#[cfg(feature = "leptos")]
+use crate::{
+    audit::{AuditEntry, AuditEvent, AuditLog},
+    consent::{ConsentRecord, ConsentStep},
+    error::SigError,
+    profile::{ProfileStore, SavedSignature, SignatureOrigin},
+    reading::{ReadGate, ReadProgress, ReadTracker},
+    signature_core::SignaturePad,
+    ui_common::{DomBindings, FieldIds}
+};
+
+/// Drawing canvas size, px.
+#[cfg(feature = "leptos")]
+const CANVAS_SIZE: (u32, u32) = (500, 200);
+
+/// Props of `signature_pad_leptos`.
+///
+/// Fields were added over time (`field_id`, `read_gate`, `consent`, ...);
+/// build the struct with `LProps::new` and struct update syntax so code
+/// keeps compiling when more are added:
+///
+/// ```ignore
+/// LProps {
+///     read_gate: ReadGate::ScrollToEnd,
+///     ..LProps::new("contract-root", 120, 350, 220)
+/// }
+/// ```
 #[cfg(feature = "leptos")]
 #[derive(Clone)]
 pub struct LProps {
     pub contract_container_id: String,
     pub place_x:               i32,
     pub place_y:               i32,
-    pub place_width:           i32
+    pub place_width:           i32,
+    /// Field this pad fills; namespaces the placed image id (see `FieldIds`).
+    pub field_id:              Option<String>,
+    /// Reading requirement; "Подписать" stays disabled until it is met.
+    pub read_gate:             ReadGate,
+    /// Consent checkbox above the controls; a required one also gates
+    /// "Подписать".
+    pub consent:               Option<ConsentStep>,
+    /// Saved signature to offer for reuse; the signer can also opt in to
+    /// storing the one they draw (consent wording from the store).
+    pub profile:               Option<ProfileStore>,
+    /// Called with the reading progress while `read_gate` is tracked.
+    pub on_read_progress:      Option<Callback<ReadProgress>>,
+    /// Called with every audit entry as it is recorded.
+    pub on_audit:              Option<Callback<AuditEntry>>,
+    /// Called with errors the pad can't show itself, e.g. when the read
+    /// gate can't track `contract_container_id` (signing then stays locked).
+    pub on_error:              Option<Callback<SigError>>,
+    /// Called after "Подписать" placed the signature.
+    pub on_signed:             Option<Callback<LSigned>>,
+    /// Pad ready for signing: once mounted, and again on "Переподписать".
+    pub on_open:               Option<Callback<()>>,
+    /// Pad unmounted before the signer signed (e.g. they navigated away).
+    pub on_cancel:             Option<Callback<()>>,
+    /// Canvas cleared with "Очистить".
+    pub on_clear:              Option<Callback<()>>,
+    /// Placed signature removed from the contract (remove or re-sign). A
+    /// previously reported `LSigned` should be discarded.
+    pub on_removed:            Option<Callback<()>>
 }
 
+/// What `signature_pad_leptos` reports when the signer signs.
 #[cfg(feature = "leptos")]
-pub fn signature_pad_leptos(props: LProps) -> View {
+#[derive(Clone, Debug, PartialEq)]
+pub struct LSigned {
+    /// PNG data URL of the placed signature.
+    pub data_url:    String,
+    /// Consent as given, if `LProps::consent` was ticked.
+    pub consent:     Option<ConsentRecord>,
+    /// Drawn now, or taken unchanged from `LProps::profile`.
+    pub origin:      SignatureOrigin,
+    /// Session audit log up to signing (see `verify_audit_chain`).
+    pub audit_trail: Vec<AuditEntry>
+}
+
+#[cfg(feature = "leptos")]
+impl LProps {
+    /// Image placed at (`place_x`, `place_y`), `place_width` px wide, inside
+    /// `contract_container_id`; no field id, read gate, consent, profile
+    /// or callbacks.
+    pub fn new(
+        contract_container_id: impl Into<String>,
+        place_x: i32,
+        place_y: i32,
+        place_width: i32
+    ) -> Self {
+        Self {
+            contract_container_id: contract_container_id.into(),
+            place_x,
+            place_y,
+            place_width,
+            field_id: None,
+            read_gate: ReadGate::None,
+            consent: None,
+            profile: None,
+            on_read_progress: None,
+            on_audit: None,
+            on_error: None,
+            on_signed: None,
+            on_open: None,
+            on_cancel: None,
+            on_clear: None,
+            on_removed: None
+        }
+    }
+}
+
+#[cfg(feature = "leptos")]
+pub fn signature_pad_leptos(props: LProps) -> impl IntoView {
     let canvas_ref: NodeRef<Canvas> = NodeRef::new();
-    let pad: RwSignal<Option<std::rc::Rc<std::cell::RefCell<SignaturePad>>>> = RwSignal::new(None);
+    let pad: Rc<RefCell<Option<SignaturePad>>> = Rc::default();
+    let ids = FieldIds::new(props.field_id.as_deref());
 
-    let on_mount = {
-        let canvas_ref = canvas_ref.clone();
-        move |_| {
-            if let Some(canvas) = canvas_ref.get() {
-                if let Ok(p) = SignaturePad::new(canvas) {
-                    pad.set(Some(std::rc::Rc::new(std::cell::RefCell::new(p))));
+    // Hash-chained audit log of the session
+    let audit: Rc<RefCell<AuditLog>> = Rc::default();
+    let record = {
+        let audit = audit.clone();
+        let on_audit = props.on_audit;
+        let on_error = props.on_error;
+        Rc::new(move |event: AuditEvent, pointer_type: Option<String>| {
+            let recorded = audit
+                .borrow_mut()
+                .record(event, pointer_type.as_deref())
+                .cloned();
+            match recorded {
+                Ok(entry) => {
+                    if let Some(cb) = &on_audit {
+                        cb.run(entry);
+                    }
+                }
+                Err(e) => {
+                    if let Some(cb) = &on_error {
+                        cb.run(e);
+                    }
                 }
             }
-        }
+        })
+    };
+
+    // Read gate; the tracker lives as long as the mount effect
+    let read_ok = RwSignal::new(props.read_gate == ReadGate::None);
+    let consent_given = RwSignal::new(false);
+    let consent_required = props.consent.as_ref().is_some_and(|c| c.required);
+    // Placed on the contract; the pad stays locked until removed or re-signed
+    let signed = RwSignal::new(false);
+    let can_sign =
+        move || !signed.get() && read_ok.get() && (!consent_required || consent_given.get());
+    let read_tracker: Rc<RefCell<Option<ReadTracker>>> = Rc::default();
+
+    // Saved profile signature, whether the pad holds it unchanged, and the
+    // signer's consent to store the one being drawn
+    let saved = RwSignal::new(None::<SavedSignature>);
+    let reused: Rc<Cell<bool>> = Rc::default();
+    let remember = RwSignal::new(false);
+    if let Some(store) = props.profile.clone() {
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Ok(found) = store.load().await {
+                saved.set(found);
+            }
+        });
+    }
+
+    // Pad is ready for a (new) signature
+    let open = {
+        let record = record.clone();
+        let on_open = props.on_open;
+        Rc::new(move || {
+            record(AuditEvent::ModalOpened, None);
+            if let Some(cb) = &on_open {
+                cb.run(());
+            }
+        })
     };
 
+    // Leaving without signing cancels the session
+    {
+        let record = SendWrapper::new(record.clone());
+        let on_cancel = props.on_cancel;
+        on_cleanup(move || {
+            if signed.try_get_untracked() == Some(false) {
+                record(AuditEvent::Cancelled, None);
+                if let Some(cb) = &on_cancel {
+                    cb.run(());
+                }
+            }
+        });
+    }
+
+    // Runs once the canvas is in the DOM
+    {
+        let pad = pad.clone();
+        let container_id = props.contract_container_id.clone();
+        let read_gate = props.read_gate;
+        let on_read_progress = props.on_read_progress;
+        let on_error = props.on_error;
+        let record = record.clone();
+        let open = open.clone();
+        Effect::new(move |_| {
+            let Some(canvas) = canvas_ref.get() else {
+                return;
+            };
+            if pad.borrow().is_none() {
+                *pad.borrow_mut() = SignaturePad::new(canvas).ok();
+                if pad.borrow().is_some() {
+                    open();
+                }
+            }
+            if read_gate != ReadGate::None && read_tracker.borrow().is_none() {
+                let record = record.clone();
+                let tracked =
+                    DomBindings::track_reading(&container_id, read_gate, move |progress| {
+                        if let Some(cb) = &on_read_progress {
+                            cb.run(progress);
+                        }
+                        if progress.complete {
+                            record(
+                                AuditEvent::ReadingCompleted {
+                                    sections:   progress.sections_total,
+                                    elapsed_ms: progress.elapsed_ms.round() as u64
+                                },
+                                None
+                            );
+                            read_ok.set(true);
+                        }
+                    });
+                match tracked {
+                    Ok(tracker) => *read_tracker.borrow_mut() = Some(tracker),
+                    Err(e) => {
+                        if let Some(cb) = &on_error {
+                            cb.run(e);
+                        }
+                    }
+                }
+            }
+        });
+    }
+
     let pointer_down = {
         let pad = pad.clone();
+        let reused = reused.clone();
+        let record = record.clone();
         move |ev: web_sys::PointerEvent| {
-            if let Some(p) = pad.get() {
-                if let Ok(rect) = ev
-                    .target()
-                    .and_then(|t| t.dyn_into::<web_sys::HtmlCanvasElement>().ok())
-                    .and_then(|c| Some(c.get_bounding_client_rect()))
-                {
-                    let x = ev.client_x() as f64 - rect.x();
-                    let y = ev.client_y() as f64 - rect.y();
-                    p.borrow_mut().pointer_down(x, y);
+            if signed.get_untracked() {
+                return;
+            }
+            if let Some(p) = pad.borrow_mut().as_mut() {
+                if !ev.is_trusted() {
+                    p.note_untrusted_event();
+                    return;
                 }
+                let (cx, cy) = (ev.client_x() as f64, ev.client_y() as f64);
+                // A second finger pinches/pans instead of drawing
+                if ev.pointer_type() == "touch" {
+                    let drawing = p.is_drawing();
+                    if !p.touch_down(ev.pointer_id(), cx, cy) {
+                        if drawing && !p.is_drawing() {
+                            record(AuditEvent::StrokeCancelled, Some(ev.pointer_type()));
+                        }
+                        return;
+                    }
+                }
+                let (x, y) = p.client_to_canvas(cx, cy);
+                // Stylus eraser end / barrel eraser button
+                if ev.buttons() & 32 != 0 || p.is_eraser() {
+                    p.eraser_down(x, y);
+                    return;
+                }
+                p.pointer_down(x, y);
+                reused.set(false);
+                record(
+                    AuditEvent::StrokeStarted {
+                        x,
+                        y
+                    },
+                    Some(ev.pointer_type())
+                );
             }
         }
     };
@@ -53,42 +295,139 @@ pub fn signature_pad_leptos(props: LProps) -> View {
     let pointer_move = {
         let pad = pad.clone();
         move |ev: web_sys::PointerEvent| {
-            if let Some(p) = pad.get() {
-                if let Ok(rect) = ev
-                    .target()
-                    .and_then(|t| t.dyn_into::<web_sys::HtmlCanvasElement>().ok())
-                    .and_then(|c| Some(c.get_bounding_client_rect()))
-                {
-                    let x = ev.client_x() as f64 - rect.x();
-                    let y = ev.client_y() as f64 - rect.y();
-                    p.borrow_mut().pointer_move(x, y);
+            if let Some(p) = pad.borrow_mut().as_mut() {
+                if !ev.is_trusted() {
+                    p.note_untrusted_event();
+                    return;
+                }
+                let (cx, cy) = (ev.client_x() as f64, ev.client_y() as f64);
+                if ev.pointer_type() == "touch" && p.touch_move(ev.pointer_id(), cx, cy) {
+                    return;
                 }
+                let (x, y) = p.client_to_canvas(cx, cy);
+                p.pointer_move(x, y);
             }
         }
     };
 
     let pointer_up = {
         let pad = pad.clone();
-        move |_ev: web_sys::PointerEvent| {
-            if let Some(p) = pad.get() {
-                p.borrow_mut().pointer_up();
+        let record = record.clone();
+        let reused = reused.clone();
+        move |ev: web_sys::PointerEvent| {
+            let mut pad = pad.borrow_mut();
+            let Some(p) = pad.as_mut() else {
+                return;
+            };
+            if !ev.is_trusted() {
+                p.note_untrusted_event();
+                return;
+            }
+            if ev.pointer_type() == "touch" {
+                p.touch_up(ev.pointer_id());
+            }
+            if p.is_erasing() {
+                p.pointer_up();
+                let points = p.erased_points();
+                if points > 0 {
+                    reused.set(false);
+                    record(
+                        AuditEvent::Erased {
+                            points
+                        },
+                        Some(ev.pointer_type())
+                    );
+                }
+            } else if p.is_drawing() {
+                p.pointer_up();
+                let points = p.strokes().last().map_or(0, |s| s.points.len());
+                record(
+                    AuditEvent::StrokeEnded {
+                        points
+                    },
+                    Some(ev.pointer_type())
+                );
             }
         }
     };
+    let pointer_leave = pointer_up.clone();
 
     let do_sign = {
         let pad = pad.clone();
         let props = props.clone();
+        let reused = reused.clone();
+        let record = record.clone();
+        let audit = audit.clone();
         move |_ev: web_sys::MouseEvent| {
-            if let Some(p) = pad.get() {
-                if let Ok(data_url) = p.borrow().to_png_data_url() {
-                    let _ = crate::ui_common::DomBindings::place_signature_img(
-                        &props.contract_container_id,
-                        &data_url,
-                        props.place_x,
-                        props.place_y,
-                        props.place_width
-                    );
+            if !can_sign() {
+                return;
+            }
+            let data_url = pad.borrow().as_ref().map(|p| p.to_png_data_url());
+            let Some(Ok(data_url)) = data_url else {
+                return;
+            };
+            let placed = DomBindings::place_signature_img_for_field(
+                &props.contract_container_id,
+                props.field_id.as_deref(),
+                &data_url,
+                props.place_x,
+                props.place_y,
+                props.place_width
+            );
+            match placed {
+                Ok(()) => {
+                    let mut origin = SignatureOrigin::Drawn;
+                    let profile = saved.get_untracked();
+                    if let (Some(store), Some(profile), true) =
+                        (&props.profile, profile, reused.get())
+                    {
+                        origin = SignatureOrigin::Reused {
+                            profile_id:   store.profile_id.clone(),
+                            consented_at: profile.consented_at
+                        };
+                    } else if let (Some(store), true) =
+                        (props.profile.clone(), remember.get_untracked())
+                    {
+                        // Signer ticked the consent box: store this signature
+                        let strokes = pad
+                            .borrow()
+                            .as_ref()
+                            .map(|p| p.strokes().to_vec())
+                            .unwrap_or_default();
+                        let record = record.clone();
+                        wasm_bindgen_futures::spawn_local(async move {
+                            let stored = store.save(strokes, CANVAS_SIZE.0, CANVAS_SIZE.1).await;
+                            if let Ok(profile) = stored {
+                                record(
+                                    AuditEvent::ProfileSaved {
+                                        profile_id: store.profile_id.clone()
+                                    },
+                                    None
+                                );
+                                saved.set(Some(profile));
+                            }
+                        });
+                    }
+                    remember.set(false);
+                    signed.set(true);
+                    record(AuditEvent::Confirmed, None);
+                    if let Some(cb) = &props.on_signed {
+                        cb.run(LSigned {
+                            data_url,
+                            consent: props
+                                .consent
+                                .as_ref()
+                                .filter(|_| consent_given.get_untracked())
+                                .map(ConsentStep::record),
+                            origin,
+                            audit_trail: audit.borrow().entries().to_vec()
+                        });
+                    }
+                }
+                Err(e) => {
+                    if let Some(cb) = &props.on_error {
+                        cb.run(e);
+                    }
                 }
             }
         }
@@ -96,28 +435,187 @@ pub fn signature_pad_leptos(props: LProps) -> View {
 
     let do_clear = {
         let pad = pad.clone();
+        let reused = reused.clone();
+        let record = record.clone();
+        let on_clear = props.on_clear;
         move |_ev: web_sys::MouseEvent| {
-            if let Some(p) = pad.get() {
-                p.borrow_mut().clear();
+            if let Some(p) = pad.borrow_mut().as_mut() {
+                p.clear();
+                reused.set(false);
+                record(AuditEvent::Cleared, None);
+                if let Some(cb) = &on_clear {
+                    cb.run(());
+                }
+            }
+        }
+    };
+
+    // Take the placed signature off the contract and unlock a blank pad
+    let remove = {
+        let pad = pad.clone();
+        let reused = reused.clone();
+        let record = record.clone();
+        let props = props.clone();
+        Rc::new(move || {
+            signed.set(false);
+            consent_given.set(false);
+            reused.set(false);
+            if let Some(p) = pad.borrow_mut().as_mut() {
+                p.clear();
             }
+            let removed = DomBindings::remove_signature(
+                &props.contract_container_id,
+                props.field_id.as_deref(),
+                None
+            );
+            match removed {
+                Ok(true) => {
+                    record(AuditEvent::Removed, None);
+                    if let Some(cb) = &props.on_removed {
+                        cb.run(());
+                    }
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    if let Some(cb) = &props.on_error {
+                        cb.run(e);
+                    }
+                }
+            }
+        })
+    };
+    let do_resign = {
+        let remove = remove.clone();
+        move |_ev: web_sys::MouseEvent| {
+            remove();
+            open();
         }
     };
+    let do_remove = move |_ev: web_sys::MouseEvent| remove();
+
+    let profile = props.profile.clone().map(|store| {
+        // Load the saved profile signature into the pad
+        let use_saved = {
+            let pad = pad.clone();
+            let reused = reused.clone();
+            let record = record.clone();
+            let profile_id = store.profile_id.clone();
+            move |_ev: web_sys::MouseEvent| {
+                let Some(profile) = saved.get_untracked().filter(|_| !signed.get_untracked())
+                else {
+                    return;
+                };
+                if let Some(p) = pad.borrow_mut().as_mut() {
+                    p.load_strokes(profile.fit(CANVAS_SIZE));
+                    reused.set(true);
+                    record(
+                        AuditEvent::ProfileUsed {
+                            profile_id: profile_id.clone()
+                        },
+                        None
+                    );
+                }
+            }
+        };
+        // Delete the saved profile signature (withdraws consent)
+        let delete_saved = {
+            let reused = reused.clone();
+            let record = record.clone();
+            let store = store.clone();
+            move |_ev: web_sys::MouseEvent| {
+                let store = store.clone();
+                let record = record.clone();
+                reused.set(false);
+                wasm_bindgen_futures::spawn_local(async move {
+                    if store.delete().await.is_ok() {
+                        record(
+                            AuditEvent::ProfileDeleted {
+                                profile_id: store.profile_id.clone()
+                            },
+                            None
+                        );
+                        saved.set(None);
+                    }
+                });
+            }
+        };
+        let none_saved = move || saved.with(Option::is_none);
+        view! {
+            <div class="cs-profile">
+                <button class="cs-use-saved-btn" on:click=use_saved hidden=none_saved>
+                    {"Использовать сохранённую подпись"}
+                </button>
+                <button class="cs-delete-saved-btn secondary" on:click=delete_saved hidden=none_saved>
+                    {"Удалить сохранённую подпись"}
+                </button>
+                <label class="cs-profile-consent">
+                    <input
+                        type="checkbox"
+                        prop:checked=move || remember.get()
+                        on:change=move |_| remember.update(|r| *r = !*r)
+                    />
+                    {store.consent_text}
+                </label>
+            </div>
+        }
+    });
+
+    let consent = props.consent.clone().map(|step| {
+        let record = record.clone();
+        let text = step.text.clone();
+        let on_change = move |_| {
+            let given = !consent_given.get_untracked();
+            consent_given.set(given);
+            if given {
+                record(
+                    AuditEvent::ConsentGiven {
+                        text: text.clone()
+                    },
+                    None
+                );
+            }
+        };
+        let link = step.link.map(|link| {
+            view! {
+                " "
+                <a href=link.url target="_blank" rel="noopener noreferrer">{link.label}</a>
+            }
+        });
+        view! {
+            <label class="cs-consent">
+                <input
+                    type="checkbox"
+                    prop:checked=move || consent_given.get()
+                    on:change=on_change
+                />
+                {step.text}
+                {link}
+            </label>
+        }
+    });
 
-    leptos::view! {
-        on:load=on_mount
+    view! {
         <div class="signature-area">
             <canvas
-                _ref=canvas_ref
-                width="500" height="200"
+                node_ref=canvas_ref
+                width=CANVAS_SIZE.0 height=CANVAS_SIZE.1
+                // Pinch and pan stay on the pad instead of moving the page
+                style="touch-action:none"
                 on:pointerdown=pointer_down
                 on:pointermove=pointer_move
                 on:pointerup=pointer_up
-                on:pointerleave=pointer_up
+                on:pointerleave=pointer_leave
             />
+            {consent}
             <div class="controls">
-                <button on:click=do_sign>{"Подписать"}</button>
-                <button on:click=do_clear>{"Очистить"}</button>
+                <button id=ids.sign_btn() on:click=do_sign disabled=move || !can_sign()>
+                    {"Подписать"}
+                </button>
+                <button on:click=do_clear disabled=move || signed.get()>{"Очистить"}</button>
+                <button on:click=do_resign hidden=move || !signed.get()>{"Переподписать"}</button>
+                <button on:click=do_remove>{"Удалить подпись"}</button>
             </div>
+            {profile}
         </div>
     }
 }
@@ -1,5 +1,21 @@
+mod audit;
+mod certificate;
+mod compose;
+mod consent;
+mod draft;
+mod envelope;
 mod error;
+mod format;
+mod pdf;
+mod pdf_stamp;
+mod placement;
+mod profile;
+mod reading;
+mod risk;
+mod seal;
+mod session;
 mod signature_core;
+mod similarity;
 mod ui_common;
 
 #[cfg(feature = "yew")]
@@ -8,10 +24,42 @@ mod yew;
 #[cfg(feature = "leptos")]
 mod leptos;
 
+pub use audit::{
+    verify_audit_chain, AuditContext, AuditEntry, AuditEvent, AuditLog, GENESIS_HASH
+};
+pub use certificate::Certificate;
+pub use compose::{Block, BlockContent, Composition, Side, TextStyle};
+pub use consent::{ConsentLink, ConsentRecord, ConsentStep};
+pub use draft::{
+    BrowserStorage, Draft, DraftStorage, DraftStore, IndexedDb, LocalStorage, RestoredEnvelope,
+    StorageFuture
+};
+pub use envelope::{canonicalize_text, hash_strokes, sha256_hex, SignedEnvelope, SignerMetadata};
 pub use error::SigError;
-pub use signature_core::SignaturePad;
+pub use pdf::{ContractPdf, PageSetup, PdfSignature, SignatureImage, TextRun, TrueTypeFont};
+pub use pdf_stamp::{stamp_signature, PdfRect, StampTarget};
+pub use placement::{
+    Align, AnchorLayout, AnchorTarget, FitMode, InteractiveOptions, PlacementGeometry,
+    PlacementHandle, PlacementMode, VerticalAlign
+};
+pub use profile::{ProfileStore, SavedSignature, SignatureOrigin, DEFAULT_CONSENT_TEXT};
+pub use reading::{ReadGate, ReadProgress, ReadTracker, MUST_READ_SELECTOR};
+pub use risk::{assess_strokes, RiskAssessment, RiskFlag};
+pub use seal::{
+    seal_envelope, verify_envelope, EnvelopeSeal, EnvelopeSigner, EnvelopeVerifier, SealAlgorithm,
+    WebCryptoKey, WebCryptoKeyPair
+};
+pub use session::{SessionSigner, SessionStatus, SignerState, SigningSession};
+pub use signature_core::{
+    Background, Baseline, Decorations, LineCap, LineJoin, PenStyle, SignaturePad, Stroke,
+    StrokePoint, ViewTransform
+};
+pub use similarity::{compare_strokes, compare_strokes_with, SimilarityReport, SimilarityWeights};
+pub use ui_common::{
+    AnchorTracker, DomBindings, FieldIds, FullscreenSigning, InitialsHandle, SignatureHandle
+};
 
 #[cfg(feature = "leptos")]
-pub use crate::leptos::{signature_pad_leptos, LProps};
+pub use crate::leptos::{signature_pad_leptos, LProps, LSigned};
 #[cfg(feature = "yew")]
-pub use crate::yew::{SignaturePadYew, SignatureProps, SignatureStyle};
+pub use crate::yew::{MobileMode, SignaturePadYew, SignatureProps, SignatureStyle};
@@ -0,0 +1,1140 @@
+// src/pdf.rs
+
+use std::{collections::BTreeMap, fmt::Write as _};
+
+use miniz_oxide::{deflate::compress_to_vec_zlib, inflate::decompress_to_vec_zlib};
+
+use crate::{
+    envelope::SignedEnvelope,
+    error::SigError,
+    format::num,
+    placement::{FitMode, PlacementGeometry},
+    signature_core::{LineCap, LineJoin, PenStyle, Stroke}
+};
+
+/// zlib level for content, image and font streams.
+const COMPRESSION_LEVEL: u8 = 6;
+/// Pen width `SignaturePad` draws with, canvas px.
+const DEFAULT_LINE_WIDTH: f64 = 2.0;
+/// Helvetica ascender, in units of the font size.
+const BUILTIN_ASCENT: f64 = 0.718;
+
+/// Page size and margins, in PDF points.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PageSetup {
+    pub width:  f64,
+    pub height: f64,
+    pub margin: f64
+}
+
+impl Default for PageSetup {
+    /// A4 portrait.
+    fn default() -> Self {
+        Self {
+            width:  595.28,
+            height: 841.89,
+            margin: 28.0
+        }
+    }
+}
+
+/// One line (or part of a line) of contract text, in px relative to the
+/// contract container's content origin.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextRun {
+    pub text:  String,
+    /// Left of the first glyph.
+    pub x:     f64,
+    /// Top of the glyph box (ascender line).
+    pub y:     f64,
+    /// Rendered width in the browser; the PDF run is stretched to match.
+    /// `0.0` keeps the font's natural width.
+    pub width: f64,
+    /// Font size, px.
+    pub size:  f64
+}
+
+/// Decoded 8-bit RGBA bitmap.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignatureImage {
+    pub width:  u32,
+    pub height: u32,
+    pub rgba:   Vec<u8>
+}
+
+impl SignatureImage {
+    /// Decode a non-interlaced 8-bit RGB/RGBA PNG (what `canvas.toDataURL`
+    /// produces).
+    pub fn from_png(png: &[u8]) -> Result<Self, SigError> {
+        decode_png(png)
+    }
+
+    /// Image XObject dict entries, RGB samples and the alpha (SMask) samples.
+    pub(crate) fn streams(&self) -> (String, Vec<u8>, Vec<u8>) {
+        let mut rgb = Vec::with_capacity(self.rgba.len() / 4 * 3);
+        let mut alpha = Vec::with_capacity(self.rgba.len() / 4);
+        for px in self.rgba.chunks_exact(4) {
+            rgb.extend_from_slice(&px[..3]);
+            alpha.push(px[3]);
+        }
+        let dims = format!(
+            "/Type /XObject /Subtype /Image /Width {} /Height {} /BitsPerComponent 8",
+            self.width, self.height
+        );
+        (dims, rgb, alpha)
+    }
+}
+
+/// A placed signature: vector strokes if available, else the bitmap.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PdfSignature {
+    /// Box of the placed image inside the contract container, px.
+    pub geometry:      PlacementGeometry,
+    /// Strokes in canvas px; drawn as vector paths when not empty.
+    pub strokes:       Vec<Stroke>,
+    pub canvas_width:  f64,
+    pub canvas_height: f64,
+    /// Pen width of strokes without a recorded pen, canvas px.
+    pub line_width:    f64,
+    /// Raster fallback for signatures without stroke data.
+    pub image:         Option<SignatureImage>
+}
+
+impl PdfSignature {
+    /// Build from a confirmed envelope. Needs `envelope.placement`; the
+    /// canvas size is taken from the envelope's PNG, which is also what gets
+    /// drawn for composed envelopes.
+    pub fn from_envelope(envelope: &SignedEnvelope) -> Result<Self, SigError> {
+        let geometry = envelope
+            .placement
+            .ok_or_else(|| SigError::InvalidState("envelope has no placement".into()))?;
+        let image = SignatureImage::from_png(&envelope.png_bytes()?)?;
+        Ok(Self {
+            geometry,
+            strokes: if envelope.composed {
+                // Text and stamps exist only in the image
+                Vec::new()
+            } else {
+                envelope.strokes.clone()
+            },
+            canvas_width: image.width as f64,
+            canvas_height: image.height as f64,
+            line_width: DEFAULT_LINE_WIDTH,
+            image: Some(image)
+        })
+    }
+
+    /// Bitmap to draw instead of the strokes, if there are no strokes.
+    pub(crate) fn raster(&self) -> Option<&SignatureImage> {
+        if self.strokes.iter().any(|st| !st.points.is_empty()) {
+            None
+        } else {
+            self.image.as_ref()
+        }
+    }
+
+    /// Content stream operators drawing the signature into the box with
+    /// top-left (`left`, `top`) and size `w` x `h`, in pt, clipped to the box.
+    /// `image` is the XObject name of `raster()`, if it is drawn.
+    pub(crate) fn draw_ops(
+        &self,
+        left: f64,
+        top: f64,
+        w: f64,
+        h: f64,
+        fit: FitMode,
+        image: Option<&str>
+    ) -> String {
+        let (cw, ch) = (self.canvas_width.max(1.0), self.canvas_height.max(1.0));
+        let (sx, sy) = (w / cw, h / ch);
+        let s = match fit {
+            FitMode::Cover => sx.max(sy),
+            FitMode::None | FitMode::Contain => sx.min(sy)
+        };
+        let ox = left + (w - cw * s) / 2.0;
+        let oy = top - (h - ch * s) / 2.0;
+
+        let mut out = format!(
+            "q {} {} {} {} re W n\n",
+            num(left),
+            num(top - h),
+            num(w),
+            num(h)
+        );
+        if let Some(name) = image {
+            let _ = writeln!(
+                out,
+                "{} 0 0 {} {} {} cm /{name} Do",
+                num(cw * s),
+                num(ch * s),
+                num(ox),
+                num(oy - ch * s)
+            );
+        } else {
+            let pt = |x: f64, y: f64| (num(ox + x * s), num(oy - y * s));
+            for stroke in &self.strokes {
+                let Some((first, rest)) = stroke.points.split_first() else {
+                    continue;
+                };
+                let pen = stroke
+                    .pen
+                    .clone()
+                    .unwrap_or_else(|| PenStyle::solid("#000000", self.line_width));
+                out.push_str(&pen_ops(&pen));
+                let (x0, y0) = pt(first.x, first.y);
+                if pen.is_variable() && pen.opacity() >= 1.0 && !rest.is_empty() {
+                    // Pressure-dependent width: one path per segment
+                    let mut prev = (first, x0, y0);
+                    for p in rest {
+                        let (x, y) = pt(p.x, p.y);
+                        let width = pen.width((prev.0.pressure + p.pressure) / 2.0);
+                        let _ = writeln!(
+                            out,
+                            "{} w {} {} m {x} {y} l S",
+                            num(width * s),
+                            prev.1,
+                            prev.2
+                        );
+                        prev = (p, x, y);
+                    }
+                    continue;
+                }
+                let mean = stroke.points.iter().map(|p| p.pressure).sum::<f32>()
+                    / stroke.points.len() as f32;
+                let _ = write!(out, "{} w {x0} {y0} m", num(pen.width(mean) * s));
+                if rest.is_empty() {
+                    // Tap: zero-length segment, the round cap draws a dot
+                    let _ = write!(out, " {x0} {y0} l");
+                }
+                for p in rest {
+                    let (x, y) = pt(p.x, p.y);
+                    let _ = write!(out, " {x} {y} l");
+                }
+                out.push_str(" S\n");
+            }
+        }
+        out.push_str("Q\n");
+        out
+    }
+}
+
+/// Colour, cap and join operators for `pen`. Opacity is approximated by
+/// mixing the colour with the white page.
+fn pen_ops(pen: &PenStyle) -> String {
+    let (r, g, b) = pen.rgb();
+    let a = pen.opacity();
+    let c = |v: u8| num(1.0 - a + a * v as f64 / 255.0);
+    let cap = match pen.cap {
+        LineCap::Butt => 0,
+        LineCap::Round => 1,
+        LineCap::Square => 2
+    };
+    let join = match pen.join {
+        LineJoin::Miter => 0,
+        LineJoin::Round => 1,
+        LineJoin::Bevel => 2
+    };
+    format!("{} {} {} RG {cap} J {join} j\n", c(r), c(g), c(b))
+}
+
+/// TrueType font embedded into the PDF. The built-in Helvetica only covers
+/// WinAnsi (Latin-1); Cyrillic contracts need one of these.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrueTypeFont {
+    data:         Vec<u8>,
+    name:         String,
+    units_per_em: f64,
+    ascent:       i16,
+    descent:      i16,
+    bbox:         [i16; 4],
+    /// Advance widths, font units, indexed by glyph id (last one repeats).
+    advances:     Vec<u16>,
+    /// Offset and format of the Unicode `cmap` subtable.
+    cmap:         (usize, u16)
+}
+
+impl TrueTypeFont {
+    /// Parse a TrueType (`glyf`-based) font file. CFF-flavoured OpenType is
+    /// not supported.
+    pub fn parse(data: Vec<u8>) -> Result<Self, SigError> {
+        let tag = read_u32(&data, 0)?;
+        if tag == u32::from_be_bytes(*b"OTTO") {
+            return Err(font_err("CFF-based OpenType fonts are not supported"));
+        }
+        let tables = table_directory(&data)?;
+        let table = |name: &[u8; 4]| {
+            tables.get(name).copied().ok_or_else(|| {
+                font_err(&format!(
+                    "missing '{}' table",
+                    String::from_utf8_lossy(name)
+                ))
+            })
+        };
+
+        let head = table(b"head")?;
+        let units_per_em = read_u16(&data, head + 18)? as f64;
+        let bbox = [
+            read_i16(&data, head + 36)?,
+            read_i16(&data, head + 38)?,
+            read_i16(&data, head + 40)?,
+            read_i16(&data, head + 42)?
+        ];
+
+        let hhea = table(b"hhea")?;
+        let ascent = read_i16(&data, hhea + 4)?;
+        let descent = read_i16(&data, hhea + 6)?;
+        let metrics = read_u16(&data, hhea + 34)? as usize;
+
+        let hmtx = table(b"hmtx")?;
+        let advances = (0..metrics)
+            .map(|i| read_u16(&data, hmtx + i * 4))
+            .collect::<Result<Vec<_>, _>>()?;
+        if advances.is_empty() {
+            return Err(font_err("empty 'hmtx' table"));
+        }
+
+        let cmap = find_unicode_cmap(&data, table(b"cmap")?)?;
+        let name = tables
+            .get(b"name")
+            .and_then(|&off| postscript_name(&data, off))
+            .unwrap_or_else(|| "ContractFont".into());
+
+        Ok(Self {
+            data,
+            name,
+            units_per_em: if units_per_em > 0.0 {
+                units_per_em
+            } else {
+                1000.0
+            },
+            ascent,
+            descent,
+            bbox,
+            advances,
+            cmap
+        })
+    }
+
+    /// Glyph id of `c`, `0` (`.notdef`) if the font doesn't have it.
+    fn glyph_id(&self, c: char) -> u16 {
+        let (off, format) = self.cmap;
+        let c = c as u32;
+        let found = match format {
+            4 => cmap_format4(&self.data, off, c),
+            12 => cmap_format12(&self.data, off, c),
+            _ => None
+        };
+        found.unwrap_or(0)
+    }
+
+    fn advance(&self, gid: u16) -> f64 {
+        let w = self
+            .advances
+            .get(gid as usize)
+            .or_else(|| self.advances.last())
+            .copied()
+            .unwrap_or(0);
+        w as f64 / self.units_per_em
+    }
+
+    fn ascent(&self) -> f64 {
+        self.ascent as f64 / self.units_per_em
+    }
+
+    /// Advance width of `text` set at `size`.
+    pub(crate) fn text_width(&self, text: &str, size: f64) -> f64 {
+        text.chars()
+            .map(|c| self.advance(self.glyph_id(c)))
+            .sum::<f64>()
+            * size
+    }
+
+    /// Font units scaled to the PDF glyph space (1000 per em).
+    fn to_pdf_units(&self, v: f64) -> i64 {
+        (v * 1000.0 / self.units_per_em).round() as i64
+    }
+}
+
+/// The signed contract as a PDF: text runs and signatures laid out in the
+/// container's px space, scaled to the page width and paginated.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContractPdf {
+    pub page:       PageSetup,
+    /// Width of the contract container's content, px.
+    pub width:      f64,
+    pub texts:      Vec<TextRun>,
+    pub signatures: Vec<PdfSignature>,
+    /// Embedded font; `None` uses Helvetica (Latin-1 only).
+    pub font:       Option<TrueTypeFont>
+}
+
+impl ContractPdf {
+    pub fn new(width: f64) -> Self {
+        Self {
+            page: PageSetup::default(),
+            width,
+            texts: Vec::new(),
+            signatures: Vec::new(),
+            font: None
+        }
+    }
+
+    /// Render to PDF bytes. The output depends only on the input (no
+    /// timestamps or random ids).
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SigError> {
+        if self.width <= 0.0 {
+            return Err(SigError::InvalidState(
+                "contract width must be positive".into()
+            ));
+        }
+        let page = self.page;
+        let scale = (page.width - 2.0 * page.margin) / self.width;
+        let page_px = (page.height - 2.0 * page.margin) / scale;
+        if scale <= 0.0 || page_px <= 0.0 {
+            return Err(SigError::InvalidState(
+                "page has no room inside the margins".into()
+            ));
+        }
+
+        // px (container content) -> (page index, x pt, y pt)
+        let to_page = |x: f64, y: f64, page_idx: usize| {
+            (
+                page.margin + x * scale,
+                page.height - page.margin - (y - page_idx as f64 * page_px) * scale
+            )
+        };
+        let page_of = |y: f64| (y.max(0.0) / page_px).floor() as usize;
+
+        let page_count = self
+            .texts
+            .iter()
+            .map(|t| page_of(t.y))
+            .chain(self.signatures.iter().map(|s| page_of(s.geometry.y)))
+            .max()
+            .map_or(1, |last| last + 1);
+        let mut contents = vec![String::new(); page_count];
+        let mut used_glyphs: BTreeMap<u16, char> = BTreeMap::new();
+
+        // Text
+        for run in &self.texts {
+            let idx = page_of(run.y);
+            let size = run.size * scale;
+            let (text, natural, ascent) = match &self.font {
+                Some(font) => {
+                    let mut hex = String::new();
+                    let mut natural = 0.0;
+                    for c in run.text.chars() {
+                        let gid = font.glyph_id(c);
+                        used_glyphs.entry(gid).or_insert(c);
+                        natural += font.advance(gid);
+                        let _ = write!(hex, "{gid:04X}");
+                    }
+                    (format!("<{hex}>"), natural * run.size, font.ascent())
+                }
+                None => (pdf_literal(&win_ansi(&run.text)), 0.0, BUILTIN_ASCENT)
+            };
+            let (x, y) = to_page(run.x, run.y + ascent * run.size, idx);
+            let stretch = if run.width > 0.0 && natural > 0.0 {
+                run.width / natural * 100.0
+            } else {
+                100.0
+            };
+            let _ = writeln!(
+                contents[idx],
+                "BT /F1 {} Tf {} Tz 1 0 0 1 {} {} Tm {text} Tj ET",
+                num(size),
+                num(stretch),
+                num(x),
+                num(y)
+            );
+        }
+
+        // Signatures
+        let mut images = Vec::new();
+        for sig in &self.signatures {
+            let g = sig.geometry;
+            let idx = page_of(g.y);
+            let (left, top) = to_page(g.x, g.y, idx);
+            let image = sig.raster().map(|image| {
+                images.push(image);
+                format!("Im{}", images.len())
+            });
+            // Geometry already has the image's aspect unless it is a
+            // `FitMode::Cover` box, so cover reproduces the page in all cases
+            contents[idx].push_str(&sig.draw_ops(
+                left,
+                top,
+                g.width * scale,
+                g.height * scale,
+                FitMode::Cover,
+                image.as_deref()
+            ));
+        }
+
+        // Objects
+        let mut pdf = PdfBuilder::new();
+        let catalog = pdf.reserve();
+        let pages = pdf.reserve();
+        let font = pdf.reserve();
+        let page_ids: Vec<(usize, usize)> = (0..page_count)
+            .map(|_| (pdf.reserve(), pdf.reserve()))
+            .collect();
+        let image_ids: Vec<(usize, usize)> = images
+            .iter()
+            .map(|_| (pdf.reserve(), pdf.reserve()))
+            .collect();
+
+        pdf.object(
+            catalog,
+            &format!("<< /Type /Catalog /Pages {pages} 0 R >>"),
+            None
+        );
+        let kids: Vec<String> = page_ids.iter().map(|(p, _)| format!("{p} 0 R")).collect();
+        pdf.object(
+            pages,
+            &format!(
+                "<< /Type /Pages /Kids [{}] /Count {page_count} >>",
+                kids.join(" ")
+            ),
+            None
+        );
+
+        match &self.font {
+            Some(ttf) => write_truetype(&mut pdf, font, ttf, &used_glyphs),
+            None => pdf.object(
+                font,
+                "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>",
+                None
+            )
+        }
+
+        let xobjects: String = image_ids
+            .iter()
+            .enumerate()
+            .map(|(i, (id, _))| format!("/Im{} {id} 0 R ", i + 1))
+            .collect();
+        for ((page_id, content_id), content) in page_ids.iter().zip(&contents) {
+            pdf.object(
+                *page_id,
+                &format!(
+                    "<< /Type /Page /Parent {pages} 0 R /MediaBox [0 0 {} {}] \
+                     /Resources << /Font << /F1 {font} 0 R >> /XObject << {xobjects}>> >> \
+                     /Contents {content_id} 0 R >>",
+                    num(page.width),
+                    num(page.height)
+                ),
+                None
+            );
+            pdf.flate_stream(*content_id, "", content.as_bytes());
+        }
+
+        for (image, (id, mask)) in images.iter().zip(&image_ids) {
+            let (dims, rgb, alpha) = image.streams();
+            pdf.flate_stream(
+                *id,
+                &format!("{dims} /ColorSpace /DeviceRGB /SMask {mask} 0 R"),
+                &rgb
+            );
+            pdf.flate_stream(*mask, &format!("{dims} /ColorSpace /DeviceGray"), &alpha);
+        }
+
+        Ok(pdf.finish(catalog))
+    }
+}
+
+/// Type0 / CIDFontType2 font with the whole font file embedded and a
+/// ToUnicode map, so text stays searchable and copyable.
+fn write_truetype(
+    pdf: &mut PdfBuilder,
+    id: usize,
+    font: &TrueTypeFont,
+    used: &BTreeMap<u16, char>
+) {
+    let cid_font = pdf.reserve();
+    let descriptor = pdf.reserve();
+    let file = pdf.reserve();
+    let to_unicode = pdf.reserve();
+    let name: String = font
+        .name
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect();
+
+    pdf.object(
+        id,
+        &format!(
+            "<< /Type /Font /Subtype /Type0 /BaseFont /{name} /Encoding /Identity-H \
+             /DescendantFonts [{cid_font} 0 R] /ToUnicode {to_unicode} 0 R >>"
+        ),
+        None
+    );
+
+    let widths: String = used
+        .keys()
+        .map(|&gid| format!("{gid} [{}] ", (font.advance(gid) * 1000.0).round() as i64))
+        .collect();
+    pdf.object(
+        cid_font,
+        &format!(
+            "<< /Type /Font /Subtype /CIDFontType2 /BaseFont /{name} \
+             /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> \
+             /FontDescriptor {descriptor} 0 R /CIDToGIDMap /Identity /W [{widths}] >>"
+        ),
+        None
+    );
+
+    let [x_min, y_min, x_max, y_max] = font.bbox.map(|v| font.to_pdf_units(v as f64));
+    let ascent = font.to_pdf_units(font.ascent as f64);
+    pdf.object(
+        descriptor,
+        &format!(
+            "<< /Type /FontDescriptor /FontName /{name} /Flags 32 \
+             /FontBBox [{x_min} {y_min} {x_max} {y_max}] /ItalicAngle 0 /Ascent {ascent} \
+             /Descent {} /CapHeight {ascent} /StemV 80 /FontFile2 {file} 0 R >>",
+            font.to_pdf_units(font.descent as f64)
+        ),
+        None
+    );
+    pdf.flate_stream(file, &format!("/Length1 {}", font.data.len()), &font.data);
+
+    let mut cmap = String::from(
+        "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n\
+         /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+         /CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n\
+         1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n"
+    );
+    let entries: Vec<(&u16, &char)> = used.iter().collect();
+    for chunk in entries.chunks(100) {
+        let _ = writeln!(cmap, "{} beginbfchar", chunk.len());
+        for (gid, c) in chunk {
+            let utf16: String = c
+                .encode_utf16(&mut [0; 2])
+                .iter()
+                .map(|u| format!("{u:04X}"))
+                .collect();
+            let _ = writeln!(cmap, "<{gid:04X}> <{utf16}>");
+        }
+        cmap.push_str("endbfchar\n");
+    }
+    cmap.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend\n");
+    pdf.flate_stream(to_unicode, "", cmap.as_bytes());
+}
+
+/// Sequential object writer with a classic xref table.
+struct PdfBuilder {
+    out:     Vec<u8>,
+    offsets: Vec<usize>
+}
+
+impl PdfBuilder {
+    fn new() -> Self {
+        Self {
+            // Binary comment marks the file as binary for transfer tools
+            out:     b"%PDF-1.7\n%\xE2\xE3\xCF\xD3\n".to_vec(),
+            offsets: Vec::new()
+        }
+    }
+
+    /// Allocate an object number to be written later.
+    fn reserve(&mut self) -> usize {
+        self.offsets.push(0);
+        self.offsets.len()
+    }
+
+    fn object(&mut self, id: usize, dict: &str, stream: Option<&[u8]>) {
+        self.offsets[id - 1] = self.out.len();
+        self.out
+            .extend_from_slice(format!("{id} 0 obj\n{dict}\n").as_bytes());
+        if let Some(data) = stream {
+            self.out.extend_from_slice(b"stream\n");
+            self.out.extend_from_slice(data);
+            self.out.extend_from_slice(b"\nendstream\n");
+        }
+        self.out.extend_from_slice(b"endobj\n");
+    }
+
+    /// Stream object with Flate-compressed `data`; `extra` goes into its dict.
+    fn flate_stream(&mut self, id: usize, extra: &str, data: &[u8]) {
+        let packed = compress_to_vec_zlib(data, COMPRESSION_LEVEL);
+        let dict = format!(
+            "<< {extra}{}/Filter /FlateDecode /Length {} >>",
+            if extra.is_empty() { "" } else { " " },
+            packed.len()
+        );
+        self.object(id, &dict, Some(&packed));
+    }
+
+    fn finish(mut self, root: usize) -> Vec<u8> {
+        let xref = self.out.len();
+        let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", self.offsets.len() + 1);
+        for off in &self.offsets {
+            let _ = writeln!(table, "{off:010} 00000 n ");
+        }
+        let _ = write!(
+            table,
+            "trailer\n<< /Size {} /Root {root} 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            self.offsets.len() + 1
+        );
+        self.out.extend_from_slice(table.as_bytes());
+        self.out
+    }
+}
+
+/// Encode for Helvetica/WinAnsi; characters outside Latin-1 become `?`.
+fn win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c as u32 {
+            0x20..=0x7E | 0xA0..=0xFF => c as u8,
+            _ => b'?'
+        })
+        .collect()
+}
+
+fn pdf_literal(bytes: &[u8]) -> String {
+    let mut s = String::from("(");
+    for &b in bytes {
+        match b {
+            b'(' | b')' | b'\\' => {
+                s.push('\\');
+                s.push(b as char);
+            }
+            0x20..=0x7E => s.push(b as char),
+            _ => {
+                let _ = write!(s, "\\{b:03o}");
+            }
+        }
+    }
+    s.push(')');
+    s
+}
+
+fn font_err(msg: &str) -> SigError {
+    SigError::OpFailed(format!("font: {msg}"))
+}
+
+fn read_u16(data: &[u8], off: usize) -> Result<u16, SigError> {
+    data.get(off..off + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or_else(|| font_err("truncated"))
+}
+
+fn read_i16(data: &[u8], off: usize) -> Result<i16, SigError> {
+    read_u16(data, off).map(|v| v as i16)
+}
+
+fn read_u32(data: &[u8], off: usize) -> Result<u32, SigError> {
+    data.get(off..off + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| font_err("truncated"))
+}
+
+fn table_directory(data: &[u8]) -> Result<BTreeMap<[u8; 4], usize>, SigError> {
+    let count = read_u16(data, 4)? as usize;
+    (0..count)
+        .map(|i| {
+            let rec = 12 + i * 16;
+            let tag = data
+                .get(rec..rec + 4)
+                .ok_or_else(|| font_err("truncated"))?;
+            Ok((
+                [tag[0], tag[1], tag[2], tag[3]],
+                read_u32(data, rec + 8)? as usize
+            ))
+        })
+        .collect()
+}
+
+/// Pick a Unicode subtable: full repertoire (3,10) format 12 first, then BMP
+/// format 4.
+fn find_unicode_cmap(data: &[u8], cmap: usize) -> Result<(usize, u16), SigError> {
+    let count = read_u16(data, cmap + 2)? as usize;
+    let mut best: Option<(u8, usize, u16)> = None;
+    for i in 0..count {
+        let rec = cmap + 4 + i * 8;
+        let platform = read_u16(data, rec)?;
+        let encoding = read_u16(data, rec + 2)?;
+        let sub = cmap + read_u32(data, rec + 4)? as usize;
+        let format = read_u16(data, sub)?;
+        let rank = match (platform, encoding, format) {
+            (3, 10, 12) | (0, _, 12) => 2,
+            (3, 1, 4) | (0, _, 4) => 1,
+            _ => continue
+        };
+        if best.is_none_or(|(r, ..)| rank > r) {
+            best = Some((rank, sub, format));
+        }
+    }
+    best.map(|(_, sub, format)| (sub, format))
+        .ok_or_else(|| font_err("no Unicode 'cmap' subtable"))
+}
+
+fn cmap_format4(data: &[u8], sub: usize, c: u32) -> Option<u16> {
+    if c > 0xFFFF {
+        return None;
+    }
+    let c = c as u16;
+    let seg_x2 = read_u16(data, sub + 6).ok()? as usize;
+    let ends = sub + 14;
+    let starts = ends + seg_x2 + 2;
+    let deltas = starts + seg_x2;
+    let ranges = deltas + seg_x2;
+    for i in (0..seg_x2).step_by(2) {
+        if read_u16(data, ends + i).ok()? < c {
+            continue;
+        }
+        let start = read_u16(data, starts + i).ok()?;
+        if start > c {
+            return None;
+        }
+        let delta = read_u16(data, deltas + i).ok()?;
+        let range = read_u16(data, ranges + i).ok()? as usize;
+        if range == 0 {
+            return Some(c.wrapping_add(delta));
+        }
+        let addr = ranges + i + range + 2 * (c - start) as usize;
+        let gid = read_u16(data, addr).ok()?;
+        return (gid != 0).then(|| gid.wrapping_add(delta));
+    }
+    None
+}
+
+fn cmap_format12(data: &[u8], sub: usize, c: u32) -> Option<u16> {
+    let groups = read_u32(data, sub + 12).ok()? as usize;
+    (0..groups).find_map(|i| {
+        let g = sub + 16 + i * 12;
+        let start = read_u32(data, g).ok()?;
+        let end = read_u32(data, g + 4).ok()?;
+        if (start..=end).contains(&c) {
+            let first = read_u32(data, g + 8).ok()?;
+            u16::try_from(first + (c - start)).ok()
+        } else {
+            None
+        }
+    })
+}
+
+/// PostScript name (name id 6) from the `name` table.
+fn postscript_name(data: &[u8], table: usize) -> Option<String> {
+    let count = read_u16(data, table + 2).ok()? as usize;
+    let strings = table + read_u16(data, table + 4).ok()? as usize;
+    (0..count).find_map(|i| {
+        let rec = table + 6 + i * 12;
+        let platform = read_u16(data, rec).ok()?;
+        if read_u16(data, rec + 6).ok()? != 6 {
+            return None;
+        }
+        let len = read_u16(data, rec + 8).ok()? as usize;
+        let off = strings + read_u16(data, rec + 10).ok()? as usize;
+        let bytes = data.get(off..off + len)?;
+        match platform {
+            // UTF-16BE
+            0 | 3 => {
+                let units: Vec<u16> = bytes
+                    .chunks_exact(2)
+                    .map(|b| u16::from_be_bytes([b[0], b[1]]))
+                    .collect();
+                String::from_utf16(&units).ok()
+            }
+            _ => Some(bytes.iter().map(|&b| b as char).collect())
+        }
+    })
+}
+
+fn decode_png(png: &[u8]) -> Result<SignatureImage, SigError> {
+    let err = |msg: &str| SigError::OpFailed(format!("png: {msg}"));
+    if png.get(..8) != Some(b"\x89PNG\r\n\x1a\n".as_slice()) {
+        return Err(err("bad signature"));
+    }
+
+    let mut pos = 8;
+    let mut header = None;
+    let mut idat = Vec::new();
+    while pos + 8 <= png.len() {
+        let len =
+            u32::from_be_bytes([png[pos], png[pos + 1], png[pos + 2], png[pos + 3]]) as usize;
+        let kind = &png[pos + 4..pos + 8];
+        // Lengths up to 4 GiB overflow usize on wasm32
+        let end = (pos + 8)
+            .checked_add(len)
+            .ok_or_else(|| err("truncated chunk"))?;
+        let body = png
+            .get(pos + 8..end)
+            .ok_or_else(|| err("truncated chunk"))?;
+        match kind {
+            b"IHDR" if len >= 13 => {
+                let w = u32::from_be_bytes([body[0], body[1], body[2], body[3]]);
+                let h = u32::from_be_bytes([body[4], body[5], body[6], body[7]]);
+                header = Some((w, h, body[8], body[9], body[12]));
+            }
+            b"IDAT" => idat.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {}
+        }
+        // length + type + data + CRC
+        pos = end.saturating_add(4);
+    }
+
+    let (width, height, depth, color, interlace) = header.ok_or_else(|| err("no IHDR"))?;
+    let channels = match (depth, color, interlace) {
+        (8, 6, 0) => 4,
+        (8, 2, 0) => 3,
+        _ => return Err(err("only non-interlaced 8-bit RGB/RGBA is supported"))
+    };
+    let raw = decompress_to_vec_zlib(&idat).map_err(|_| err("corrupt image data"))?;
+
+    let stride = (width as usize)
+        .checked_mul(channels)
+        .ok_or_else(|| err("image too large"))?;
+    let needed = (stride + 1)
+        .checked_mul(height as usize)
+        .ok_or_else(|| err("image too large"))?;
+    if raw.len() < needed {
+        return Err(err("image data too short"));
+    }
+    let mut pixels = vec![0u8; stride * height as usize];
+    for row in 0..height as usize {
+        let filter = raw[row * (stride + 1)];
+        let src = &raw[row * (stride + 1) + 1..(row + 1) * (stride + 1)];
+        let (done, rest) = pixels.split_at_mut(row * stride);
+        let prev = row.checked_sub(1).map(|_| &done[done.len() - stride..]);
+        let cur = &mut rest[..stride];
+        for i in 0..stride {
+            let a = if i >= channels { cur[i - channels] } else { 0 };
+            let b = prev.map_or(0, |p| p[i]);
+            let c = match prev {
+                Some(p) if i >= channels => p[i - channels],
+                _ => 0
+            };
+            cur[i] = src[i].wrapping_add(match filter {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                4 => paeth(a, b, c),
+                _ => return Err(err("bad row filter"))
+            });
+        }
+    }
+
+    let rgba = if channels == 4 {
+        pixels
+    } else {
+        pixels
+            .chunks_exact(3)
+            .flat_map(|px| [px[0], px[1], px[2], 0xFF])
+            .collect()
+    };
+    Ok(SignatureImage {
+        width,
+        height,
+        rgba
+    })
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = (
+        (p - a as i16).abs(),
+        (p - b as i16).abs(),
+        (p - c as i16).abs()
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use miniz_oxide::deflate::compress_to_vec_zlib;
+
+    use super::*;
+
+    fn png(width: u32, height: u32, color: u8, rows: &[&[u8]]) -> Vec<u8> {
+        let chunk = |out: &mut Vec<u8>, kind: &[u8], body: &[u8]| {
+            out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+            out.extend_from_slice(kind);
+            out.extend_from_slice(body);
+            // CRC is not checked
+            out.extend_from_slice(&[0; 4]);
+        };
+        let mut ihdr = width.to_be_bytes().to_vec();
+        ihdr.extend_from_slice(&height.to_be_bytes());
+        ihdr.extend_from_slice(&[8, color, 0, 0, 0]);
+        let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+        chunk(&mut out, b"IHDR", &ihdr);
+        chunk(&mut out, b"IDAT", &compress_to_vec_zlib(&rows.concat(), 6));
+        chunk(&mut out, b"IEND", &[]);
+        out
+    }
+
+    #[test]
+    fn decodes_rgba_and_rgb_png() {
+        // Row 0 unfiltered, row 1 "up": adds the row above
+        let rgba = png(
+            2,
+            2,
+            6,
+            &[&[0, 1, 2, 3, 4, 5, 6, 7, 8], &[2, 1, 1, 1, 1, 0, 0, 0, 0]]
+        );
+        let image = decode_png(&rgba).unwrap();
+        assert_eq!((image.width, image.height), (2, 2));
+        assert_eq!(image.rgba, [1, 2, 3, 4, 5, 6, 7, 8, 2, 3, 4, 5, 5, 6, 7, 8]);
+
+        // "sub" filter adds the pixel to the left; RGB gets opaque alpha
+        let rgb = png(2, 1, 2, &[&[1, 10, 20, 30, 1, 1, 1]]);
+        assert_eq!(
+            decode_png(&rgb).unwrap().rgba,
+            [10, 20, 30, 255, 11, 21, 31, 255]
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_or_hostile_png() {
+        let full = png(2, 1, 6, &[&[0, 1, 2, 3, 4, 5, 6, 7, 8]]);
+        for cut in [0, 7, 20, 40, full.len() - 20] {
+            assert!(decode_png(&full[..cut]).is_err(), "cut at {cut}");
+        }
+
+        // IDAT claiming 4 GiB
+        let mut huge = full.clone();
+        let idat = huge.windows(4).position(|w| w == b"IDAT").unwrap();
+        huge[idat - 4..idat].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(decode_png(&huge).is_err());
+
+        // Header larger than the pixel data
+        let mut tall = full;
+        tall[16..20].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(decode_png(&tall).is_err());
+    }
+
+    fn contract() -> ContractPdf {
+        let mut pdf = ContractPdf::new(500.0);
+        for (i, text) in ["First line", "Second line"].into_iter().enumerate() {
+            pdf.texts.push(TextRun {
+                text:  text.into(),
+                x:     10.0,
+                y:     i as f64 * 20.0,
+                width: 0.0,
+                size:  14.0
+            });
+        }
+        // Far enough down for a second page
+        pdf.texts.push(TextRun {
+            text:  "Подпись".into(),
+            x:     10.0,
+            y:     1000.0,
+            width: 0.0,
+            size:  14.0
+        });
+        pdf
+    }
+
+    /// Check the xref table against the objects and return the file as text.
+    fn check_structure(bytes: &[u8]) -> String {
+        let text = String::from_utf8_lossy(bytes).into_owned();
+        assert!(text.starts_with("%PDF-1.7\n"));
+        assert!(text.ends_with("%%EOF\n"));
+        let startxref: usize = text
+            .rsplit("startxref\n")
+            .next()
+            .and_then(|t| t.lines().next())
+            .and_then(|n| n.parse().ok())
+            .unwrap();
+        assert!(bytes[startxref..].starts_with(b"xref\n"));
+
+        let table = String::from_utf8_lossy(&bytes[startxref..]);
+        let count: usize = table.lines().nth(1).unwrap()[2..].parse().unwrap();
+        assert!(table.contains(&format!("/Size {count} ")));
+        for (id, line) in table.lines().skip(3).take(count - 1).enumerate() {
+            let off: usize = line[..10].parse().unwrap();
+            let header = format!("{} 0 obj\n", id + 1);
+            assert!(
+                bytes[off..].starts_with(header.as_bytes()),
+                "object {}",
+                id + 1
+            );
+        }
+        text
+    }
+
+    #[test]
+    fn to_bytes_writes_consistent_structure() {
+        let pdf = contract();
+        let bytes = pdf.to_bytes().unwrap();
+        assert_eq!(bytes, pdf.to_bytes().unwrap());
+        let text = check_structure(&bytes);
+        assert!(text.contains("/Count 2 "));
+        assert!(text.contains("/BaseFont /Helvetica"));
+
+        let mut embedded = pdf;
+        let font = include_bytes!("../examples/yew-demo/fonts/DejaVuSans.ttf");
+        embedded.font = Some(TrueTypeFont::parse(font.to_vec()).unwrap());
+        let text = check_structure(&embedded.to_bytes().unwrap());
+        assert!(text.contains("/Subtype /Type0"));
+        assert!(text.contains("/FontFile2 "));
+    }
+
+    #[test]
+    fn to_bytes_rejects_bad_geometry() {
+        assert!(ContractPdf::new(0.0).to_bytes().is_err());
+        let mut pdf = ContractPdf::new(100.0);
+        pdf.page.margin = pdf.page.height;
+        assert!(pdf.to_bytes().is_err());
+    }
+
+    fn envelope(data_url: &str) -> SignedEnvelope {
+        let mut env: SignedEnvelope = serde_json::from_value(serde_json::json!({
+            "document_hash": "", "signature_image_hash": "", "stroke_data_hash": "",
+            "signed_at": "", "signer": { "name": "", "email": null, "id": null },
+            "data_url": data_url, "strokes": []
+        }))
+        .unwrap();
+        env.placement = Some(PlacementGeometry {
+            x:      10.0,
+            y:      20.0,
+            width:  100.0,
+            height: 40.0
+        });
+        env
+    }
+
+    fn base64(bytes: &[u8]) -> String {
+        const ALPHABET: &[u8] =
+            b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let mut out = String::new();
+        for chunk in bytes.chunks(3) {
+            let n = chunk.iter().fold(0u32, |n, b| n << 8 | *b as u32) << (8 * (3 - chunk.len()));
+            for i in 0..=chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            }
+        }
+        while !out.len().is_multiple_of(4) {
+            out.push('=');
+        }
+        out
+    }
+
+    #[test]
+    fn envelope_image_is_decoded_without_js() {
+        let data = png(2, 1, 6, &[&[0, 255, 0, 0, 255, 0, 0, 255, 128]]);
+        let url = format!("data:image/png;base64,{}", base64(&data));
+        let signature = PdfSignature::from_envelope(&envelope(&url)).unwrap();
+        assert_eq!(
+            (signature.canvas_width, signature.canvas_height),
+            (2.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn envelope_data_url_with_a_quote_is_rejected() {
+        let url = "data:image/png;base64,iVBORw0KGgo=');globalThis.pwned=1;('";
+        assert!(matches!(
+            PdfSignature::from_envelope(&envelope(url)),
+            Err(SigError::OpFailed(_))
+        ));
+    }
+}
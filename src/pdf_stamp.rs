@@ -0,0 +1,1534 @@
+// src/pdf_stamp.rs
+
+use std::{collections::BTreeMap, fmt::Write as _};
+
+use miniz_oxide::{deflate::compress_to_vec_zlib, inflate::decompress_to_vec_zlib};
+
+use crate::{error::SigError, format::num, pdf::PdfSignature, placement::FitMode};
+
+/// zlib level for the streams of the update.
+const COMPRESSION_LEVEL: u8 = 6;
+/// Guard against cyclic page trees, field trees and `/Prev` chains.
+const MAX_DEPTH: usize = 64;
+
+/// Rectangle on a PDF page in points, origin at the bottom-left corner of
+/// the page as it is displayed: the visible area (`/CropBox`, else
+/// `/MediaBox`) after `/Rotate`. On an unrotated page whose box starts at
+/// 0 0 this is plain user space (as in widget `/Rect`).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PdfRect {
+    pub x:      f64,
+    pub y:      f64,
+    pub width:  f64,
+    pub height: f64
+}
+
+/// Where on an existing PDF the signature goes.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StampTarget {
+    /// Rectangle on the page with this zero-based index.
+    Rect { page: usize, rect: PdfRect },
+    /// Widget of the AcroForm field with this name (full dotted name or
+    /// its last part), e.g. a `/FT /Sig` field.
+    Field(String)
+}
+
+/// Draw `signature` onto an existing PDF and return the file with an
+/// incremental update appended; the original bytes are kept unchanged in
+/// front. The signature is drawn as a Form XObject (vector strokes, or
+/// the bitmap if there are none), fit into the target keeping its aspect.
+///
+/// Encrypted files are rejected.
+pub fn stamp_signature(
+    pdf: &[u8],
+    target: &StampTarget,
+    signature: &PdfSignature
+) -> Result<Vec<u8>, SigError> {
+    let file = PdfFile::parse(pdf)?;
+    if file.trailer.get(b"Encrypt").is_some() {
+        return Err(pdf_err("encrypted PDFs are not supported"));
+    }
+
+    let pages = file.pages()?;
+    let (page_idx, rect) = match target {
+        StampTarget::Rect {
+            page,
+            rect
+        } => (*page, *rect),
+        StampTarget::Field(name) => file.field_widget(name, &pages)?
+    };
+    let page = pages
+        .get(page_idx)
+        .ok_or_else(|| pdf_err(&format!("page {page_idx} out of range ({})", pages.len())))?;
+    let rect = match target {
+        StampTarget::Rect {
+            ..
+        } => rect,
+        // Widget rectangles are in user space
+        StampTarget::Field(_) => page.view.displayed(rect)
+    };
+
+    let mut update = Update::new(pdf, &file);
+
+    // Signature as a Form XObject in its own box
+    let image = signature.raster();
+    let image_id = image.map(|img| {
+        let (dims, rgb, alpha) = img.streams();
+        let mask = update.alloc();
+        update.stream(mask, &format!("{dims} /ColorSpace /DeviceGray"), &alpha);
+        let id = update.alloc();
+        update.stream(
+            id,
+            &format!("{dims} /ColorSpace /DeviceRGB /SMask {mask} 0 R"),
+            &rgb
+        );
+        id
+    });
+    let form = update.alloc();
+    let resources = image_id
+        .map(|id| format!(" /Resources << /XObject << /Im1 {id} 0 R >> >>"))
+        .unwrap_or_default();
+    update.stream(
+        form,
+        &format!(
+            "/Type /XObject /Subtype /Form /BBox [0 0 {} {}]{resources}",
+            num(rect.width),
+            num(rect.height)
+        ),
+        signature
+            .draw_ops(
+                0.0,
+                rect.height,
+                rect.width,
+                rect.height,
+                FitMode::Contain,
+                image_id.map(|_| "Im1")
+            )
+            .as_bytes()
+    );
+
+    // Page: isolate the existing content in q/Q, then draw the form
+    let mut xobjects = match file.resolve(&page.resources)? {
+        Obj::Dict(res) => match res.get(b"XObject") {
+            Some(x) => file.resolve(x)?.into_dict().unwrap_or_default(),
+            None => Dict::default()
+        },
+        _ => Dict::default()
+    };
+    let name = (1..)
+        .map(|i| format!("CSig{i}").into_bytes())
+        .find(|n| xobjects.get(n).is_none())
+        .unwrap_or_default();
+    xobjects.set(name.clone(), Obj::Ref(form, 0));
+    let mut resources = file
+        .resolve(&page.resources)?
+        .into_dict()
+        .unwrap_or_default();
+    resources.set(b"XObject".to_vec(), Obj::Dict(xobjects));
+
+    let open = update.alloc();
+    update.stream(open, "", b"q\n");
+    let draw = update.alloc();
+    let matrix = page.view.matrix(rect).map(num).join(" ");
+    update.stream(
+        draw,
+        "",
+        format!(
+            "Q\nq {matrix} cm /{} Do Q\n",
+            String::from_utf8_lossy(&name)
+        )
+        .as_bytes()
+    );
+    let mut contents = vec![Obj::Ref(open, 0)];
+    contents.extend(file.contents(&page.dict)?);
+    contents.push(Obj::Ref(draw, 0));
+
+    let mut dict = page.dict.clone();
+    dict.set(b"Resources".to_vec(), Obj::Dict(resources));
+    dict.set(b"Contents".to_vec(), Obj::Array(contents));
+    update.object(page.num, page.generation, &Obj::Dict(dict).to_pdf());
+
+    Ok(update.finish())
+}
+
+fn pdf_err(msg: &str) -> SigError {
+    SigError::OpFailed(format!("pdf: {msg}"))
+}
+
+// ---- Objects ----
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Dict(Vec<(Vec<u8>, Obj)>);
+
+impl Dict {
+    fn get(&self, key: &[u8]) -> Option<&Obj> {
+        self.0.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    fn set(&mut self, key: Vec<u8>, value: Obj) {
+        match self.0.iter_mut().find(|(k, _)| *k == key) {
+            Some(entry) => entry.1 = value,
+            None => self.0.push((key, value))
+        }
+    }
+
+    fn int(&self, key: &[u8]) -> Option<i64> {
+        match self.get(key)? {
+            Obj::Int(v) => Some(*v),
+            _ => None
+        }
+    }
+
+    fn name_is(&self, key: &[u8], value: &[u8]) -> bool {
+        matches!(self.get(key), Some(Obj::Name(n)) if n == value)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+enum Obj {
+    #[default]
+    Null,
+    Bool(bool),
+    Int(i64),
+    Real(f64),
+    Name(Vec<u8>),
+    Str(Vec<u8>),
+    Array(Vec<Obj>),
+    Dict(Dict),
+    Ref(u32, u16),
+    /// Dict and raw (still encoded) data.
+    Stream(Dict, Vec<u8>)
+}
+
+impl Obj {
+    fn into_dict(self) -> Option<Dict> {
+        match self {
+            Self::Dict(d) | Self::Stream(d, _) => Some(d),
+            _ => None
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Int(v) => Some(*v as f64),
+            Self::Real(v) => Some(*v),
+            _ => None
+        }
+    }
+
+    /// Serialize (streams are never re-written, so they aren't supported).
+    fn to_pdf(&self) -> String {
+        let mut out = String::new();
+        self.write(&mut out);
+        out
+    }
+
+    fn write(&self, out: &mut String) {
+        match self {
+            Self::Null | Self::Stream(..) => out.push_str("null"),
+            Self::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+            Self::Int(v) => {
+                let _ = write!(out, "{v}");
+            }
+            Self::Real(v) => out.push_str(&num(*v)),
+            Self::Name(n) => {
+                out.push('/');
+                for &b in n {
+                    if b.is_ascii_graphic() && !is_delimiter(b) && b != b'#' {
+                        out.push(b as char);
+                    } else {
+                        let _ = write!(out, "#{b:02X}");
+                    }
+                }
+            }
+            Self::Str(s) => {
+                out.push('<');
+                for b in s {
+                    let _ = write!(out, "{b:02X}");
+                }
+                out.push('>');
+            }
+            Self::Array(items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push(' ');
+                    }
+                    item.write(out);
+                }
+                out.push(']');
+            }
+            Self::Dict(d) => {
+                out.push_str("<<");
+                for (k, v) in &d.0 {
+                    Self::Name(k.clone()).write(out);
+                    out.push(' ');
+                    v.write(out);
+                }
+                out.push_str(">>");
+            }
+            Self::Ref(n, g) => {
+                let _ = write!(out, "{n} {g} R");
+            }
+        }
+    }
+}
+
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, b'\0' | b'\t' | b'\n' | b'\x0C' | b'\r' | b' ')
+}
+
+fn is_delimiter(b: u8) -> bool {
+    matches!(
+        b,
+        b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+    )
+}
+
+// ---- Lexer / parser ----
+
+struct Parser<'a> {
+    data: &'a [u8],
+    pos:  usize
+}
+
+impl<'a> Parser<'a> {
+    fn new(data: &'a [u8], pos: usize) -> Self {
+        Self {
+            data,
+            pos
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) {
+        while let Some(b) = self.peek() {
+            if is_whitespace(b) {
+                self.pos += 1;
+            } else if b == b'%' {
+                while self.peek().is_some_and(|b| b != b'\n' && b != b'\r') {
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn token(&mut self) -> &'a [u8] {
+        self.skip_ws();
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|b| !is_whitespace(b) && !is_delimiter(b))
+        {
+            self.pos += 1;
+        }
+        &self.data[start..self.pos]
+    }
+
+    fn keyword(&mut self, kw: &[u8]) -> Result<(), SigError> {
+        if self.token() == kw {
+            Ok(())
+        } else {
+            Err(pdf_err(&format!(
+                "expected '{}'",
+                String::from_utf8_lossy(kw)
+            )))
+        }
+    }
+
+    fn uint(&mut self) -> Option<u64> {
+        std::str::from_utf8(self.token()).ok()?.parse().ok()
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Obj, SigError> {
+        if depth > MAX_DEPTH {
+            return Err(pdf_err("nesting too deep"));
+        }
+        self.skip_ws();
+        let b = self
+            .peek()
+            .ok_or_else(|| pdf_err("unexpected end of file"))?;
+        match b {
+            b'<' if self.data.get(self.pos + 1) == Some(&b'<') => {
+                self.pos += 2;
+                let mut dict = Dict::default();
+                loop {
+                    self.skip_ws();
+                    if self.data.get(self.pos..self.pos + 2) == Some(b">>") {
+                        self.pos += 2;
+                        return Ok(Obj::Dict(dict));
+                    }
+                    let Obj::Name(key) = self.object(depth + 1)? else {
+                        return Err(pdf_err("dictionary key is not a name"));
+                    };
+                    let value = self.object(depth + 1)?;
+                    dict.0.push((key, value));
+                }
+            }
+            b'<' => {
+                self.pos += 1;
+                let mut hex = Vec::new();
+                while let Some(b) = self.peek() {
+                    self.pos += 1;
+                    match b {
+                        b'>' => break,
+                        b if b.is_ascii_hexdigit() => hex.push(b),
+                        _ => {}
+                    }
+                }
+                if hex.len() % 2 == 1 {
+                    hex.push(b'0');
+                }
+                let bytes = hex
+                    .chunks(2)
+                    .map(|p| {
+                        u8::from_str_radix(std::str::from_utf8(p).unwrap_or("00"), 16).unwrap_or(0)
+                    })
+                    .collect();
+                Ok(Obj::Str(bytes))
+            }
+            b'(' => self.literal_string().map(Obj::Str),
+            b'[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_ws();
+                    if self.peek() == Some(b']') {
+                        self.pos += 1;
+                        return Ok(Obj::Array(items));
+                    }
+                    items.push(self.object(depth + 1)?);
+                }
+            }
+            b'/' => {
+                self.pos += 1;
+                let raw = self.token();
+                let mut name = Vec::with_capacity(raw.len());
+                let mut i = 0;
+                while i < raw.len() {
+                    if raw[i] == b'#'
+                        && let Some(v) = raw
+                            .get(i + 1..i + 3)
+                            .and_then(|h| std::str::from_utf8(h).ok())
+                            .and_then(|h| u8::from_str_radix(h, 16).ok())
+                    {
+                        name.push(v);
+                        i += 3;
+                    } else {
+                        name.push(raw[i]);
+                        i += 1;
+                    }
+                }
+                Ok(Obj::Name(name))
+            }
+            _ => {
+                let start = self.pos;
+                let tok = self.token();
+                match tok {
+                    b"true" => return Ok(Obj::Bool(true)),
+                    b"false" => return Ok(Obj::Bool(false)),
+                    b"null" => return Ok(Obj::Null),
+                    _ => {}
+                }
+                let text = std::str::from_utf8(tok).map_err(|_| pdf_err("bad token"))?;
+                if let Ok(n) = text.parse::<i64>() {
+                    // `n g R`?
+                    let save = self.pos;
+                    if let (Some(g), b"R") = (self.uint(), self.token())
+                        && let (Ok(n), Ok(g)) = (u32::try_from(n), u16::try_from(g))
+                    {
+                        return Ok(Obj::Ref(n, g));
+                    }
+                    self.pos = save;
+                    return Ok(Obj::Int(n));
+                }
+                text.parse::<f64>().map(Obj::Real).map_err(|_| {
+                    self.pos = start;
+                    pdf_err(&format!("unexpected token '{text}'"))
+                })
+            }
+        }
+    }
+
+    fn literal_string(&mut self) -> Result<Vec<u8>, SigError> {
+        self.pos += 1;
+        let mut out = Vec::new();
+        let mut depth = 1;
+        while let Some(b) = self.peek() {
+            self.pos += 1;
+            match b {
+                b'(' => {
+                    depth += 1;
+                    out.push(b);
+                }
+                b')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(out);
+                    }
+                    out.push(b);
+                }
+                b'\\' => {
+                    let Some(e) = self.peek() else { break };
+                    self.pos += 1;
+                    match e {
+                        b'n' => out.push(b'\n'),
+                        b'r' => out.push(b'\r'),
+                        b't' => out.push(b'\t'),
+                        b'b' => out.push(0x08),
+                        b'f' => out.push(0x0C),
+                        b'0'..=b'7' => {
+                            let mut v = (e - b'0') as u32;
+                            for _ in 0..2 {
+                                match self.peek() {
+                                    Some(d @ b'0'..=b'7') => {
+                                        v = v * 8 + (d - b'0') as u32;
+                                        self.pos += 1;
+                                    }
+                                    _ => break
+                                }
+                            }
+                            out.push(v as u8);
+                        }
+                        // Line continuation
+                        b'\r' => {
+                            if self.peek() == Some(b'\n') {
+                                self.pos += 1;
+                            }
+                        }
+                        b'\n' => {}
+                        other => out.push(other)
+                    }
+                }
+                _ => out.push(b)
+            }
+        }
+        Err(pdf_err("unterminated string"))
+    }
+}
+
+// ---- File structure ----
+
+#[derive(Clone, Copy, Debug)]
+enum Location {
+    Offset(usize),
+    /// Object stream number and index inside it.
+    InStream(u32, usize)
+}
+
+struct Page {
+    num:        u32,
+    generation: u16,
+    dict:       Dict,
+    /// Own or inherited `/Resources` (may be a reference).
+    resources:  Obj,
+    view:       PageView
+}
+
+/// Page attributes inherited through the page tree.
+#[derive(Clone, Default)]
+struct Inherited {
+    resources: Obj,
+    media_box: Obj,
+    crop_box:  Obj,
+    rotate:    Obj
+}
+
+impl Inherited {
+    fn merge(&self, dict: &Dict) -> Self {
+        let pick = |key: &[u8], parent: &Obj| dict.get(key).cloned().unwrap_or(parent.clone());
+        Self {
+            resources: pick(b"Resources", &self.resources),
+            media_box: pick(b"MediaBox", &self.media_box),
+            crop_box:  pick(b"CropBox", &self.crop_box),
+            rotate:    pick(b"Rotate", &self.rotate)
+        }
+    }
+}
+
+/// Visible area of a page in user space and its clockwise `/Rotate`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct PageView {
+    area:   PdfRect,
+    /// 0, 90, 180 or 270.
+    rotate: u16
+}
+
+impl PageView {
+    /// Displayed point -> user space.
+    fn user_point(&self, x: f64, y: f64) -> (f64, f64) {
+        let a = &self.area;
+        match self.rotate {
+            90 => (a.x + a.width - y, a.y + x),
+            180 => (a.x + a.width - x, a.y + a.height - y),
+            270 => (a.x + y, a.y + a.height - x),
+            _ => (a.x + x, a.y + y)
+        }
+    }
+
+    /// User-space rectangle -> as displayed.
+    fn displayed(&self, rect: PdfRect) -> PdfRect {
+        let a = &self.area;
+        let corner = |x: f64, y: f64| match self.rotate {
+            90 => (y - a.y, a.x + a.width - x),
+            180 => (a.x + a.width - x, a.y + a.height - y),
+            270 => (a.y + a.height - y, x - a.x),
+            _ => (x - a.x, y - a.y)
+        };
+        let (x0, y0) = corner(rect.x, rect.y);
+        let (x1, y1) = corner(rect.x + rect.width, rect.y + rect.height);
+        PdfRect {
+            x:      x0.min(x1),
+            y:      y0.min(y1),
+            width:  (x1 - x0).abs(),
+            height: (y1 - y0).abs()
+        }
+    }
+
+    /// `cm` operands mapping a box of `rect`'s size at the origin onto
+    /// displayed `rect`, upright as the page is shown.
+    fn matrix(&self, rect: PdfRect) -> [f64; 6] {
+        let (e, f) = self.user_point(rect.x, rect.y);
+        let (a, b, c, d) = match self.rotate {
+            90 => (0.0, 1.0, -1.0, 0.0),
+            180 => (-1.0, 0.0, 0.0, -1.0),
+            270 => (0.0, -1.0, 1.0, 0.0),
+            _ => (1.0, 0.0, 0.0, 1.0)
+        };
+        [a, b, c, d, e, f]
+    }
+}
+
+struct PdfFile<'a> {
+    data:           &'a [u8],
+    /// Object number -> (generation, location), newest revision wins.
+    xref:           BTreeMap<u32, (u16, Location)>,
+    /// Trailer of the newest revision (or the dict of its xref stream).
+    trailer:        Dict,
+    startxref:      usize,
+    xref_is_stream: bool
+}
+
+impl<'a> PdfFile<'a> {
+    fn parse(data: &'a [u8]) -> Result<Self, SigError> {
+        let tail_start = data.len().saturating_sub(2048);
+        let tail = &data[tail_start..];
+        let at = tail
+            .windows(9)
+            .rposition(|w| w == b"startxref")
+            .ok_or_else(|| pdf_err("no startxref"))?;
+        let startxref = Parser::new(data, tail_start + at + 9)
+            .uint()
+            .ok_or_else(|| pdf_err("bad startxref"))? as usize;
+
+        let mut file = Self {
+            data,
+            xref: BTreeMap::new(),
+            trailer: Dict::default(),
+            startxref,
+            xref_is_stream: false
+        };
+
+        let mut next = Some(startxref);
+        let mut first = true;
+        for _ in 0..MAX_DEPTH {
+            let Some(offset) = next else { break };
+            let (trailer, is_stream) = file.read_xref_section(offset)?;
+            if let Some(stm) = trailer.int(b"XRefStm") {
+                // Hybrid file: the stream holds the compressed objects
+                file.read_xref_section(stm as usize)?;
+            }
+            next = trailer.int(b"Prev").map(|p| p as usize);
+            if first {
+                file.trailer = trailer;
+                file.xref_is_stream = is_stream;
+                first = false;
+            }
+        }
+        if file.trailer.get(b"Root").is_none() {
+            return Err(pdf_err("trailer has no /Root"));
+        }
+        Ok(file)
+    }
+
+    /// Read one xref table or xref stream; earlier-read (newer) entries win.
+    fn read_xref_section(&mut self, offset: usize) -> Result<(Dict, bool), SigError> {
+        let mut p = Parser::new(self.data, offset);
+        p.skip_ws();
+        if self.data.get(p.pos..p.pos + 4) == Some(b"xref") {
+            p.pos += 4;
+            loop {
+                let save = p.pos;
+                let (Some(start), Some(count)) = (p.uint(), p.uint()) else {
+                    p.pos = save;
+                    break;
+                };
+                for i in 0..count {
+                    let off = p.uint().ok_or_else(|| pdf_err("bad xref entry"))?;
+                    let generation = p.uint().ok_or_else(|| pdf_err("bad xref entry"))?;
+                    let kind = p.token();
+                    let num = (start + i) as u32;
+                    if kind == b"n" {
+                        self.xref
+                            .entry(num)
+                            .or_insert((generation as u16, Location::Offset(off as usize)));
+                    }
+                }
+            }
+            p.keyword(b"trailer")?;
+            let trailer = p
+                .object(0)?
+                .into_dict()
+                .ok_or_else(|| pdf_err("trailer is not a dictionary"))?;
+            return Ok((trailer, false));
+        }
+
+        // Cross-reference stream
+        let Obj::Stream(dict, raw) = self.parse_indirect(offset)? else {
+            return Err(pdf_err("startxref points to neither a table nor a stream"));
+        };
+        let data = self.decode(&dict, &raw)?;
+        let w: Vec<usize> = match dict.get(b"W") {
+            Some(Obj::Array(w)) if w.len() == 3 => w
+                .iter()
+                .map(|v| v.as_f64().unwrap_or(0.0) as usize)
+                .collect(),
+            _ => return Err(pdf_err("xref stream has no /W"))
+        };
+        let size = dict.int(b"Size").unwrap_or(0);
+        let index: Vec<i64> = match dict.get(b"Index") {
+            Some(Obj::Array(ix)) => ix
+                .iter()
+                .filter_map(|v| v.as_f64())
+                .map(|v| v as i64)
+                .collect(),
+            _ => vec![0, size]
+        };
+        let row = w.iter().sum::<usize>();
+        if row == 0 {
+            return Err(pdf_err("bad xref stream /W"));
+        }
+        let field = |bytes: &[u8]| bytes.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64);
+        let mut rows = data.chunks_exact(row);
+        for pair in index.chunks_exact(2) {
+            for i in 0..pair[1].max(0) {
+                let Some(r) = rows.next() else { break };
+                let kind = if w[0] == 0 { 1 } else { field(&r[..w[0]]) };
+                let a = field(&r[w[0]..w[0] + w[1]]);
+                let b = field(&r[w[0] + w[1]..]);
+                let num = (pair[0] + i) as u32;
+                let entry = match kind {
+                    1 => (b as u16, Location::Offset(a as usize)),
+                    2 => (0, Location::InStream(a as u32, b as usize)),
+                    _ => continue
+                };
+                self.xref.entry(num).or_insert(entry);
+            }
+        }
+        Ok((dict, true))
+    }
+
+    /// Parse `n g obj ... endobj` at `offset`.
+    fn parse_indirect(&self, offset: usize) -> Result<Obj, SigError> {
+        let mut p = Parser::new(self.data, offset);
+        p.uint().ok_or_else(|| pdf_err("bad object header"))?;
+        p.uint().ok_or_else(|| pdf_err("bad object header"))?;
+        p.keyword(b"obj")?;
+        let obj = p.object(0)?;
+        let Obj::Dict(dict) = obj else {
+            return Ok(obj);
+        };
+        if p.token() != b"stream" {
+            return Ok(Obj::Dict(dict));
+        }
+        // EOL after `stream` is CRLF or LF
+        if p.peek() == Some(b'\r') {
+            p.pos += 1;
+        }
+        if p.peek() == Some(b'\n') {
+            p.pos += 1;
+        }
+        let len = match dict.get(b"Length") {
+            Some(Obj::Int(n)) => *n,
+            Some(r @ Obj::Ref(..)) => match self.resolve(r)? {
+                Obj::Int(n) => n,
+                _ => return Err(pdf_err("bad stream /Length"))
+            },
+            _ => return Err(pdf_err("stream has no /Length"))
+        };
+        let end = usize::try_from(len)
+            .ok()
+            .and_then(|len| p.pos.checked_add(len))
+            .ok_or_else(|| pdf_err("bad stream /Length"))?;
+        let raw = self
+            .data
+            .get(p.pos..end)
+            .ok_or_else(|| pdf_err("stream runs past end of file"))?
+            .to_vec();
+        Ok(Obj::Stream(dict, raw))
+    }
+
+    fn object(&self, num: u32, depth: usize) -> Result<Obj, SigError> {
+        if depth > MAX_DEPTH {
+            return Err(pdf_err("reference chain too deep"));
+        }
+        match self.xref.get(&num) {
+            None => Ok(Obj::Null),
+            Some((_, Location::Offset(off))) => self.parse_indirect(*off),
+            Some((_, Location::InStream(stm, idx))) => {
+                let Obj::Stream(dict, raw) = self.object(*stm, depth + 1)? else {
+                    return Err(pdf_err("object stream is not a stream"));
+                };
+                let data = self.decode(&dict, &raw)?;
+                let n = dict.int(b"N").unwrap_or(0) as usize;
+                let first = dict.int(b"First").unwrap_or(0) as usize;
+                let mut p = Parser::new(&data, 0);
+                let mut offset = None;
+                for i in 0..n {
+                    let (Some(_), Some(off)) = (p.uint(), p.uint()) else {
+                        break;
+                    };
+                    if i == *idx {
+                        offset = Some(off as usize);
+                        break;
+                    }
+                }
+                let offset = offset.ok_or_else(|| pdf_err("object missing from object stream"))?;
+                Parser::new(&data, first + offset).object(0)
+            }
+        }
+    }
+
+    /// Follow a reference (direct objects are returned as is).
+    fn resolve(&self, obj: &Obj) -> Result<Obj, SigError> {
+        let mut cur = obj.clone();
+        for depth in 0..MAX_DEPTH {
+            match cur {
+                Obj::Ref(n, _) => cur = self.object(n, depth)?,
+                other => return Ok(other)
+            }
+        }
+        Err(pdf_err("reference chain too deep"))
+    }
+
+    fn resolve_dict(&self, obj: &Obj) -> Result<Dict, SigError> {
+        self.resolve(obj)?
+            .into_dict()
+            .ok_or_else(|| pdf_err("expected a dictionary"))
+    }
+
+    /// Decode a Flate stream (the only filter xref and object streams use).
+    fn decode(&self, dict: &Dict, raw: &[u8]) -> Result<Vec<u8>, SigError> {
+        let filter = match dict.get(b"Filter") {
+            None => return Ok(raw.to_vec()),
+            Some(Obj::Name(n)) => n.clone(),
+            Some(Obj::Array(a)) if a.len() == 1 => match &a[0] {
+                Obj::Name(n) => n.clone(),
+                _ => Vec::new()
+            },
+            _ => Vec::new()
+        };
+        if filter != b"FlateDecode" {
+            return Err(pdf_err("unsupported stream filter"));
+        }
+        let data = decompress_to_vec_zlib(raw).map_err(|_| pdf_err("corrupt Flate stream"))?;
+
+        let params = match dict.get(b"DecodeParms") {
+            Some(Obj::Dict(d)) => d.clone(),
+            Some(Obj::Array(a)) if a.len() == 1 => {
+                self.resolve(&a[0])?.into_dict().unwrap_or_default()
+            }
+            _ => return Ok(data)
+        };
+        match params.int(b"Predictor").unwrap_or(1) {
+            1 => Ok(data),
+            p if p >= 10 => {
+                let colors = params.int(b"Colors").unwrap_or(1) as usize;
+                let bits = params.int(b"BitsPerComponent").unwrap_or(8) as usize;
+                let columns = params.int(b"Columns").unwrap_or(1) as usize;
+                Ok(png_unpredict(
+                    &data,
+                    (colors * bits * columns).div_ceil(8),
+                    (colors * bits).div_ceil(8)
+                ))
+            }
+            _ => Err(pdf_err("unsupported stream predictor"))
+        }
+    }
+
+    /// Leaf pages in document order.
+    fn pages(&self) -> Result<Vec<Page>, SigError> {
+        let root = self.resolve_dict(self.trailer.get(b"Root").unwrap_or(&Obj::Null))?;
+        let pages = root
+            .get(b"Pages")
+            .ok_or_else(|| pdf_err("catalog has no /Pages"))?;
+        let mut out = Vec::new();
+        self.collect_pages(pages, &Inherited::default(), &mut out, 0)?;
+        Ok(out)
+    }
+
+    fn collect_pages(
+        &self,
+        node: &Obj,
+        inherited: &Inherited,
+        out: &mut Vec<Page>,
+        depth: usize
+    ) -> Result<(), SigError> {
+        if depth > MAX_DEPTH {
+            return Err(pdf_err("page tree too deep"));
+        }
+        let dict = self.resolve_dict(node)?;
+        let attrs = inherited.merge(&dict);
+        if dict.name_is(b"Type", b"Pages") || dict.get(b"Kids").is_some() {
+            if let Some(Obj::Array(kids)) =
+                dict.get(b"Kids").map(|k| self.resolve(k)).transpose()?
+            {
+                for kid in &kids {
+                    self.collect_pages(kid, &attrs, out, depth + 1)?;
+                }
+            }
+            return Ok(());
+        }
+        let Obj::Ref(num, generation) = node else {
+            return Err(pdf_err("page is not an indirect object"));
+        };
+        // US Letter when the box is missing
+        let area = match self.rect(&attrs.crop_box)? {
+            Some(crop) => crop,
+            None => self.rect(&attrs.media_box)?.unwrap_or(PdfRect {
+                x:      0.0,
+                y:      0.0,
+                width:  612.0,
+                height: 792.0
+            })
+        };
+        let rotate = match self.resolve(&attrs.rotate)? {
+            Obj::Int(r) => r.rem_euclid(360) as u16 / 90 * 90,
+            _ => 0
+        };
+        out.push(Page {
+            num: *num,
+            generation: *generation,
+            dict,
+            resources: attrs.resources,
+            view: PageView {
+                area,
+                rotate
+            }
+        });
+        Ok(())
+    }
+
+    /// Normalized `[x0 y0 x1 y1]` rectangle (`/MediaBox`, `/Rect`, ...).
+    fn rect(&self, obj: &Obj) -> Result<Option<PdfRect>, SigError> {
+        let Obj::Array(r) = self.resolve(obj)? else {
+            return Ok(None);
+        };
+        if r.len() != 4 {
+            return Ok(None);
+        }
+        let mut v = [0.0; 4];
+        for (slot, o) in v.iter_mut().zip(&r) {
+            *slot = self.resolve(o)?.as_f64().unwrap_or(0.0);
+        }
+        Ok(Some(PdfRect {
+            x:      v[0].min(v[2]),
+            y:      v[1].min(v[3]),
+            width:  (v[2] - v[0]).abs(),
+            height: (v[3] - v[1]).abs()
+        }))
+    }
+
+    /// Existing content streams of a page, as references.
+    fn contents(&self, page: &Dict) -> Result<Vec<Obj>, SigError> {
+        Ok(match page.get(b"Contents") {
+            None => Vec::new(),
+            Some(Obj::Array(items)) => items.clone(),
+            Some(r @ Obj::Ref(..)) => match self.resolve(r)? {
+                // Indirect array of streams
+                Obj::Array(items) => items,
+                _ => vec![r.clone()]
+            },
+            Some(_) => return Err(pdf_err("bad page /Contents"))
+        })
+    }
+
+    /// Page index and user-space rectangle of the widget of AcroForm field
+    /// `name`.
+    fn field_widget(&self, name: &str, pages: &[Page]) -> Result<(usize, PdfRect), SigError> {
+        let root = self.resolve_dict(self.trailer.get(b"Root").unwrap_or(&Obj::Null))?;
+        let form = root
+            .get(b"AcroForm")
+            .map(|f| self.resolve_dict(f))
+            .transpose()?
+            .ok_or_else(|| pdf_err("document has no AcroForm"))?;
+        let fields = match form.get(b"Fields").map(|f| self.resolve(f)).transpose()? {
+            Some(Obj::Array(f)) => f,
+            _ => Vec::new()
+        };
+
+        let (widget_num, widget) = fields
+            .iter()
+            .find_map(|f| self.find_field(f, "", name, 0).transpose())
+            .transpose()?
+            .ok_or_else(|| SigError::ElementNotFound(format!("PDF field '{name}'")))?;
+
+        let rect = self
+            .rect(widget.get(b"Rect").unwrap_or(&Obj::Null))?
+            .ok_or_else(|| pdf_err("field widget has no /Rect"))?;
+
+        // `/P` is optional; fall back to the page listing the widget
+        let page = match widget.get(b"P") {
+            Some(Obj::Ref(p, _)) => pages.iter().position(|pg| pg.num == *p),
+            _ => None
+        }
+        .or_else(|| {
+            pages.iter().position(|pg| {
+                let annots = pg.dict.get(b"Annots").and_then(|a| self.resolve(a).ok());
+                matches!(annots, Some(Obj::Array(a))
+                    if a.iter().any(|o| matches!(o, Obj::Ref(n, _) if Some(*n) == widget_num)))
+            })
+        })
+        .ok_or_else(|| pdf_err(&format!("page of field '{name}' not found")))?;
+        Ok((page, rect))
+    }
+
+    /// Depth-first search for the field named `wanted`; returns the widget
+    /// (object number, dict) that carries the `/Rect`.
+    fn find_field(
+        &self,
+        node: &Obj,
+        parent: &str,
+        wanted: &str,
+        depth: usize
+    ) -> Result<Option<(Option<u32>, Dict)>, SigError> {
+        if depth > MAX_DEPTH {
+            return Err(pdf_err("field tree too deep"));
+        }
+        let num = match node {
+            Obj::Ref(n, _) => Some(*n),
+            _ => None
+        };
+        let dict = self.resolve_dict(node)?;
+        let partial = match dict.get(b"T") {
+            Some(Obj::Str(t)) => Some(text_string(t)),
+            _ => None
+        };
+        let full = match (&partial, parent.is_empty()) {
+            (Some(t), true) => t.clone(),
+            (Some(t), false) => format!("{parent}.{t}"),
+            (None, _) => parent.to_string()
+        };
+        let kids = match dict.get(b"Kids").map(|k| self.resolve(k)).transpose()? {
+            Some(Obj::Array(k)) => k,
+            _ => Vec::new()
+        };
+
+        if partial.is_some() && (full == wanted || partial.as_deref() == Some(wanted)) {
+            if dict.get(b"Rect").is_some() {
+                return Ok(Some((num, dict)));
+            }
+            // Separate widget annotations: take the first one
+            for kid in &kids {
+                let kid_dict = self.resolve_dict(kid)?;
+                if kid_dict.get(b"Rect").is_some() {
+                    let kid_num = match kid {
+                        Obj::Ref(n, _) => Some(*n),
+                        _ => None
+                    };
+                    return Ok(Some((kid_num, kid_dict)));
+                }
+            }
+            return Ok(None);
+        }
+        for kid in &kids {
+            if let Some(found) = self.find_field(kid, &full, wanted, depth + 1)? {
+                return Ok(Some(found));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// PDF text string: UTF-16BE with BOM, else (near enough) Latin-1.
+fn text_string(bytes: &[u8]) -> String {
+    match bytes.strip_prefix(&[0xFE, 0xFF]) {
+        Some(utf16) => {
+            let units: Vec<u16> = utf16
+                .chunks_exact(2)
+                .map(|b| u16::from_be_bytes([b[0], b[1]]))
+                .collect();
+            String::from_utf16_lossy(&units)
+        }
+        None => bytes.iter().map(|&b| b as char).collect()
+    }
+}
+
+/// Undo PNG row predictors (`/Predictor` 10..=15).
+fn png_unpredict(data: &[u8], stride: usize, bpp: usize) -> Vec<u8> {
+    let mut out: Vec<u8> = Vec::with_capacity(data.len());
+    for (row, chunk) in data.chunks(stride + 1).enumerate() {
+        let Some((&filter, src)) = chunk.split_first() else {
+            break;
+        };
+        let start = out.len();
+        for i in 0..src.len() {
+            let a = if i >= bpp { out[start + i - bpp] } else { 0 };
+            let b = if row > 0 { out[start + i - stride] } else { 0 };
+            let c = if row > 0 && i >= bpp {
+                out[start + i - stride - bpp]
+            } else {
+                0
+            };
+            let v = match filter {
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                4 => {
+                    let p = a as i16 + b as i16 - c as i16;
+                    let (pa, pb, pc) = (
+                        (p - a as i16).abs(),
+                        (p - b as i16).abs(),
+                        (p - c as i16).abs()
+                    );
+                    if pa <= pb && pa <= pc {
+                        a
+                    } else if pb <= pc {
+                        b
+                    } else {
+                        c
+                    }
+                }
+                _ => 0
+            };
+            out.push(src[i].wrapping_add(v));
+        }
+    }
+    out
+}
+
+// ---- Incremental update ----
+
+struct Update<'a> {
+    out:     Vec<u8>,
+    file:    &'a PdfFile<'a>,
+    next:    u32,
+    /// Object number -> (generation, offset) of everything written.
+    written: BTreeMap<u32, (u16, usize)>
+}
+
+impl<'a> Update<'a> {
+    fn new(original: &[u8], file: &'a PdfFile<'a>) -> Self {
+        let mut out = original.to_vec();
+        if !out.ends_with(b"\n") {
+            out.push(b'\n');
+        }
+        let size = file.trailer.int(b"Size").unwrap_or(0).max(0) as u32;
+        let max = file.xref.keys().next_back().map_or(0, |n| n + 1);
+        Self {
+            out,
+            file,
+            next: size.max(max),
+            written: BTreeMap::new()
+        }
+    }
+
+    fn alloc(&mut self) -> u32 {
+        let id = self.next;
+        self.next += 1;
+        id
+    }
+
+    fn object(&mut self, num: u32, generation: u16, body: &str) {
+        self.written.insert(num, (generation, self.out.len()));
+        self.out
+            .extend_from_slice(format!("{num} {generation} obj\n{body}\nendobj\n").as_bytes());
+    }
+
+    fn stream(&mut self, num: u32, extra: &str, data: &[u8]) {
+        let packed = compress_to_vec_zlib(data, COMPRESSION_LEVEL);
+        self.written.insert(num, (0, self.out.len()));
+        let sep = if extra.is_empty() { "" } else { " " };
+        self.out.extend_from_slice(
+            format!(
+                "{num} 0 obj\n<< {extra}{sep}/Filter /FlateDecode /Length {} >>\nstream\n",
+                packed.len()
+            )
+            .as_bytes()
+        );
+        self.out.extend_from_slice(&packed);
+        self.out.extend_from_slice(b"\nendstream\nendobj\n");
+    }
+
+    /// Trailer entries carried over from the previous revision.
+    fn carried(&self) -> String {
+        let mut s = String::new();
+        for key in [&b"Root"[..], b"Info", b"ID"] {
+            if let Some(v) = self.file.trailer.get(key) {
+                let _ = write!(s, " {}{}", Obj::Name(key.to_vec()).to_pdf(), {
+                    let mut out = String::from(" ");
+                    v.write(&mut out);
+                    out
+                });
+            }
+        }
+        s
+    }
+
+    /// Append the xref section (same kind as the previous revision) and
+    /// trailer.
+    fn finish(mut self) -> Vec<u8> {
+        let prev = self.file.startxref;
+        if self.file.xref_is_stream {
+            let id = self.alloc();
+            let offset = self.out.len();
+            self.written.insert(id, (0, offset));
+            let mut rows = Vec::new();
+            for (generation, off) in self.written.values() {
+                rows.push(1u8);
+                rows.extend_from_slice(&(*off as u32).to_be_bytes());
+                rows.extend_from_slice(&generation.to_be_bytes());
+            }
+            let packed = compress_to_vec_zlib(&rows, COMPRESSION_LEVEL);
+            let dict = format!(
+                "<< /Type /XRef /Size {} /Prev {prev}{} /W [1 4 2] /Index [{}] \
+                 /Filter /FlateDecode /Length {} >>",
+                self.next,
+                self.carried(),
+                self.subsections()
+                    .iter()
+                    .map(|(start, count)| format!("{start} {count}"))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                packed.len()
+            );
+            self.out
+                .extend_from_slice(format!("{id} 0 obj\n{dict}\nstream\n").as_bytes());
+            self.out.extend_from_slice(&packed);
+            self.out.extend_from_slice(
+                format!("\nendstream\nendobj\nstartxref\n{offset}\n%%EOF\n").as_bytes()
+            );
+        } else {
+            let offset = self.out.len();
+            let mut table = String::from("xref\n");
+            for (start, count) in self.subsections() {
+                let _ = writeln!(table, "{start} {count}");
+                for num in start..start + count {
+                    let (generation, off) = self.written[&num];
+                    let _ = writeln!(table, "{off:010} {generation:05} n ");
+                }
+            }
+            let _ = write!(
+                table,
+                "trailer\n<< /Size {} /Prev {prev}{} >>\nstartxref\n{offset}\n%%EOF\n",
+                self.next,
+                self.carried()
+            );
+            self.out.extend_from_slice(table.as_bytes());
+        }
+        self.out
+    }
+
+    /// Runs of consecutive written object numbers.
+    fn subsections(&self) -> Vec<(u32, u32)> {
+        let mut runs: Vec<(u32, u32)> = Vec::new();
+        for &num in self.written.keys() {
+            match runs.last_mut() {
+                Some((start, count)) if *start + *count == num => *count += 1,
+                _ => runs.push((num, 1))
+            }
+        }
+        runs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        placement::PlacementGeometry,
+        signature_core::{Stroke, StrokePoint}
+    };
+
+    const CONTENT: &[u8] = b"BT /F1 12 Tf 72 720 Td (Hi) Tj ET";
+
+    /// Objects 1..=5 of a one-page form: catalog, pages, page, content,
+    /// signature widget.
+    fn objects(page_extra: &str) -> Vec<Vec<u8>> {
+        let mut content = format!("<< /Length {} >>\nstream\n", CONTENT.len()).into_bytes();
+        content.extend_from_slice(CONTENT);
+        content.extend_from_slice(b"\nendstream");
+        vec![
+            b"<< /Type /Catalog /Pages 2 0 R /AcroForm << /Fields [5 0 R] >> >>".to_vec(),
+            b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_vec(),
+            format!(
+                "<< /Type /Page /Parent 2 0 R /Contents 4 0 R /Annots [5 0 R] {page_extra} >>"
+            )
+            .into_bytes(),
+            content,
+            b"<< /Type /Annot /Subtype /Widget /FT /Sig /T (sig) /Rect [400 100 560 160] >>"
+                .to_vec(),
+        ]
+    }
+
+    fn header() -> Vec<u8> {
+        b"%PDF-1.5\n%\xe2\xe3\xcf\xd3\n".to_vec()
+    }
+
+    fn push_object(out: &mut Vec<u8>, num: usize, body: &[u8]) -> usize {
+        let offset = out.len();
+        out.extend_from_slice(format!("{num} 0 obj\n").as_bytes());
+        out.extend_from_slice(body);
+        out.extend_from_slice(b"\nendobj\n");
+        offset
+    }
+
+    /// Classic xref table.
+    fn with_table(objects: &[Vec<u8>]) -> Vec<u8> {
+        let mut out = header();
+        let offsets: Vec<usize> = objects
+            .iter()
+            .enumerate()
+            .map(|(i, body)| push_object(&mut out, i + 1, body))
+            .collect();
+        let xref = out.len();
+        let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for off in offsets {
+            let _ = writeln!(table, "{off:010} 00000 n ");
+        }
+        let _ = write!(
+            table,
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            objects.len() + 1
+        );
+        out.extend_from_slice(table.as_bytes());
+        out
+    }
+
+    /// Xref stream; objects listed in `packed` go into an object stream.
+    fn with_xref_stream(objects: &[Vec<u8>], packed: &[usize]) -> Vec<u8> {
+        let mut out = header();
+        let mut rows = vec![(0u8, 0u32, 0u16); objects.len() + 3];
+        let stm = objects.len() + 1;
+        let (mut index, mut body) = (String::new(), Vec::new());
+        for (i, obj) in objects.iter().enumerate() {
+            let num = i + 1;
+            if let Some(pos) = packed.iter().position(|&n| n == num) {
+                let _ = write!(index, "{num} {} ", body.len());
+                body.extend_from_slice(obj);
+                body.push(b'\n');
+                rows[num] = (2, stm as u32, pos as u16);
+            } else {
+                rows[num] = (1, push_object(&mut out, num, obj) as u32, 0);
+            }
+        }
+        let mut data = index.clone().into_bytes();
+        data.extend_from_slice(&body);
+        let data = compress_to_vec_zlib(&data, COMPRESSION_LEVEL);
+        let mut stream = format!(
+            "<< /Type /ObjStm /N {} /First {} /Filter /FlateDecode /Length {} >>\nstream\n",
+            packed.len(),
+            index.len(),
+            data.len()
+        )
+        .into_bytes();
+        stream.extend_from_slice(&data);
+        stream.extend_from_slice(b"\nendstream");
+        rows[stm] = (1, push_object(&mut out, stm, &stream) as u32, 0);
+
+        let xref = out.len();
+        rows[stm + 1] = (1, xref as u32, 0);
+        let mut table = Vec::new();
+        for (kind, a, b) in rows {
+            table.push(kind);
+            table.extend_from_slice(&a.to_be_bytes());
+            table.extend_from_slice(&b.to_be_bytes());
+        }
+        let table = compress_to_vec_zlib(&table, COMPRESSION_LEVEL);
+        let mut body = format!(
+            "<< /Type /XRef /Size {} /Root 1 0 R /W [1 4 2] /Filter /FlateDecode /Length {} \
+             >>\nstream\n",
+            stm + 2,
+            table.len()
+        )
+        .into_bytes();
+        body.extend_from_slice(&table);
+        body.extend_from_slice(b"\nendstream");
+        push_object(&mut out, stm + 1, &body);
+        out.extend_from_slice(format!("startxref\n{xref}\n%%EOF\n").as_bytes());
+        out
+    }
+
+    fn signature() -> PdfSignature {
+        let point = |x, y| StrokePoint {
+            x,
+            y,
+            t: 0.0,
+            pressure: 0.5
+        };
+        PdfSignature {
+            geometry:      PlacementGeometry {
+                x:      0.0,
+                y:      0.0,
+                width:  100.0,
+                height: 40.0
+            },
+            strokes:       vec![Stroke {
+                points: vec![point(10.0, 10.0), point(90.0, 30.0)],
+                pen:    None
+            }],
+            canvas_width:  100.0,
+            canvas_height: 40.0,
+            line_width:    2.0,
+            image:         None
+        }
+    }
+
+    /// Re-parse a stamped file: page count and the decoded last content
+    /// stream of the first page.
+    fn reparse(pdf: &[u8]) -> (usize, String) {
+        let file = PdfFile::parse(pdf).unwrap();
+        let pages = file.pages().unwrap();
+        let contents = file.contents(&pages[0].dict).unwrap();
+        let Obj::Stream(dict, raw) = file.resolve(contents.last().unwrap()).unwrap() else {
+            panic!("content is not a stream");
+        };
+        let text = String::from_utf8(file.decode(&dict, &raw).unwrap()).unwrap();
+
+        let res = file.resolve_dict(&pages[0].resources).unwrap();
+        let xobjects = file.resolve_dict(res.get(b"XObject").unwrap()).unwrap();
+        let form = file.resolve(xobjects.get(b"CSig1").unwrap()).unwrap();
+        assert!(matches!(form, Obj::Stream(..)));
+        (pages.len(), text)
+    }
+
+    fn stamp(pdf: &[u8], target: &StampTarget) -> String {
+        let out = stamp_signature(pdf, target, &signature()).unwrap();
+        assert!(out.starts_with(pdf));
+        let (pages, text) = reparse(&out);
+        assert_eq!(pages, 1);
+        text
+    }
+
+    #[test]
+    fn stamps_file_with_xref_table() {
+        let pdf = with_table(&objects("/MediaBox [0 0 612 792]"));
+        let target = StampTarget::Rect {
+            page: 0,
+            rect: PdfRect {
+                x:      100.0,
+                y:      200.0,
+                width:  100.0,
+                height: 40.0
+            }
+        };
+        assert_eq!(
+            stamp(&pdf, &target),
+            "Q\nq 1 0 0 1 100 200 cm /CSig1 Do Q\n"
+        );
+        assert_eq!(
+            stamp(&pdf, &StampTarget::Field("sig".into())),
+            "Q\nq 1 0 0 1 400 100 cm /CSig1 Do Q\n"
+        );
+    }
+
+    #[test]
+    fn stamps_file_with_xref_and_object_streams() {
+        let pdf = with_xref_stream(&objects("/MediaBox [0 0 612 792]"), &[2, 3, 5]);
+        let text = stamp(&pdf, &StampTarget::Field("sig".into()));
+        assert_eq!(text, "Q\nq 1 0 0 1 400 100 cm /CSig1 Do Q\n");
+
+        // Stamping the result again goes through its xref stream
+        let once = stamp_signature(&pdf, &StampTarget::Field("sig".into()), &signature()).unwrap();
+        let twice =
+            stamp_signature(&once, &StampTarget::Field("sig".into()), &signature()).unwrap();
+        let file = PdfFile::parse(&twice).unwrap();
+        assert!(file.xref_is_stream);
+        assert_eq!(
+            file.contents(&file.pages().unwrap()[0].dict).unwrap().len(),
+            5
+        );
+    }
+
+    #[test]
+    fn honours_rotate_and_box_origin() {
+        let pdf = with_table(&objects("/MediaBox [50 50 662 842] /Rotate 90"));
+        let target = StampTarget::Rect {
+            page: 0,
+            rect: PdfRect {
+                x:      10.0,
+                y:      20.0,
+                width:  100.0,
+                height: 40.0
+            }
+        };
+        assert_eq!(
+            stamp(&pdf, &target),
+            "Q\nq 0 1 -1 0 642 60 cm /CSig1 Do Q\n"
+        );
+
+        // Inherited crop box wins over the media box
+        let mut objs = objects("/MediaBox [0 0 612 792]");
+        objs[1] = b"<< /Type /Pages /Kids [3 0 R] /Count 1 /CropBox [20 30 500 700] >>".to_vec();
+        let target = StampTarget::Rect {
+            page: 0,
+            rect: PdfRect {
+                x:      0.0,
+                y:      0.0,
+                width:  10.0,
+                height: 10.0
+            }
+        };
+        assert_eq!(
+            stamp(&with_table(&objs), &target),
+            "Q\nq 1 0 0 1 20 30 cm /CSig1 Do Q\n"
+        );
+    }
+
+    #[test]
+    fn widget_rect_maps_back_onto_itself() {
+        let widget = PdfRect {
+            x:      400.0,
+            y:      100.0,
+            width:  160.0,
+            height: 60.0
+        };
+        for rotate in [0, 90, 180, 270] {
+            let view = PageView {
+                area: PdfRect {
+                    x:      50.0,
+                    y:      -20.0,
+                    width:  612.0,
+                    height: 792.0
+                },
+                rotate
+            };
+            let shown = view.displayed(widget);
+            let [a, b, c, d, e, f] = view.matrix(shown);
+            let map = |u: f64, v: f64| (a * u + c * v + e, b * u + d * v + f);
+            let (x0, y0) = map(0.0, 0.0);
+            let (x1, y1) = map(shown.width, shown.height);
+            assert_eq!(
+                (x0.min(x1), y0.min(y1), (x1 - x0).abs(), (y1 - y0).abs()),
+                (widget.x, widget.y, widget.width, widget.height),
+                "rotate {rotate}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_hostile_stream_length() {
+        for length in ["-1", "9223372036854775807", "99999"] {
+            let mut objs = objects("");
+            objs[3] = format!("<< /Length {length} >>\nstream\nq Q\nendstream").into_bytes();
+            let pdf = with_table(&objs);
+            let file = PdfFile::parse(&pdf).unwrap();
+            assert!(file.object(4, 0).is_err(), "/Length {length}");
+        }
+    }
+}
@@ -0,0 +1,696 @@
+// src/placement.rs
+
+use std::{cell::RefCell, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{Element, Event, EventTarget, HtmlElement, PointerEvent};
+
+use crate::{
+    error::SigError,
+    ui_common::{anchor_offsets, set_inline_style}
+};
+
+/// Class of the resize grip shown next to an interactive signature image.
+const RESIZE_HANDLE_CLASS: &str = "cs-resize-handle";
+/// Side of the resize grip, px.
+const RESIZE_HANDLE_SIZE: f64 = 14.0;
+
+/// How the placed signature image behaves after confirmation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PlacementMode {
+    /// Positioned once, click-through (`pointer-events:none`).
+    #[default]
+    Fixed,
+    /// Signer can drag and resize the image inside the contract container
+    /// before it is locked.
+    Interactive
+}
+
+/// Final box of a placed signature, in px relative to the contract
+/// container's content origin (scroll offsets included).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PlacementGeometry {
+    pub x:      f64,
+    pub y:      f64,
+    pub width:  f64,
+    pub height: f64
+}
+
+impl PlacementGeometry {
+    /// Keep the box inside `bounds_w` x `bounds_h`, shrinking it if it is
+    /// larger than the bounds (aspect ratio preserved).
+    pub fn clamp_to(self, bounds_w: f64, bounds_h: f64) -> Self {
+        let mut g = self;
+        if g.width > bounds_w && g.width > 0.0 {
+            g.height *= bounds_w / g.width;
+            g.width = bounds_w;
+        }
+        if g.height > bounds_h && g.height > 0.0 {
+            g.width *= bounds_h / g.height;
+            g.height = bounds_h;
+        }
+        g.x = g.x.clamp(0.0, (bounds_w - g.width).max(0.0));
+        g.y = g.y.clamp(0.0, (bounds_h - g.height).max(0.0));
+        g
+    }
+
+    /// Move the top-left corner onto the nearest anchor within `radius` px.
+    pub fn snap_to(self, anchors: &[(f64, f64)], radius: f64) -> Self {
+        let nearest = anchors
+            .iter()
+            .map(|&(ax, ay)| ((ax - self.x).hypot(ay - self.y), ax, ay))
+            .filter(|(d, ..)| *d <= radius)
+            .min_by(|a, b| a.0.total_cmp(&b.0));
+        match nearest {
+            Some((_, x, y)) => Self {
+                x,
+                y,
+                ..self
+            },
+            None => self
+        }
+    }
+}
+
+/// Horizontal alignment of the image inside the anchor box.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Align {
+    #[default]
+    Start,
+    Center,
+    End
+}
+
+/// Vertical alignment of the image inside the anchor box.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VerticalAlign {
+    #[default]
+    Start,
+    Center,
+    End,
+    /// Stand on the anchor's bottom edge (the signature line), letting the
+    /// lower part of the image hang below it like handwriting descenders.
+    Baseline
+}
+
+/// Share of the image height that hangs below the line with
+/// `VerticalAlign::Baseline`.
+const BASELINE_DESCENT: f64 = 0.2;
+
+/// How the image is sized relative to the anchor box.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FitMode {
+    /// Use the requested width; the anchor only positions the image.
+    #[default]
+    None,
+    /// Largest size that fits entirely inside the anchor box.
+    Contain,
+    /// Fill the anchor box, cropping the overflow (`object-fit: cover`).
+    Cover
+}
+
+impl FitMode {
+    /// Value for the image's `object-fit` style.
+    pub(crate) fn object_fit(self) -> &'static str {
+        match self {
+            Self::Cover => "cover",
+            Self::None | Self::Contain => "contain"
+        }
+    }
+}
+
+/// Which element inside the contract container the signature is placed at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AnchorTarget {
+    /// Element with this id.
+    Id(String),
+    /// Element with `data-signature-field="<name>"`.
+    Field(String)
+}
+
+impl AnchorTarget {
+    /// Human-readable reference used in audit events.
+    pub fn label(&self) -> String {
+        match self {
+            Self::Id(id) => id.clone(),
+            Self::Field(name) => format!("[data-signature-field=\"{name}\"]")
+        }
+    }
+}
+
+/// How a signature is laid out relative to its anchor.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AnchorLayout {
+    pub align_x:    Align,
+    pub align_y:    VerticalAlign,
+    /// Extra shift after alignment, px.
+    pub offset_x:   f64,
+    pub offset_y:   f64,
+    /// Upper bound for the image height, px (width shrinks with it).
+    pub max_height: Option<f64>,
+    pub fit:        FitMode
+}
+
+impl AnchorLayout {
+    /// Geometry of an image with aspect `ratio` (height / width) and requested
+    /// `width`, laid out against the `anchor` box.
+    pub fn resolve(&self, anchor: PlacementGeometry, width: f64, ratio: f64) -> PlacementGeometry {
+        let (mut w, mut h) = match self.fit {
+            FitMode::None => (width, width * ratio),
+            FitMode::Contain => {
+                let w = anchor.width.min(if ratio > 0.0 {
+                    anchor.height / ratio
+                } else {
+                    anchor.width
+                });
+                (w, w * ratio)
+            }
+            FitMode::Cover => (anchor.width, anchor.height)
+        };
+        if let Some(max_h) = self.max_height
+            && h > max_h
+        {
+            if self.fit != FitMode::Cover && h > 0.0 {
+                w *= max_h / h;
+            }
+            h = max_h;
+        }
+
+        let x = match self.align_x {
+            Align::Start => anchor.x,
+            Align::Center => anchor.x + (anchor.width - w) / 2.0,
+            Align::End => anchor.x + anchor.width - w
+        };
+        let y = match self.align_y {
+            VerticalAlign::Start => anchor.y,
+            VerticalAlign::Center => anchor.y + (anchor.height - h) / 2.0,
+            VerticalAlign::End => anchor.y + anchor.height - h,
+            VerticalAlign::Baseline => anchor.y + anchor.height - h * (1.0 - BASELINE_DESCENT)
+        };
+        PlacementGeometry {
+            x:      x + self.offset_x,
+            y:      y + self.offset_y,
+            width:  w,
+            height: h
+        }
+    }
+}
+
+/// Tuning of interactive placement.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InteractiveOptions {
+    /// Elements inside the container the image snaps to.
+    pub snap_selector: Option<String>,
+    /// Snap distance, px.
+    pub snap_radius:   f64,
+    /// Smallest width the image can be resized to, px.
+    pub min_width:     f64
+}
+
+impl Default for InteractiveOptions {
+    fn default() -> Self {
+        Self {
+            snap_selector: Some("[data-signature-anchor]".into()),
+            snap_radius:   12.0,
+            min_width:     40.0
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum DragMode {
+    Move,
+    Resize
+}
+
+struct DragState {
+    mode:     Option<(DragMode, i32)>,
+    start:    (f64, f64),
+    origin:   PlacementGeometry,
+    geometry: PlacementGeometry,
+    anchors:  Vec<(f64, f64)>
+}
+
+type Listener = (EventTarget, &'static str, Closure<dyn FnMut(Event)>);
+
+/// RAII handle of an interactive (draggable, resizable) signature image.
+/// Dropping it removes the grip and listeners and makes the image
+/// click-through again at its last geometry.
+pub struct PlacementHandle {
+    img:       HtmlElement,
+    grip:      HtmlElement,
+    state:     Rc<RefCell<DragState>>,
+    listeners: Vec<Listener>
+}
+
+impl PlacementHandle {
+    /// Make `img` (already absolutely positioned inside `container`)
+    /// draggable and resizable. `on_change` gets the geometry after every
+    /// drag or resize.
+    pub fn new(
+        container: HtmlElement,
+        img: HtmlElement,
+        options: InteractiveOptions,
+        on_change: impl FnMut(PlacementGeometry) + 'static
+    ) -> Result<Self, SigError> {
+        let document = img.owner_document().ok_or(SigError::DomUnavailable)?;
+        let grip = document
+            .create_element("div")
+            .map_err(|_| SigError::OpFailed("createElement(div)".into()))?
+            .dyn_into::<HtmlElement>()
+            .map_err(|_| SigError::OpFailed("created element is not HtmlElement".into()))?;
+        grip.set_class_name(RESIZE_HANDLE_CLASS);
+        container
+            .append_child(&grip)
+            .map_err(|_| SigError::OpFailed("appendChild(grip)".into()))?;
+
+        set_inline_style(
+            &img,
+            &[
+                ("pointer-events", "auto"),
+                ("cursor", "move"),
+                ("touch-action", "none"),
+                ("outline", "1px dashed #888")
+            ]
+        )?;
+        set_inline_style(
+            &grip,
+            &[
+                ("position", "absolute"),
+                ("width", &format!("{RESIZE_HANDLE_SIZE}px")),
+                ("height", &format!("{RESIZE_HANDLE_SIZE}px")),
+                ("background", "#888"),
+                ("cursor", "nwse-resize"),
+                ("touch-action", "none"),
+                ("z-index", "2")
+            ]
+        )?;
+
+        let geometry = current_geometry(&img);
+        let state = Rc::new(RefCell::new(DragState {
+            mode: None,
+            start: (0.0, 0.0),
+            origin: geometry,
+            geometry,
+            anchors: Vec::new()
+        }));
+        apply_geometry(&img, &grip, geometry)?;
+
+        let on_change = Rc::new(RefCell::new(on_change));
+        let mut listeners = Vec::new();
+
+        // Data URL images decode asynchronously: re-measure once loaded
+        let state_cb = Rc::clone(&state);
+        let img_cb = img.clone();
+        let grip_cb = grip.clone();
+        listeners.push(listen(&img, "load", move |_e: Event| {
+            let geometry = current_geometry(&img_cb);
+            state_cb.borrow_mut().geometry = geometry;
+            let _ = apply_geometry(&img_cb, &grip_cb, geometry);
+        })?);
+
+        for (target, mode) in [(&img, DragMode::Move), (&grip, DragMode::Resize)] {
+            // pointerdown: start drag/resize, capture the pointer
+            let state_cb = Rc::clone(&state);
+            let container_cb = container.clone();
+            let img_cb = img.clone();
+            let target_cb: Element = target.clone().into();
+            let snap_selector = options.snap_selector.clone();
+            listeners.push(listen_pointer(
+                target,
+                "pointerdown",
+                move |e: PointerEvent| {
+                    if !e.is_trusted() {
+                        return;
+                    }
+                    e.prevent_default();
+                    e.stop_propagation();
+                    let _ = target_cb.set_pointer_capture(e.pointer_id());
+                    let mut st = state_cb.borrow_mut();
+                    st.mode = Some((mode, e.pointer_id()));
+                    st.start = (e.client_x() as f64, e.client_y() as f64);
+                    st.origin = current_geometry(&img_cb);
+                    st.anchors = snap_selector
+                        .as_deref()
+                        .map(|sel| anchor_offsets(&container_cb, sel))
+                        .unwrap_or_default();
+                }
+            )?);
+
+            // pointermove: update geometry while captured
+            let state_cb = Rc::clone(&state);
+            let container_cb = container.clone();
+            let img_cb = img.clone();
+            let grip_cb = grip.clone();
+            let snap_radius = options.snap_radius;
+            let min_width = options.min_width;
+            listeners.push(listen_pointer(
+                target,
+                "pointermove",
+                move |e: PointerEvent| {
+                    let mut st = state_cb.borrow_mut();
+                    let Some((mode, id)) = st.mode else {
+                        return;
+                    };
+                    if id != e.pointer_id() {
+                        return;
+                    }
+                    let dx = e.client_x() as f64 - st.start.0;
+                    let dy = e.client_y() as f64 - st.start.1;
+                    let origin = st.origin;
+                    let bounds_w = container_cb.scroll_width() as f64;
+                    let bounds_h = container_cb.scroll_height() as f64;
+                    let next = match mode {
+                        DragMode::Move => PlacementGeometry {
+                            x: origin.x + dx,
+                            y: origin.y + dy,
+                            ..origin
+                        }
+                        .snap_to(&st.anchors, snap_radius),
+                        DragMode::Resize => {
+                            let width = (origin.width + dx).max(min_width);
+                            let ratio = if origin.width > 0.0 {
+                                origin.height / origin.width
+                            } else {
+                                0.0
+                            };
+                            PlacementGeometry {
+                                width,
+                                height: width * ratio,
+                                ..origin
+                            }
+                        }
+                    }
+                    .clamp_to(bounds_w, bounds_h);
+                    st.geometry = next;
+                    let _ = apply_geometry(&img_cb, &grip_cb, next);
+                }
+            )?);
+
+            // pointerup / pointercancel: finish and report
+            for event in ["pointerup", "pointercancel"] {
+                let state_cb = Rc::clone(&state);
+                let on_change_cb = Rc::clone(&on_change);
+                listeners.push(listen_pointer(target, event, move |e: PointerEvent| {
+                    let finished = {
+                        let mut st = state_cb.borrow_mut();
+                        match st.mode {
+                            Some((_, id)) if id == e.pointer_id() => {
+                                st.mode = None;
+                                Some(st.geometry)
+                            }
+                            _ => None
+                        }
+                    };
+                    if let Some(geometry) = finished {
+                        (on_change_cb.borrow_mut())(geometry);
+                    }
+                })?);
+            }
+        }
+
+        Ok(Self {
+            img,
+            grip,
+            state,
+            listeners
+        })
+    }
+
+    /// Geometry after the last drag/resize.
+    pub fn geometry(&self) -> PlacementGeometry {
+        self.state.borrow().geometry
+    }
+}
+
+impl Drop for PlacementHandle {
+    fn drop(&mut self) {
+        for (target, event, cb) in self.listeners.drain(..) {
+            let _ = target.remove_event_listener_with_callback(event, cb.as_ref().unchecked_ref());
+        }
+        self.grip.remove();
+        let _ = set_inline_style(
+            &self.img,
+            &[
+                ("pointer-events", "none"),
+                ("cursor", "auto"),
+                ("outline", "none")
+            ]
+        );
+    }
+}
+
+fn listen_pointer(
+    target: &HtmlElement,
+    event: &'static str,
+    mut f: impl FnMut(PointerEvent) + 'static
+) -> Result<Listener, SigError> {
+    listen(target, event, move |e: Event| {
+        if let Ok(e) = e.dyn_into::<PointerEvent>() {
+            f(e);
+        }
+    })
+}
+
+fn listen(
+    target: &HtmlElement,
+    event: &'static str,
+    f: impl FnMut(Event) + 'static
+) -> Result<Listener, SigError> {
+    let cb = Closure::wrap(Box::new(f) as Box<dyn FnMut(Event)>);
+    target
+        .add_event_listener_with_callback(event, cb.as_ref().unchecked_ref())
+        .map_err(|_| SigError::OpFailed(format!("addEventListener({event})")))?;
+    Ok((target.clone().into(), event, cb))
+}
+
+fn current_geometry(img: &HtmlElement) -> PlacementGeometry {
+    PlacementGeometry {
+        x:      img.offset_left() as f64,
+        y:      img.offset_top() as f64,
+        width:  img.offset_width() as f64,
+        height: img.offset_height() as f64
+    }
+}
+
+fn apply_geometry(
+    img: &HtmlElement,
+    grip: &HtmlElement,
+    g: PlacementGeometry
+) -> Result<(), SigError> {
+    set_inline_style(
+        img,
+        &[
+            ("left", &format!("{}px", g.x)),
+            ("top", &format!("{}px", g.y)),
+            ("width", &format!("{}px", g.width))
+        ]
+    )?;
+    let half = RESIZE_HANDLE_SIZE / 2.0;
+    set_inline_style(
+        grip,
+        &[
+            ("left", &format!("{}px", g.x + g.width - half)),
+            ("top", &format!("{}px", g.y + g.height - half))
+        ]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f64, y: f64, width: f64, height: f64) -> PlacementGeometry {
+        PlacementGeometry {
+            x,
+            y,
+            width,
+            height
+        }
+    }
+
+    /// 200 x 40 signature line at (100, 50).
+    const ANCHOR: PlacementGeometry = PlacementGeometry {
+        x:      100.0,
+        y:      50.0,
+        width:  200.0,
+        height: 40.0
+    };
+
+    fn layout(fit: FitMode) -> AnchorLayout {
+        AnchorLayout {
+            fit,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn resolve_without_fit_keeps_the_requested_width() {
+        let none = layout(FitMode::None);
+        assert_eq!(
+            none.resolve(ANCHOR, 120.0, 0.5),
+            rect(100.0, 50.0, 120.0, 60.0)
+        );
+
+        let centered = AnchorLayout {
+            align_x: Align::Center,
+            align_y: VerticalAlign::Center,
+            ..none
+        };
+        assert_eq!(
+            centered.resolve(ANCHOR, 120.0, 0.5),
+            rect(140.0, 40.0, 120.0, 60.0)
+        );
+
+        let end = AnchorLayout {
+            align_x: Align::End,
+            align_y: VerticalAlign::End,
+            ..none
+        };
+        assert_eq!(
+            end.resolve(ANCHOR, 120.0, 0.5),
+            rect(180.0, 30.0, 120.0, 60.0)
+        );
+
+        // A fifth of the image hangs below the line
+        let baseline = AnchorLayout {
+            align_y: VerticalAlign::Baseline,
+            ..none
+        };
+        assert_eq!(
+            baseline.resolve(ANCHOR, 120.0, 0.5),
+            rect(100.0, 42.0, 120.0, 60.0)
+        );
+    }
+
+    #[test]
+    fn resolve_contain_fits_inside_the_anchor() {
+        let contain = layout(FitMode::Contain);
+        // Tall image: height-bound
+        assert_eq!(
+            contain.resolve(ANCHOR, 999.0, 0.5),
+            rect(100.0, 50.0, 80.0, 40.0)
+        );
+        // Wide image: width-bound
+        assert_eq!(
+            contain.resolve(ANCHOR, 999.0, 0.1),
+            rect(100.0, 50.0, 200.0, 20.0)
+        );
+        // Degenerate ratio doesn't divide by zero
+        assert_eq!(
+            contain.resolve(ANCHOR, 999.0, 0.0),
+            rect(100.0, 50.0, 200.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn resolve_cover_fills_the_anchor() {
+        let cover = layout(FitMode::Cover);
+        assert_eq!(cover.resolve(ANCHOR, 120.0, 0.5), ANCHOR);
+        assert_eq!(cover.resolve(ANCHOR, 120.0, 3.0), ANCHOR);
+    }
+
+    #[test]
+    fn resolve_caps_the_height() {
+        let capped = |fit| AnchorLayout {
+            max_height: Some(30.0),
+            ..layout(fit)
+        };
+        // Aspect ratio kept
+        assert_eq!(
+            capped(FitMode::None).resolve(ANCHOR, 120.0, 0.5),
+            rect(100.0, 50.0, 60.0, 30.0)
+        );
+        assert_eq!(
+            capped(FitMode::Contain).resolve(ANCHOR, 120.0, 0.5),
+            rect(100.0, 50.0, 60.0, 30.0)
+        );
+        // Cover crops instead
+        assert_eq!(
+            capped(FitMode::Cover).resolve(ANCHOR, 120.0, 0.5),
+            rect(100.0, 50.0, 200.0, 30.0)
+        );
+    }
+
+    #[test]
+    fn resolve_applies_negative_offsets() {
+        let shifted = AnchorLayout {
+            offset_x: -10.0,
+            offset_y: -5.0,
+            ..layout(FitMode::None)
+        };
+        assert_eq!(
+            shifted.resolve(ANCHOR, 120.0, 0.5),
+            rect(90.0, 45.0, 120.0, 60.0)
+        );
+
+        // Past the container's origin; `clamp_to` pulls it back in
+        let corner = rect(4.0, 2.0, 200.0, 40.0);
+        let g = shifted.resolve(corner, 120.0, 0.5);
+        assert_eq!((g.x, g.y), (-6.0, -3.0));
+        assert_eq!(g.clamp_to(500.0, 500.0), rect(0.0, 0.0, 120.0, 60.0));
+    }
+
+    #[test]
+    fn clamp_keeps_the_box_inside_each_edge() {
+        let (w, h) = (300.0, 200.0);
+        assert_eq!(
+            rect(-20.0, 10.0, 100.0, 50.0).clamp_to(w, h),
+            rect(0.0, 10.0, 100.0, 50.0)
+        );
+        assert_eq!(
+            rect(10.0, -20.0, 100.0, 50.0).clamp_to(w, h),
+            rect(10.0, 0.0, 100.0, 50.0)
+        );
+        assert_eq!(
+            rect(250.0, 10.0, 100.0, 50.0).clamp_to(w, h),
+            rect(200.0, 10.0, 100.0, 50.0)
+        );
+        assert_eq!(
+            rect(10.0, 180.0, 100.0, 50.0).clamp_to(w, h),
+            rect(10.0, 150.0, 100.0, 50.0)
+        );
+        let inside = rect(10.0, 20.0, 100.0, 50.0);
+        assert_eq!(inside.clamp_to(w, h), inside);
+    }
+
+    #[test]
+    fn clamp_shrinks_an_oversized_box() {
+        // Too wide, then too tall; aspect ratio kept
+        assert_eq!(
+            rect(50.0, 0.0, 600.0, 100.0).clamp_to(300.0, 200.0),
+            rect(0.0, 0.0, 300.0, 50.0)
+        );
+        assert_eq!(
+            rect(0.0, 50.0, 100.0, 400.0).clamp_to(300.0, 200.0),
+            rect(0.0, 0.0, 50.0, 200.0)
+        );
+    }
+
+    #[test]
+    fn snap_moves_only_within_the_radius() {
+        let anchors = [(100.0, 100.0), (200.0, 100.0)];
+        assert_eq!(
+            rect(105.0, 103.0, 80.0, 20.0).snap_to(&anchors, 10.0),
+            rect(100.0, 100.0, 80.0, 20.0)
+        );
+        // Exactly on the radius still snaps
+        assert_eq!(
+            rect(190.0, 100.0, 80.0, 20.0).snap_to(&anchors, 10.0),
+            rect(200.0, 100.0, 80.0, 20.0)
+        );
+        let outside = rect(115.0, 100.0, 80.0, 20.0);
+        assert_eq!(outside.snap_to(&anchors, 10.0), outside);
+        assert_eq!(outside.snap_to(&[], 10.0), outside);
+    }
+
+    #[test]
+    fn snap_picks_the_nearest_anchor() {
+        let anchors = [(100.0, 100.0), (108.0, 100.0)];
+        assert_eq!(
+            rect(105.0, 100.0, 80.0, 20.0).snap_to(&anchors, 10.0),
+            rect(108.0, 100.0, 80.0, 20.0)
+        );
+    }
+}
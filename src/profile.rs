@@ -0,0 +1,226 @@
+// src/profile.rs
+
+//! Saved signature profiles: a signer draws once, consents to storage, and
+//! reuses the signature on later contracts. Stored through any
+//! [`DraftStorage`] backend.
+
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    draft::{BrowserStorage, DraftStorage},
+    error::SigError,
+    signature_core::{Stroke, StrokePoint}
+};
+
+/// Prefix of every profile key.
+const KEY_PREFIX: &str = "contract-signature:profile:";
+
+/// Consent wording shown next to "remember this signature" unless the store
+/// sets its own (see [`ProfileStore::with_consent_text`]).
+pub const DEFAULT_CONSENT_TEXT: &str =
+    "Сохранить подпись на этом устройстве и использовать её для следующих договоров";
+
+/// How the signature in an envelope was produced.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SignatureOrigin {
+    /// Drawn on the pad for this contract.
+    #[default]
+    Drawn,
+    /// Taken unchanged from a saved profile.
+    Reused {
+        profile_id:   String,
+        /// ISO 8601 UTC time the signer consented to storing it.
+        consented_at: String
+    }
+}
+
+/// Signature stored for reuse, with the consent it was stored under.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedSignature {
+    pub strokes:       Vec<Stroke>,
+    /// Canvas size the strokes were drawn on, px.
+    pub canvas_width:  u32,
+    pub canvas_height: u32,
+    /// Consent wording the signer agreed to.
+    pub consent_text:  String,
+    /// ISO 8601 UTC.
+    pub consented_at:  String
+}
+
+impl SavedSignature {
+    /// Strokes scaled (uniformly, centered) onto a canvas of `size`.
+    pub fn fit(&self, size: (u32, u32)) -> Vec<Stroke> {
+        let (sw, sh) = (
+            self.canvas_width.max(1) as f64,
+            self.canvas_height.max(1) as f64
+        );
+        let (w, h) = (size.0 as f64, size.1 as f64);
+        if (sw, sh) == (w, h) {
+            return self.strokes.clone();
+        }
+        let s = (w / sw).min(h / sh);
+        let (dx, dy) = ((w - sw * s) / 2.0, (h - sh * s) / 2.0);
+        self.strokes
+            .iter()
+            .map(|stroke| Stroke {
+                points: stroke
+                    .points
+                    .iter()
+                    .map(|p| StrokePoint {
+                        x: dx + p.x * s,
+                        y: dy + p.y * s,
+                        ..p.clone()
+                    })
+                    .collect(),
+                pen:    stroke.pen.clone()
+            })
+            .collect()
+    }
+}
+
+/// Saved signature of one profile (e.g. an account manager).
+#[derive(Clone)]
+pub struct ProfileStore {
+    backend:          Rc<dyn DraftStorage>,
+    pub profile_id:   String,
+    /// Wording of the "remember this signature" consent; saved with the
+    /// signature. Defaults to [`DEFAULT_CONSENT_TEXT`].
+    pub consent_text: String
+}
+
+impl PartialEq for ProfileStore {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.backend, &other.backend)
+            && self.profile_id == other.profile_id
+            && self.consent_text == other.consent_text
+    }
+}
+
+impl ProfileStore {
+    pub fn new(backend: Rc<dyn DraftStorage>, profile_id: impl Into<String>) -> Self {
+        Self {
+            backend,
+            profile_id: profile_id.into(),
+            consent_text: DEFAULT_CONSENT_TEXT.into()
+        }
+    }
+
+    /// Show (and save) `text` as the storage consent instead of
+    /// [`DEFAULT_CONSENT_TEXT`], e.g. wording approved by legal or another
+    /// language.
+    pub fn with_consent_text(mut self, text: impl Into<String>) -> Self {
+        self.consent_text = text.into();
+        self
+    }
+
+    /// IndexedDB, falling back to `localStorage`.
+    pub fn browser(profile_id: impl Into<String>) -> Self {
+        Self::new(Rc::new(BrowserStorage::default()), profile_id)
+    }
+
+    fn key(&self) -> String {
+        format!("{KEY_PREFIX}{}", self.profile_id)
+    }
+
+    /// Store `strokes` for reuse. Call only after the signer explicitly
+    /// agreed to `consent_text`; the wording is kept with the signature.
+    pub async fn save(
+        &self,
+        strokes: Vec<Stroke>,
+        canvas_width: u32,
+        canvas_height: u32
+    ) -> Result<SavedSignature, SigError> {
+        if self.consent_text.trim().is_empty() {
+            return Err(SigError::InvalidState(
+                "saving a signature requires the signer's consent".into()
+            ));
+        }
+        if strokes.iter().all(|s| s.points.is_empty()) {
+            return Err(SigError::InvalidState("nothing to save".into()));
+        }
+        let saved = SavedSignature {
+            strokes,
+            canvas_width,
+            canvas_height,
+            consent_text: self.consent_text.clone(),
+            consented_at: String::from(js_sys::Date::new_0().to_iso_string())
+        };
+        let json = serde_json::to_string(&saved)
+            .map_err(|e| SigError::OpFailed(format!("serialize profile: {e}")))?;
+        self.backend.set(&self.key(), json).await?;
+        Ok(saved)
+    }
+
+    /// Saved signature, `None` if there is none (or it can't be parsed).
+    pub async fn load(&self) -> Result<Option<SavedSignature>, SigError> {
+        let json = self.backend.get(&self.key()).await?;
+        Ok(json.and_then(|j| serde_json::from_str(&j).ok()))
+    }
+
+    /// Delete the saved signature (withdraws consent).
+    pub async fn delete(&self) -> Result<(), SigError> {
+        self.backend.remove(&self.key()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::draft::LocalStorage;
+
+    fn saved(width: u32, height: u32) -> SavedSignature {
+        SavedSignature {
+            strokes:       vec![Stroke {
+                points: vec![
+                    StrokePoint {
+                        x:        0.0,
+                        y:        0.0,
+                        t:        1.0,
+                        pressure: 0.5
+                    },
+                    StrokePoint {
+                        x:        width as f64,
+                        y:        height as f64,
+                        t:        2.0,
+                        pressure: 0.5
+                    },
+                ],
+                pen:    None
+            }],
+            canvas_width:  width,
+            canvas_height: height,
+            consent_text:  DEFAULT_CONSENT_TEXT.into(),
+            consented_at:  "2026-01-01T00:00:00.000Z".into()
+        }
+    }
+
+    #[test]
+    fn fit_keeps_strokes_on_the_same_canvas() {
+        let profile = saved(400, 200);
+        assert_eq!(profile.fit((400, 200)), profile.strokes);
+    }
+
+    #[test]
+    fn fit_scales_uniformly_and_centers() {
+        let fitted = saved(400, 200).fit((200, 200));
+        let points = &fitted[0].points;
+        // Half size, centered vertically in the square canvas
+        assert_eq!((points[0].x, points[0].y), (0.0, 50.0));
+        assert_eq!((points[1].x, points[1].y), (200.0, 150.0));
+        assert_eq!(points[1].t, 2.0);
+    }
+
+    #[test]
+    fn consent_text_defaults_and_can_be_replaced() {
+        let store = ProfileStore::new(Rc::new(LocalStorage), "customer");
+        assert_eq!(store.consent_text, DEFAULT_CONSENT_TEXT);
+        let custom = store
+            .clone()
+            .with_consent_text("Remember my signature on this device");
+        assert_eq!(custom.consent_text, "Remember my signature on this device");
+        assert!(store != custom);
+    }
+}
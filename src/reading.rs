@@ -0,0 +1,242 @@
+// src/reading.rs
+
+//! Read gate: how far the signer has read the contract before signing
+//! unlocks, either scrolled to the end or with every `[data-must-read]`
+//! section kept in view for a dwell time.
+
+use std::{cell::RefCell, rc::Rc};
+
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{Element, HtmlElement, Window};
+
+use crate::error::SigError;
+
+/// Sections that have to be read in `ReadGate::Sections` mode.
+pub const MUST_READ_SELECTOR: &str = "[data-must-read]";
+/// How often visibility is sampled, ms.
+const TICK_MS: i32 = 250;
+/// Longest gap between samples counted as reading time, ms (throttled
+/// background timers must not fast-forward the dwell).
+const MAX_TICK_GAP_MS: f64 = 1000.0;
+/// Slack when checking that the end of the contract was reached, px.
+const END_TOLERANCE: f64 = 2.0;
+
+/// What the signer has to do before signing unlocks.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ReadGate {
+    /// No requirement (`enabled` decides alone).
+    #[default]
+    None,
+    /// Scroll the contract container to its end.
+    ScrollToEnd,
+    /// Keep every `[data-must-read]` section inside the container in view
+    /// for `dwell_ms` (cumulative, only while the tab is visible).
+    Sections { dwell_ms: f64 }
+}
+
+/// Reading progress reported by `ReadTracker`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ReadProgress {
+    /// `0.0..=1.0`: share of the contract scrolled through, or mean dwell
+    /// progress over the sections.
+    pub fraction:       f64,
+    /// Sections with full dwell (`0` in scroll mode).
+    pub sections_read:  usize,
+    pub sections_total: usize,
+    /// Time since tracking started, ms.
+    pub elapsed_ms:     f64,
+    pub complete:       bool
+}
+
+/// RAII handle sampling the reading progress of a contract container. Stops
+/// by itself once complete; on drop, the timer is cleared.
+pub struct ReadTracker {
+    window:   Window,
+    interval: Rc<RefCell<Option<i32>>>,
+    _tick:    Closure<dyn FnMut()>
+}
+
+impl Drop for ReadTracker {
+    fn drop(&mut self) {
+        if let Some(id) = self.interval.borrow_mut().take() {
+            self.window.clear_interval_with_handle(id);
+        }
+    }
+}
+
+struct ReadState {
+    gate:      ReadGate,
+    container: HtmlElement,
+    /// Sections with accumulated in-view time, ms.
+    sections:  Vec<(Element, f64)>,
+    started:   f64,
+    last_tick: f64,
+    /// Furthest scroll fraction seen (progress never goes back).
+    best:      f64,
+    reported:  Option<ReadProgress>
+}
+
+impl ReadState {
+    fn sample(&mut self, window: &Window) -> ReadProgress {
+        let now = js_sys::Date::now();
+        let dt = (now - self.last_tick).clamp(0.0, MAX_TICK_GAP_MS);
+        self.last_tick = now;
+        let win_h = window
+            .inner_height()
+            .ok()
+            .and_then(|v| v.as_f64())
+            .unwrap_or(0.0);
+        let cr = self.container.get_bounding_client_rect();
+        let mut progress = ReadProgress {
+            elapsed_ms: now - self.started,
+            ..Default::default()
+        };
+
+        match self.gate {
+            ReadGate::None => {
+                progress.fraction = 1.0;
+                progress.complete = true;
+            }
+            ReadGate::ScrollToEnd => {
+                // Bottom of the content that is on screen: inner scroll plus
+                // how much of the container the page viewport shows
+                let c = &self.container;
+                let shown = (win_h - cr.top()).clamp(0.0, c.client_height() as f64);
+                let bottom = c.scroll_top() as f64 + shown;
+                let total = (c.scroll_height() as f64).max(1.0);
+                self.best = self.best.max((bottom / total).min(1.0));
+                progress.fraction = self.best;
+                progress.complete = self.best >= 1.0 || bottom >= total - END_TOLERANCE;
+                if progress.complete {
+                    progress.fraction = 1.0;
+                }
+            }
+            ReadGate::Sections {
+                dwell_ms
+            } => {
+                let hidden = window.document().is_some_and(|d| d.hidden());
+                let top = cr.top().max(0.0);
+                let bottom = cr.bottom().min(win_h);
+                let viewport = (bottom - top).max(0.0);
+                let dwell_ms = dwell_ms.max(0.0);
+                let mut sum = 0.0;
+                for (el, dwell) in &mut self.sections {
+                    let r = el.get_bounding_client_rect();
+                    let visible = r.bottom().min(bottom) - r.top().max(top);
+                    // Half of the section, or half the viewport for tall ones
+                    let in_view = visible > 0.0 && visible >= 0.5 * r.height().min(viewport);
+                    if in_view && !hidden {
+                        *dwell += dt;
+                    }
+                    let done = if dwell_ms > 0.0 {
+                        (*dwell / dwell_ms).min(1.0)
+                    } else if *dwell > 0.0 || in_view {
+                        1.0
+                    } else {
+                        0.0
+                    };
+                    if done >= 1.0 {
+                        progress.sections_read += 1;
+                    }
+                    sum += done;
+                }
+                progress.sections_total = self.sections.len();
+                progress.fraction = sum / self.sections.len().max(1) as f64;
+                progress.complete = progress.sections_read == progress.sections_total;
+            }
+        }
+        progress
+    }
+
+    /// Worth reporting: first sample, completion, a new section, or at
+    /// least a percent of progress.
+    fn changed(&self, p: &ReadProgress) -> bool {
+        self.reported.is_none_or(|prev| {
+            prev.complete != p.complete
+                || prev.sections_read != p.sections_read
+                || (p.fraction - prev.fraction).abs() >= 0.01
+        })
+    }
+}
+
+impl ReadTracker {
+    /// Start sampling `container` for `gate`. `on_progress` gets the
+    /// progress whenever it changes, the last call with `complete == true`.
+    /// In `Sections` mode the container must hold `[data-must-read]`
+    /// elements.
+    pub fn new(
+        container: HtmlElement,
+        gate: ReadGate,
+        mut on_progress: impl FnMut(ReadProgress) + 'static
+    ) -> Result<Self, SigError> {
+        let window = web_sys::window().ok_or(SigError::DomUnavailable)?;
+        let mut sections = Vec::new();
+        if let ReadGate::Sections {
+            ..
+        } = gate
+        {
+            let list = container
+                .query_selector_all(MUST_READ_SELECTOR)
+                .map_err(|_| SigError::OpFailed("querySelectorAll".into()))?;
+            for i in 0..list.length() {
+                if let Some(el) = list.item(i).and_then(|n| n.dyn_into::<Element>().ok()) {
+                    sections.push((el, 0.0));
+                }
+            }
+            if sections.is_empty() {
+                return Err(SigError::ElementNotFound(MUST_READ_SELECTOR.into()));
+            }
+        }
+
+        let now = js_sys::Date::now();
+        let mut state = ReadState {
+            gate,
+            container,
+            sections,
+            started: now,
+            last_tick: now,
+            best: 0.0,
+            reported: None
+        };
+        let interval = Rc::new(RefCell::new(None::<i32>));
+
+        // First sample right away (short contracts may already be complete)
+        let first = state.sample(&window);
+        on_progress(first);
+        state.reported = Some(first);
+        let done = first.complete;
+
+        let tick = {
+            let window = window.clone();
+            let interval = interval.clone();
+            Closure::<dyn FnMut()>::new(move || {
+                let progress = state.sample(&window);
+                if !state.changed(&progress) {
+                    return;
+                }
+                state.reported = Some(progress);
+                on_progress(progress);
+                if progress.complete
+                    && let Some(id) = interval.borrow_mut().take()
+                {
+                    window.clear_interval_with_handle(id);
+                }
+            })
+        };
+        if !done {
+            let id = window
+                .set_interval_with_callback_and_timeout_and_arguments_0(
+                    tick.as_ref().unchecked_ref(),
+                    TICK_MS
+                )
+                .map_err(|_| SigError::OpFailed("setInterval".into()))?;
+            *interval.borrow_mut() = Some(id);
+        }
+
+        Ok(Self {
+            window,
+            interval,
+            _tick: tick
+        })
+    }
+}
@@ -0,0 +1,283 @@
+// src/risk.rs
+
+use serde::{Deserialize, Serialize};
+
+use crate::signature_core::{Stroke, StrokePoint};
+
+/// Heuristics need at least this many samples in a stroke to say anything.
+const MIN_SAMPLES: usize = 8;
+/// Sampling intervals whose coefficient of variation is below this are
+/// "machine-regular".
+const UNIFORM_INTERVAL_CV: f64 = 0.01;
+/// Mean normalized jerk below this means no hand tremor / quantization noise.
+const ZERO_JITTER: f64 = 1e-3;
+/// Faster than any hand on a touch screen or tablet (px per ms).
+const MAX_VELOCITY: f64 = 15.0;
+/// Share of collinear interior points that counts as line interpolation.
+const STRAIGHT_LINE_RATIO: f64 = 0.9;
+
+/// Reason a signature looks machine-generated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskFlag {
+    /// Pointer events with `isTrusted == false` reached the bindings.
+    UntrustedEvents,
+    /// Sampling intervals are perfectly regular.
+    UniformSampling,
+    /// Path has none of the noise real input always has.
+    ZeroJitter,
+    /// Pointer moved faster than a hand can.
+    ImpossibleVelocity,
+    /// Strokes are straight segments between a few control points.
+    StraightLineInterpolation
+}
+
+impl RiskFlag {
+    fn weight(self) -> f64 {
+        match self {
+            Self::UntrustedEvents => 1.0,
+            Self::ImpossibleVelocity => 0.6,
+            Self::UniformSampling => 0.4,
+            Self::ZeroJitter => 0.4,
+            Self::StraightLineInterpolation => 0.3
+        }
+    }
+}
+
+/// Synthetic-input assessment of a captured signature.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RiskAssessment {
+    /// `0.0` (looks human) ..= `1.0` (certainly synthetic).
+    pub score:            f64,
+    pub flags:            Vec<RiskFlag>,
+    /// Number of untrusted pointer events dropped by the bindings.
+    pub untrusted_events: u32
+}
+
+/// Analyze the captured timeline for implausible input.
+pub fn assess_strokes(strokes: &[Stroke], untrusted_events: u32) -> RiskAssessment {
+    let mut flags = Vec::new();
+    if untrusted_events > 0 {
+        flags.push(RiskFlag::UntrustedEvents);
+    }
+
+    let long: Vec<&[StrokePoint]> = strokes
+        .iter()
+        .map(|s| s.points.as_slice())
+        .filter(|p| p.len() >= MIN_SAMPLES)
+        .collect();
+
+    if has_impossible_velocity(strokes) {
+        flags.push(RiskFlag::ImpossibleVelocity);
+    }
+    if !long.is_empty() {
+        if long.iter().all(|p| is_uniform_sampling(p)) {
+            flags.push(RiskFlag::UniformSampling);
+        }
+        if long.iter().all(|p| jitter(p) < ZERO_JITTER) {
+            flags.push(RiskFlag::ZeroJitter);
+        }
+        if long
+            .iter()
+            .all(|p| collinear_ratio(p) >= STRAIGHT_LINE_RATIO)
+        {
+            flags.push(RiskFlag::StraightLineInterpolation);
+        }
+    }
+
+    let score = 1.0 - flags.iter().map(|f| 1.0 - f.weight()).product::<f64>();
+    RiskAssessment {
+        score,
+        flags,
+        untrusted_events
+    }
+}
+
+fn is_uniform_sampling(points: &[StrokePoint]) -> bool {
+    let dts: Vec<f64> = points.windows(2).map(|w| w[1].t - w[0].t).collect();
+    let n = dts.len() as f64;
+    let mean = dts.iter().sum::<f64>() / n;
+    if mean <= 0.0 {
+        // Everything dispatched within the same millisecond
+        return true;
+    }
+    let var = dts.iter().map(|d| (d - mean) * (d - mean)).sum::<f64>() / n;
+    var.sqrt() / mean < UNIFORM_INTERVAL_CV
+}
+
+/// Mean magnitude of the third difference of the path, relative to the
+/// mean step length.
+fn jitter(points: &[StrokePoint]) -> f64 {
+    let step = points
+        .windows(2)
+        .map(|w| (w[1].x - w[0].x).hypot(w[1].y - w[0].y))
+        .sum::<f64>()
+        / (points.len() - 1) as f64;
+    if step <= f64::EPSILON {
+        return 0.0;
+    }
+    let jerks: Vec<f64> = points
+        .windows(4)
+        .map(|w| {
+            let jx = w[3].x - 3.0 * w[2].x + 3.0 * w[1].x - w[0].x;
+            let jy = w[3].y - 3.0 * w[2].y + 3.0 * w[1].y - w[0].y;
+            jx.hypot(jy)
+        })
+        .collect();
+    jerks.iter().sum::<f64>() / jerks.len() as f64 / step
+}
+
+fn collinear_ratio(points: &[StrokePoint]) -> f64 {
+    let interior = points.len() - 2;
+    let collinear = points
+        .windows(3)
+        .filter(|w| {
+            let (ax, ay) = (w[1].x - w[0].x, w[1].y - w[0].y);
+            let (bx, by) = (w[2].x - w[1].x, w[2].y - w[1].y);
+            let cross = (ax * by - ay * bx).abs();
+            // Tolerance relative to segment lengths: ~0.05° of turn
+            cross <= 1e-3 * ax.hypot(ay) * bx.hypot(by)
+        })
+        .count();
+    collinear as f64 / interior as f64
+}
+
+fn has_impossible_velocity(strokes: &[Stroke]) -> bool {
+    strokes.iter().any(|s| {
+        s.points.windows(2).any(|w| {
+            let dist = (w[1].x - w[0].x).hypot(w[1].y - w[0].y);
+            let dt = w[1].t - w[0].t;
+            if dt > 0.0 {
+                dist / dt > MAX_VELOCITY
+            } else {
+                // Same-millisecond samples are normal (coalesced events), but
+                // not across a long jump
+                dist > MAX_VELOCITY
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic noise in `-1.0..1.0` (LCG).
+    struct Noise(u64);
+
+    impl Noise {
+        fn next(&mut self) -> f64 {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (self.0 >> 11) as f64 / (1u64 << 52) as f64 - 1.0
+        }
+    }
+
+    fn stroke(points: Vec<(f64, f64, f64)>) -> Stroke {
+        Stroke {
+            points: points
+                .into_iter()
+                .map(|(x, y, t)| StrokePoint {
+                    x,
+                    y,
+                    t,
+                    pressure: 0.5
+                })
+                .collect(),
+            pen:    None
+        }
+    }
+
+    /// Looping pen stroke sampled at a jittery ~8 ms with hand tremor.
+    fn human(seed: u64) -> Stroke {
+        let mut noise = Noise(seed);
+        let mut t = 1000.0;
+        stroke(
+            (0..60)
+                .map(|i| {
+                    let a = i as f64 * 0.2;
+                    t += 8.0 + 3.0 * noise.next();
+                    (
+                        40.0 + i as f64 * 4.0 + 12.0 * a.cos() + 0.4 * noise.next(),
+                        100.0 + 25.0 * a.sin() + 0.4 * noise.next(),
+                        t
+                    )
+                })
+                .collect()
+        )
+    }
+
+    /// Straight segments between control points, emitted every 16 ms.
+    fn scripted(controls: &[(f64, f64)]) -> Stroke {
+        let mut points: Vec<(f64, f64)> = controls
+            .windows(2)
+            .flat_map(|pair| {
+                let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+                (0..10).map(move |k| {
+                    let s = k as f64 / 10.0;
+                    (x0 + (x1 - x0) * s, y0 + (y1 - y0) * s)
+                })
+            })
+            .collect();
+        points.extend(controls.last());
+        stroke(
+            points
+                .into_iter()
+                .enumerate()
+                .map(|(i, (x, y))| (x, y, 1000.0 + i as f64 * 16.0))
+                .collect()
+        )
+    }
+
+    #[test]
+    fn human_like_input_is_not_flagged() {
+        let risk = assess_strokes(&[human(1), human(2)], 0);
+        assert_eq!(risk.flags, Vec::new());
+        assert_eq!(risk.score, 0.0);
+    }
+
+    #[test]
+    fn scripted_lines_are_flagged() {
+        let risk = assess_strokes(&[scripted(&[(10.0, 10.0), (200.0, 10.0)])], 0);
+        assert_eq!(
+            risk.flags,
+            vec![
+                RiskFlag::UniformSampling,
+                RiskFlag::ZeroJitter,
+                RiskFlag::StraightLineInterpolation
+            ]
+        );
+        let expected = 1.0 - 0.6 * 0.6 * 0.7;
+        assert!((risk.score - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn a_single_human_stroke_clears_the_all_strokes_checks() {
+        let risk = assess_strokes(&[scripted(&[(10.0, 10.0), (200.0, 10.0)]), human(3)], 0);
+        assert_eq!(risk.flags, Vec::new());
+    }
+
+    #[test]
+    fn impossible_velocity_and_untrusted_events_are_flagged() {
+        let teleport = stroke(vec![(0.0, 0.0, 1000.0), (900.0, 0.0, 1010.0)]);
+        let risk = assess_strokes(&[human(4), teleport], 3);
+        assert_eq!(
+            risk.flags,
+            vec![RiskFlag::UntrustedEvents, RiskFlag::ImpossibleVelocity]
+        );
+        assert_eq!(risk.score, 1.0);
+        assert_eq!(risk.untrusted_events, 3);
+
+        // Coalesced same-millisecond samples are fine if they are close
+        let coalesced = stroke(vec![(0.0, 0.0, 1000.0), (3.0, 1.0, 1000.0)]);
+        assert!(!has_impossible_velocity(&[coalesced]));
+    }
+
+    #[test]
+    fn short_strokes_only_get_the_velocity_check() {
+        let dot = stroke(vec![(5.0, 5.0, 1000.0), (5.0, 5.0, 1016.0)]);
+        assert_eq!(assess_strokes(&[dot], 0), RiskAssessment::default());
+    }
+}
@@ -0,0 +1,542 @@
+// src/seal.rs
+
+//! Detached signatures ("seals") over a serialized [`SignedEnvelope`].
+//!
+//! Two ways to seal:
+//! - WebCrypto keys via [`WebCryptoKey`] (async, browser / any JS runtime with
+//!   `crypto.subtle`);
+//! - application-held keys via the [`EnvelopeSigner`] / [`EnvelopeVerifier`]
+//!   traits (sync, pure Rust). With the `native-verify` feature they are
+//!   implemented for `ed25519-dalek` and `p256` keys, so a server can check
+//!   seals without any JS runtime.
+//!
+//! Both produce the same [`EnvelopeSeal`], so a browser-made seal can be
+//! checked on the server with [`verify_envelope`].
+
+use js_sys::{Array, Object, Reflect, Uint8Array};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{CryptoKey, SubtleCrypto};
+
+use crate::{
+    envelope::{sha256_hex, to_hex, SignedEnvelope},
+    error::SigError
+};
+
+/// Signature algorithm of a seal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SealAlgorithm {
+    /// Ed25519, 64-byte signature.
+    Ed25519,
+    /// ECDSA over P-256 with SHA-256; raw `r || s` (64 bytes), as produced by
+    /// WebCrypto.
+    EcdsaP256Sha256
+}
+
+/// Detached signature over `SignedEnvelope::to_canonical_json()`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EnvelopeSeal {
+    pub algorithm:    SealAlgorithm,
+    /// Optional key identifier (fingerprint, `kid`, ...), not interpreted.
+    pub key_id:       Option<String>,
+    /// SHA-256 (hex) of the sealed bytes; a cheap pre-check before verifying.
+    pub payload_hash: String,
+    /// The exact JSON that was signed. Verification checks the signature
+    /// over these bytes and that they describe the given envelope, so fields
+    /// added to `SignedEnvelope` by later versions don't break old seals.
+    /// Empty for seals made before it was stored (the envelope is then
+    /// re-serialized, which only works with the same crate version).
+    #[serde(default)]
+    pub payload:      String,
+    /// Signature bytes, hex.
+    pub signature:    String
+}
+
+impl EnvelopeSeal {
+    fn new(
+        algorithm: SealAlgorithm,
+        key_id: Option<String>,
+        payload: Vec<u8>,
+        signature: &[u8]
+    ) -> Result<Self, SigError> {
+        Ok(Self {
+            algorithm,
+            key_id,
+            payload_hash: sha256_hex(&payload),
+            payload: String::from_utf8(payload)
+                .map_err(|_| SigError::OpFailed("envelope JSON is not UTF-8".into()))?,
+            signature: to_hex(signature)
+        })
+    }
+
+    /// The envelope as it was sealed (`None` for seals without a stored
+    /// payload).
+    pub fn sealed_envelope(&self) -> Result<Option<SignedEnvelope>, SigError> {
+        if self.payload.is_empty() {
+            return Ok(None);
+        }
+        serde_json::from_str(&self.payload)
+            .map(Some)
+            .map_err(|e| SigError::OpFailed(format!("parse sealed envelope: {e}")))
+    }
+
+    /// Bytes to check the signature over, or `None` if the seal does not
+    /// belong to `envelope` (altered, or a different one).
+    fn payload_for(&self, envelope: &SignedEnvelope) -> Result<Option<Vec<u8>>, SigError> {
+        if self.payload.is_empty() {
+            return envelope.to_canonical_json().map(Some);
+        }
+        if sha256_hex(self.payload.as_bytes()) != self.payload_hash {
+            return Ok(None);
+        }
+        // Parsed with today's defaults, like `envelope` itself was
+        let same = serde_json::from_str::<SignedEnvelope>(&self.payload)
+            .is_ok_and(|sealed| sealed == *envelope);
+        Ok(same.then(|| self.payload.as_bytes().to_vec()))
+    }
+}
+
+/// Application-held private key.
+pub trait EnvelopeSigner {
+    fn algorithm(&self) -> SealAlgorithm;
+
+    /// Identifier copied into `EnvelopeSeal::key_id`.
+    fn key_id(&self) -> Option<String> {
+        None
+    }
+
+    /// Sign raw bytes, returning the signature in the algorithm's raw form.
+    fn sign(&self, payload: &[u8]) -> Result<Vec<u8>, SigError>;
+}
+
+/// Application-held public key.
+pub trait EnvelopeVerifier {
+    fn algorithm(&self) -> SealAlgorithm;
+
+    /// Check a raw signature over `payload`.
+    fn verify(&self, payload: &[u8], signature: &[u8]) -> Result<bool, SigError>;
+}
+
+/// Seal an envelope with an application-held key.
+pub fn seal_envelope(
+    envelope: &SignedEnvelope,
+    signer: &dyn EnvelopeSigner
+) -> Result<EnvelopeSeal, SigError> {
+    let payload = envelope.to_canonical_json()?;
+    let signature = signer.sign(&payload)?;
+    EnvelopeSeal::new(signer.algorithm(), signer.key_id(), payload, &signature)
+}
+
+/// Verify a seal with an application-held public key. Usable natively (no
+/// DOM access). Returns `Ok(false)` if the envelope was altered or the
+/// signature does not match.
+pub fn verify_envelope(
+    envelope: &SignedEnvelope,
+    seal: &EnvelopeSeal,
+    verifier: &dyn EnvelopeVerifier
+) -> Result<bool, SigError> {
+    if verifier.algorithm() != seal.algorithm {
+        return Err(SigError::Crypto(
+            "seal algorithm does not match verifier".into()
+        ));
+    }
+    let Some(payload) = seal.payload_for(envelope)? else {
+        return Ok(false);
+    };
+    if sha256_hex(&payload) != seal.payload_hash {
+        return Ok(false);
+    }
+    verifier.verify(&payload, &from_hex(&seal.signature)?)
+}
+
+#[cfg(any(feature = "native-verify", test))]
+impl EnvelopeSigner for ed25519_dalek::SigningKey {
+    fn algorithm(&self) -> SealAlgorithm {
+        SealAlgorithm::Ed25519
+    }
+
+    fn sign(&self, payload: &[u8]) -> Result<Vec<u8>, SigError> {
+        use ed25519_dalek::Signer;
+        Ok(Signer::sign(self, payload).to_bytes().to_vec())
+    }
+}
+
+#[cfg(any(feature = "native-verify", test))]
+impl EnvelopeVerifier for ed25519_dalek::VerifyingKey {
+    fn algorithm(&self) -> SealAlgorithm {
+        SealAlgorithm::Ed25519
+    }
+
+    fn verify(&self, payload: &[u8], signature: &[u8]) -> Result<bool, SigError> {
+        let Ok(signature) = ed25519_dalek::Signature::from_slice(signature) else {
+            return Ok(false);
+        };
+        Ok(self.verify_strict(payload, &signature).is_ok())
+    }
+}
+
+#[cfg(any(feature = "native-verify", test))]
+impl EnvelopeSigner for p256::ecdsa::SigningKey {
+    fn algorithm(&self) -> SealAlgorithm {
+        SealAlgorithm::EcdsaP256Sha256
+    }
+
+    fn sign(&self, payload: &[u8]) -> Result<Vec<u8>, SigError> {
+        use p256::ecdsa::{signature::Signer, Signature};
+        let signature: Signature = Signer::sign(self, payload);
+        Ok(signature.to_bytes().to_vec())
+    }
+}
+
+#[cfg(any(feature = "native-verify", test))]
+impl EnvelopeVerifier for p256::ecdsa::VerifyingKey {
+    fn algorithm(&self) -> SealAlgorithm {
+        SealAlgorithm::EcdsaP256Sha256
+    }
+
+    /// Expects the raw `r || s` form WebCrypto produces.
+    fn verify(&self, payload: &[u8], signature: &[u8]) -> Result<bool, SigError> {
+        use p256::ecdsa::{signature::Verifier, Signature};
+        let Ok(signature) = Signature::from_slice(signature) else {
+            return Ok(false);
+        };
+        Ok(Verifier::verify(self, payload, &signature).is_ok())
+    }
+}
+
+/// WebCrypto key pair.
+pub struct WebCryptoKeyPair {
+    pub private: WebCryptoKey,
+    pub public:  WebCryptoKey
+}
+
+/// `CryptoKey` together with the seal algorithm it is used for.
+#[derive(Clone)]
+pub struct WebCryptoKey {
+    key:       CryptoKey,
+    algorithm: SealAlgorithm
+}
+
+impl WebCryptoKey {
+    /// Wrap an existing `CryptoKey` (e.g. loaded from IndexedDB).
+    pub fn from_crypto_key(key: CryptoKey, algorithm: SealAlgorithm) -> Self {
+        Self {
+            key,
+            algorithm
+        }
+    }
+
+    pub fn crypto_key(&self) -> &CryptoKey {
+        &self.key
+    }
+
+    pub fn algorithm(&self) -> SealAlgorithm {
+        self.algorithm
+    }
+
+    /// Generate a fresh pair. The private key is non-extractable.
+    pub async fn generate(algorithm: SealAlgorithm) -> Result<WebCryptoKeyPair, SigError> {
+        let params = key_params(algorithm)?;
+        let usages = key_usages(&["sign", "verify"]);
+        let promise = subtle()?
+            .generate_key_with_object(&params, false, &usages)
+            .map_err(|e| crypto_err("generateKey", e))?;
+        let pair = JsFuture::from(promise)
+            .await
+            .map_err(|e| crypto_err("generateKey", e))?;
+        let private = Reflect::get(&pair, &"privateKey".into())
+            .ok()
+            .and_then(|k| k.dyn_into::<CryptoKey>().ok())
+            .ok_or_else(|| SigError::Crypto("generateKey: no privateKey".into()))?;
+        let public = Reflect::get(&pair, &"publicKey".into())
+            .ok()
+            .and_then(|k| k.dyn_into::<CryptoKey>().ok())
+            .ok_or_else(|| SigError::Crypto("generateKey: no publicKey".into()))?;
+        Ok(WebCryptoKeyPair {
+            private: Self::from_crypto_key(private, algorithm),
+            public:  Self::from_crypto_key(public, algorithm)
+        })
+    }
+
+    /// Import a PKCS#8 private key.
+    pub async fn import_pkcs8(algorithm: SealAlgorithm, der: &[u8]) -> Result<Self, SigError> {
+        Self::import("pkcs8", algorithm, der, "sign").await
+    }
+
+    /// Import a raw public key (32 bytes for Ed25519, uncompressed SEC1 point
+    /// for P-256).
+    pub async fn import_public_raw(
+        algorithm: SealAlgorithm,
+        raw: &[u8]
+    ) -> Result<Self, SigError> {
+        Self::import("raw", algorithm, raw, "verify").await
+    }
+
+    /// Export a public key in raw form, for shipping to the server.
+    pub async fn export_public_raw(&self) -> Result<Vec<u8>, SigError> {
+        let promise = subtle()?
+            .export_key("raw", &self.key)
+            .map_err(|e| crypto_err("exportKey", e))?;
+        let buf = JsFuture::from(promise)
+            .await
+            .map_err(|e| crypto_err("exportKey", e))?;
+        Ok(Uint8Array::new(&buf).to_vec())
+    }
+
+    /// Seal an envelope with this (private) key.
+    pub async fn seal(
+        &self,
+        envelope: &SignedEnvelope,
+        key_id: Option<String>
+    ) -> Result<EnvelopeSeal, SigError> {
+        let payload = envelope.to_canonical_json()?;
+        let promise = subtle()?
+            .sign_with_object_and_u8_array(&sign_params(self.algorithm)?, &self.key, &payload)
+            .map_err(|e| crypto_err("sign", e))?;
+        let buf = JsFuture::from(promise)
+            .await
+            .map_err(|e| crypto_err("sign", e))?;
+        EnvelopeSeal::new(
+            self.algorithm,
+            key_id,
+            payload,
+            &Uint8Array::new(&buf).to_vec()
+        )
+    }
+
+    /// Verify a seal with this (public) key.
+    pub async fn verify(
+        &self,
+        envelope: &SignedEnvelope,
+        seal: &EnvelopeSeal
+    ) -> Result<bool, SigError> {
+        if self.algorithm != seal.algorithm {
+            return Err(SigError::Crypto("seal algorithm does not match key".into()));
+        }
+        let Some(payload) = seal.payload_for(envelope)? else {
+            return Ok(false);
+        };
+        if sha256_hex(&payload) != seal.payload_hash {
+            return Ok(false);
+        }
+        let signature = Uint8Array::from(from_hex(&seal.signature)?.as_slice());
+        let data = Uint8Array::from(payload.as_slice());
+        let promise = subtle()?
+            .verify_with_object_and_js_u8_array_and_js_u8_array(
+                &sign_params(self.algorithm)?,
+                &self.key,
+                &signature,
+                &data
+            )
+            .map_err(|e| crypto_err("verify", e))?;
+        let ok = JsFuture::from(promise)
+            .await
+            .map_err(|e| crypto_err("verify", e))?;
+        Ok(ok.as_bool().unwrap_or(false))
+    }
+
+    async fn import(
+        format: &str,
+        algorithm: SealAlgorithm,
+        bytes: &[u8],
+        usage: &str
+    ) -> Result<Self, SigError> {
+        let data = Uint8Array::from(bytes);
+        let promise = subtle()?
+            .import_key_with_object(
+                format,
+                &data,
+                &key_params(algorithm)?,
+                false,
+                &key_usages(&[usage])
+            )
+            .map_err(|e| crypto_err("importKey", e))?;
+        let key = JsFuture::from(promise)
+            .await
+            .map_err(|e| crypto_err("importKey", e))?
+            .dyn_into::<CryptoKey>()
+            .map_err(|_| SigError::Crypto("importKey: not a CryptoKey".into()))?;
+        Ok(Self::from_crypto_key(key, algorithm))
+    }
+}
+
+fn subtle() -> Result<SubtleCrypto, SigError> {
+    let window = web_sys::window().ok_or(SigError::DomUnavailable)?;
+    let crypto = window
+        .crypto()
+        .map_err(|_| SigError::Crypto("window.crypto unavailable".into()))?;
+    Ok(crypto.subtle())
+}
+
+/// Algorithm object for generateKey/importKey.
+fn key_params(algorithm: SealAlgorithm) -> Result<Object, SigError> {
+    match algorithm {
+        SealAlgorithm::Ed25519 => js_object(&[("name", "Ed25519")]),
+        SealAlgorithm::EcdsaP256Sha256 => js_object(&[("name", "ECDSA"), ("namedCurve", "P-256")])
+    }
+}
+
+/// Algorithm object for sign/verify.
+fn sign_params(algorithm: SealAlgorithm) -> Result<Object, SigError> {
+    match algorithm {
+        SealAlgorithm::Ed25519 => js_object(&[("name", "Ed25519")]),
+        SealAlgorithm::EcdsaP256Sha256 => js_object(&[("name", "ECDSA"), ("hash", "SHA-256")])
+    }
+}
+
+fn js_object(pairs: &[(&str, &str)]) -> Result<Object, SigError> {
+    let obj = Object::new();
+    for (k, v) in pairs {
+        Reflect::set(&obj, &JsValue::from_str(k), &JsValue::from_str(v))
+            .map_err(|_| SigError::OpFailed("Reflect.set".into()))?;
+    }
+    Ok(obj)
+}
+
+fn key_usages(usages: &[&str]) -> JsValue {
+    usages
+        .iter()
+        .map(|u| JsValue::from_str(u))
+        .collect::<Array>()
+        .into()
+}
+
+fn crypto_err(op: &str, e: JsValue) -> SigError {
+    SigError::Crypto(format!("crypto.subtle.{op}: {e:?}"))
+}
+
+fn from_hex(hex: &str) -> Result<Vec<u8>, SigError> {
+    if !hex.len().is_multiple_of(2) {
+        return Err(SigError::Crypto("odd-length hex".into()));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|b| u8::from_str_radix(b, 16).ok())
+                .ok_or_else(|| SigError::Crypto("invalid hex".into()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::{SigningKey, VerifyingKey};
+
+    use super::*;
+    use crate::envelope::SignerMetadata;
+
+    /// RFC 8032, section 7.1, TEST 1.
+    const RFC8032_SECRET: &str =
+        "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+    const RFC8032_PUBLIC: &str =
+        "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+    const RFC8032_SIGNATURE: &str = "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b";
+
+    /// Ed25519 signature of `envelope()` with the RFC 8032 key; pins both
+    /// the key handling and the canonical JSON.
+    const ENVELOPE_SIGNATURE: &str = "6d05bbfd11f7d2a2050e89debde45286d9450f7ff65c9878a19572d1d210a96e8b28beff95aae7a84aa5190255f2b25c083a9c186a63ef643656cd0d9b849d05";
+
+    fn key() -> SigningKey {
+        SigningKey::from_bytes(&from_hex(RFC8032_SECRET).unwrap().try_into().unwrap())
+    }
+
+    fn public_key() -> VerifyingKey {
+        VerifyingKey::from_bytes(&from_hex(RFC8032_PUBLIC).unwrap().try_into().unwrap()).unwrap()
+    }
+
+    fn envelope() -> SignedEnvelope {
+        serde_json::from_value(serde_json::json!({
+            "document_hash": "aa",
+            "signature_image_hash": "bb",
+            "stroke_data_hash": "cc",
+            "signed_at": "2026-01-02T03:04:05.000Z",
+            "signer": { "name": "Buyer", "email": null, "id": null },
+            "data_url": "data:image/png;base64,",
+            "strokes": [{ "points": [{ "x": 1.5, "y": 2.25, "t": 10.0, "pressure": 0.5 }] }]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let env = envelope();
+        let seal = seal_envelope(&env, &key()).unwrap();
+        let seal: EnvelopeSeal =
+            serde_json::from_str(&serde_json::to_string(&seal).unwrap()).unwrap();
+        let env: SignedEnvelope =
+            serde_json::from_slice(&env.to_canonical_json().unwrap()).unwrap();
+        assert!(verify_envelope(&env, &seal, &public_key()).unwrap());
+        assert_eq!(seal.sealed_envelope().unwrap(), Some(env));
+    }
+
+    #[test]
+    fn rejects_altered_envelope_or_payload() {
+        let public = public_key();
+        let env = envelope();
+        let seal = seal_envelope(&env, &key()).unwrap();
+
+        let mut altered = env.clone();
+        altered.signer = SignerMetadata {
+            name: "Seller".into(),
+            ..Default::default()
+        };
+        assert!(!verify_envelope(&altered, &seal, &public).unwrap());
+
+        let mut forged = seal.clone();
+        forged.payload = String::from_utf8(altered.to_canonical_json().unwrap()).unwrap();
+        assert!(!verify_envelope(&altered, &forged, &public).unwrap());
+        forged.payload_hash = sha256_hex(forged.payload.as_bytes());
+        assert!(!verify_envelope(&altered, &forged, &public).unwrap());
+    }
+
+    #[test]
+    fn old_seal_survives_new_fields() {
+        // JSON as written by a version without `origin` / `consent`
+        let mut old = serde_json::to_value(envelope()).unwrap();
+        let fields = old.as_object_mut().unwrap();
+        fields.remove("origin");
+        fields.remove("consent");
+        let old_bytes = serde_json::to_vec(&old).unwrap();
+        let signature = EnvelopeSigner::sign(&key(), &old_bytes).unwrap();
+        let seal = EnvelopeSeal::new(SealAlgorithm::Ed25519, None, old_bytes.clone(), &signature)
+            .unwrap();
+
+        let env: SignedEnvelope = serde_json::from_slice(&old_bytes).unwrap();
+        assert_ne!(env.to_canonical_json().unwrap(), old_bytes);
+        assert!(verify_envelope(&env, &seal, &public_key()).unwrap());
+    }
+
+    #[test]
+    fn ed25519_matches_rfc8032() {
+        assert_eq!(to_hex(key().verifying_key().as_bytes()), RFC8032_PUBLIC);
+        let signature = from_hex(RFC8032_SIGNATURE).unwrap();
+        assert_eq!(EnvelopeSigner::sign(&key(), b"").unwrap(), signature);
+        assert!(EnvelopeVerifier::verify(&public_key(), b"", &signature).unwrap());
+        assert!(!EnvelopeVerifier::verify(&public_key(), b"x", &signature).unwrap());
+        assert!(!EnvelopeVerifier::verify(&public_key(), b"", &signature[..63]).unwrap());
+    }
+
+    #[test]
+    fn envelope_seal_known_answer() {
+        let seal = seal_envelope(&envelope(), &key()).unwrap();
+        assert_eq!(seal.signature, ENVELOPE_SIGNATURE);
+
+        let mut forged = seal.clone();
+        forged.signature = ENVELOPE_SIGNATURE.replacen('a', "b", 1);
+        assert!(verify_envelope(&envelope(), &seal, &public_key()).unwrap());
+        assert!(!verify_envelope(&envelope(), &forged, &public_key()).unwrap());
+    }
+
+    #[test]
+    fn p256_seal_verifies_and_rejects_other_keys() {
+        let key = p256::ecdsa::SigningKey::from_slice(&[7; 32]).unwrap();
+        let other = p256::ecdsa::SigningKey::from_slice(&[9; 32]).unwrap();
+        let seal = seal_envelope(&envelope(), &key).unwrap();
+        assert_eq!(from_hex(&seal.signature).unwrap().len(), 64);
+        assert!(verify_envelope(&envelope(), &seal, key.verifying_key()).unwrap());
+        assert!(!verify_envelope(&envelope(), &seal, other.verifying_key()).unwrap());
+        assert!(verify_envelope(&envelope(), &seal, &public_key()).is_err());
+    }
+}
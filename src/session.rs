@@ -0,0 +1,300 @@
+// src/session.rs
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    envelope::{SignedEnvelope, SignerMetadata},
+    error::SigError
+};
+
+/// Where a signer is in the signing order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignerState {
+    /// Waiting for earlier signers.
+    Pending,
+    /// Currently allowed to fill their fields.
+    Active,
+    Signed,
+    Declined
+}
+
+/// Overall state of a session.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionStatus {
+    InProgress,
+    Completed,
+    /// Some signer declined; nobody after them can sign.
+    Declined
+}
+
+/// One party of a multi-signer contract.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SessionSigner {
+    pub signer:         SignerMetadata,
+    /// Field ids (see `FieldIds`) this signer must fill.
+    pub fields:         Vec<String>,
+    pub state:          SignerState,
+    /// Envelopes of the fields filled so far.
+    pub envelopes:      Vec<SignedEnvelope>,
+    pub decline_reason: Option<String>
+}
+
+impl SessionSigner {
+    pub fn new(signer: SignerMetadata, fields: Vec<String>) -> Self {
+        Self {
+            signer,
+            fields,
+            state: SignerState::Pending,
+            envelopes: Vec::new(),
+            decline_reason: None
+        }
+    }
+
+    fn has_signed(&self, field_id: &str) -> bool {
+        self.envelopes
+            .iter()
+            .any(|e| e.field_id.as_deref() == Some(field_id))
+    }
+}
+
+/// Ordered multi-signer workflow on top of the pads. Serializable, so it can
+/// be stored server-side and handed to the next party.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SigningSession {
+    signers: Vec<SessionSigner>
+}
+
+impl SigningSession {
+    /// Create a session; the first signer becomes active. Every signer
+    /// needs at least one field, otherwise the session could never advance
+    /// past them.
+    pub fn new(mut signers: Vec<SessionSigner>) -> Result<Self, SigError> {
+        if let Some(first) = signers.first_mut() {
+            first.state = SignerState::Active;
+        }
+        let session = Self {
+            signers
+        };
+        session.validate()?;
+        Ok(session)
+    }
+
+    pub fn signers(&self) -> &[SessionSigner] {
+        &self.signers
+    }
+
+    pub fn status(&self) -> SessionStatus {
+        if self
+            .signers
+            .iter()
+            .any(|s| s.state == SignerState::Declined)
+        {
+            SessionStatus::Declined
+        } else if self.signers.iter().all(|s| s.state == SignerState::Signed) {
+            SessionStatus::Completed
+        } else {
+            SessionStatus::InProgress
+        }
+    }
+
+    pub fn active_signer(&self) -> Option<&SessionSigner> {
+        self.signers.iter().find(|s| s.state == SignerState::Active)
+    }
+
+    /// Is `field_id` one of the active signer's still unfilled fields?
+    pub fn is_field_enabled(&self, field_id: &str) -> bool {
+        self.active_signer()
+            .is_some_and(|s| s.fields.iter().any(|f| f == field_id) && !s.has_signed(field_id))
+    }
+
+    /// Accept an envelope for one of the active signer's fields. When all of
+    /// their fields are filled they become `Signed` and the next signer
+    /// becomes `Active`.
+    pub fn record_signature(&mut self, envelope: SignedEnvelope) -> Result<(), SigError> {
+        let field_id = envelope
+            .field_id
+            .clone()
+            .ok_or_else(|| SigError::InvalidState("envelope has no field_id".into()))?;
+        if !self.is_field_enabled(&field_id) {
+            return Err(SigError::InvalidState(format!(
+                "field '{field_id}' is not open for the active signer"
+            )));
+        }
+        let idx = self.active_index().ok_or_else(no_active)?;
+
+        let signer = &mut self.signers[idx];
+        signer.envelopes.push(envelope);
+        if signer.fields.iter().all(|f| signer.has_signed(f)) {
+            signer.state = SignerState::Signed;
+            if let Some(next) = self.signers.get_mut(idx + 1) {
+                next.state = SignerState::Active;
+            }
+        }
+        Ok(())
+    }
+
+    /// The active signer refuses to sign. Ends the session.
+    pub fn decline(&mut self, reason: Option<String>) -> Result<(), SigError> {
+        let idx = self.active_index().ok_or_else(no_active)?;
+        let signer = &mut self.signers[idx];
+        signer.state = SignerState::Declined;
+        signer.decline_reason = reason;
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> Result<String, SigError> {
+        serde_json::to_string(self)
+            .map_err(|e| SigError::OpFailed(format!("serialize session: {e}")))
+    }
+
+    /// Parse a stored session, rejecting state no sequence of calls could
+    /// have produced.
+    pub fn from_json(json: &str) -> Result<Self, SigError> {
+        let session: Self = serde_json::from_str(json)
+            .map_err(|e| SigError::OpFailed(format!("parse session: {e}")))?;
+        session.validate()?;
+        Ok(session)
+    }
+
+    /// Signers are `Signed` up to the one that is `Active` or `Declined`,
+    /// `Pending` after it; envelopes only for own fields, all of them
+    /// exactly when `Signed`.
+    fn validate(&self) -> Result<(), SigError> {
+        let invalid = |msg: String| Err(SigError::InvalidState(msg));
+        if self.signers.is_empty() {
+            return invalid("session has no signers".into());
+        }
+        let mut current = None;
+        for (i, signer) in self.signers.iter().enumerate() {
+            let name = &signer.signer.name;
+            if signer.fields.is_empty() {
+                return invalid(format!("signer '{name}' has no fields"));
+            }
+            if let Some(env) = signer.envelopes.iter().find(|e| {
+                !e.field_id
+                    .as_ref()
+                    .is_some_and(|f| signer.fields.contains(f))
+            }) {
+                return invalid(format!(
+                    "signer '{name}' has an envelope for foreign field {:?}",
+                    env.field_id
+                ));
+            }
+            let complete = signer.fields.iter().all(|f| signer.has_signed(f));
+            let expected = match current {
+                Some(_) => signer.state == SignerState::Pending && signer.envelopes.is_empty(),
+                None => match signer.state {
+                    SignerState::Signed => complete,
+                    SignerState::Active | SignerState::Declined => {
+                        current = Some(i);
+                        !complete
+                    }
+                    SignerState::Pending => false
+                }
+            };
+            if !expected {
+                return invalid(format!(
+                    "signer '{name}' cannot be {:?} at position {i}",
+                    signer.state
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn active_index(&self) -> Option<usize> {
+        self.signers
+            .iter()
+            .position(|s| s.state == SignerState::Active)
+    }
+}
+
+fn no_active() -> SigError {
+    SigError::InvalidState("no active signer".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signer(name: &str, fields: &[&str]) -> SessionSigner {
+        SessionSigner::new(
+            SignerMetadata {
+                name: name.into(),
+                ..Default::default()
+            },
+            fields.iter().map(|f| f.to_string()).collect()
+        )
+    }
+
+    fn envelope(field_id: &str) -> SignedEnvelope {
+        let mut env: SignedEnvelope = serde_json::from_value(serde_json::json!({
+            "document_hash": "", "signature_image_hash": "", "stroke_data_hash": "",
+            "signed_at": "", "signer": { "name": "", "email": null, "id": null },
+            "data_url": "", "strokes": []
+        }))
+        .unwrap();
+        env.field_id = Some(field_id.into());
+        env
+    }
+
+    fn session() -> SigningSession {
+        SigningSession::new(vec![signer("A", &["a1", "a2"]), signer("B", &["b1"])]).unwrap()
+    }
+
+    #[test]
+    fn advances_in_order() {
+        let mut s = session();
+        assert!(s.is_field_enabled("a1"));
+        assert!(!s.is_field_enabled("b1"));
+        assert!(s.record_signature(envelope("b1")).is_err());
+
+        s.record_signature(envelope("a1")).unwrap();
+        assert!(!s.is_field_enabled("a1"));
+        s.record_signature(envelope("a2")).unwrap();
+        assert_eq!(s.active_signer().unwrap().signer.name, "B");
+
+        s.record_signature(envelope("b1")).unwrap();
+        assert_eq!(s.status(), SessionStatus::Completed);
+        assert!(s.active_signer().is_none());
+        assert_eq!(SigningSession::from_json(&s.to_json().unwrap()).unwrap(), s);
+    }
+
+    #[test]
+    fn decline_ends_session() {
+        let mut s = session();
+        s.decline(Some("no".into())).unwrap();
+        assert_eq!(s.status(), SessionStatus::Declined);
+        assert!(s.decline(None).is_err());
+        assert!(!s.is_field_enabled("b1"));
+        assert_eq!(SigningSession::from_json(&s.to_json().unwrap()).unwrap(), s);
+    }
+
+    #[test]
+    fn rejects_signer_without_fields() {
+        assert!(SigningSession::new(vec![signer("A", &["a1"]), signer("B", &[])]).is_err());
+        assert!(SigningSession::new(Vec::new()).is_err());
+    }
+
+    #[test]
+    fn from_json_rejects_impossible_states() {
+        let set = |states: [SignerState; 2]| {
+            let mut s = session();
+            s.signers[0].state = states[0];
+            s.signers[1].state = states[1];
+            SigningSession::from_json(&serde_json::to_string(&s).unwrap())
+        };
+        assert!(set([SignerState::Active, SignerState::Pending]).is_ok());
+        assert!(set([SignerState::Active, SignerState::Active]).is_err());
+        assert!(set([SignerState::Pending, SignerState::Pending]).is_err());
+        assert!(set([SignerState::Pending, SignerState::Active]).is_err());
+        // Signed without filling the fields
+        assert!(set([SignerState::Signed, SignerState::Active]).is_err());
+
+        let mut s = session();
+        s.signers[0].envelopes.push(envelope("b1"));
+        assert!(SigningSession::from_json(&serde_json::to_string(&s).unwrap()).is_err());
+    }
+}
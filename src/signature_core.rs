@@ -1,17 +1,267 @@
-use js_sys::Uint8Array;
+use std::{
+    cell::RefCell,
+    fmt::Write as _,
+    rc::{Rc, Weak}
+};
+
+use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
-use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, HtmlImageElement};
+
+use crate::{error::SigError, format::num};
+
+/// Pressure reported for pointers without pressure support.
+const DEFAULT_PRESSURE: f32 = 0.5;
+/// Default eraser radius, canvas px.
+const DEFAULT_ERASER_RADIUS: f64 = 8.0;
+/// Baseline inset from the canvas sides, fraction of the width.
+const BASELINE_INSET: f64 = 0.06;
+/// Largest zoom of the view.
+const MAX_ZOOM: f64 = 6.0;
+
+/// One sampled point of a stroke, in canvas pixels.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StrokePoint {
+    pub x:        f64,
+    pub y:        f64,
+    /// Milliseconds since the Unix epoch.
+    pub t:        f64,
+    /// Normalized pressure in `0.0..=1.0`.
+    pub pressure: f32
+}
+
+/// Shape of stroke ends (canvas `lineCap`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineCap {
+    Butt,
+    #[default]
+    Round,
+    Square
+}
+
+impl LineCap {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Butt => "butt",
+            Self::Round => "round",
+            Self::Square => "square"
+        }
+    }
+}
+
+/// Shape of stroke corners (canvas `lineJoin`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineJoin {
+    Miter,
+    #[default]
+    Round,
+    Bevel
+}
+
+impl LineJoin {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Miter => "miter",
+            Self::Round => "round",
+            Self::Bevel => "bevel"
+        }
+    }
+}
+
+/// Ink of the pad. The width follows the pointer pressure between
+/// `min_width` and `max_width`; equal widths give a uniform line.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PenStyle {
+    /// CSS hex colour, `#rgb` or `#rrggbb`.
+    pub color:     String,
+    /// Width at zero pressure, canvas px.
+    pub min_width: f64,
+    /// Width at full pressure, canvas px.
+    pub max_width: f64,
+    /// `0.0..=1.0`. Translucent strokes are drawn at one width (that of
+    /// their mean pressure) so overlapping segments don't darken.
+    pub opacity:   f64,
+    pub cap:       LineCap,
+    pub join:      LineJoin
+}
+
+impl Default for PenStyle {
+    /// Black, 2 px, round ends.
+    fn default() -> Self {
+        Self::solid("#000000", 2.0)
+    }
+}
+
+impl PenStyle {
+    /// Opaque pen of one width.
+    pub fn solid(color: impl Into<String>, width: f64) -> Self {
+        Self {
+            color:     color.into(),
+            min_width: width,
+            max_width: width,
+            opacity:   1.0,
+            cap:       LineCap::default(),
+            join:      LineJoin::default()
+        }
+    }
+
+    /// Line width at `pressure` (`0.0..=1.0`).
+    pub fn width(&self, pressure: f32) -> f64 {
+        let p = (pressure as f64).clamp(0.0, 1.0);
+        let (lo, hi) = (self.min_width.max(0.0), self.max_width.max(0.0));
+        lo + (hi - lo) * p
+    }
+
+    /// Does the width depend on pressure?
+    pub fn is_variable(&self) -> bool {
+        self.min_width != self.max_width
+    }
+
+    pub(crate) fn opacity(&self) -> f64 {
+        self.opacity.clamp(0.0, 1.0)
+    }
+
+    /// Colour as 8-bit RGB; black if `color` is not a hex colour.
+    pub fn rgb(&self) -> (u8, u8, u8) {
+        let hex = self.color.trim().trim_start_matches('#');
+        let digits: Option<Vec<u8>> = hex
+            .chars()
+            .map(|c| c.to_digit(16).map(|d| d as u8))
+            .collect();
+        match (hex.len(), digits) {
+            (3, Some(d)) => (d[0] * 17, d[1] * 17, d[2] * 17),
+            (6, Some(d)) => (d[0] * 16 + d[1], d[2] * 16 + d[3], d[4] * 16 + d[5]),
+            _ => (0, 0, 0)
+        }
+    }
+
+    /// Colour as `#rrggbb` (the only form `<input type="color">` accepts).
+    pub fn hex(&self) -> String {
+        let (r, g, b) = self.rgb();
+        format!("#{r:02x}{g:02x}{b:02x}")
+    }
+}
+
+/// Zoom and pan of the pad's view: a stroke point `p` shows at
+/// `p * zoom + pan` canvas px. Strokes and exports are not affected.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewTransform {
+    /// `1.0..=6.0`.
+    pub zoom:  f64,
+    pub pan_x: f64,
+    pub pan_y: f64
+}
+
+impl Default for ViewTransform {
+    /// Identity.
+    fn default() -> Self {
+        Self {
+            zoom:  1.0,
+            pan_x: 0.0,
+            pan_y: 0.0
+        }
+    }
+}
+
+/// Signature line across the pad, with a "✕" at its start.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Baseline {
+    /// Height of the line, fraction of the canvas height from the top.
+    pub y:    f64,
+    /// Draw the "✕" mark.
+    pub mark: bool
+}
+
+impl Default for Baseline {
+    fn default() -> Self {
+        Self {
+            y:    0.75,
+            mark: true
+        }
+    }
+}
+
+/// Backdrop of the pad.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Background {
+    /// Square grid, `spacing` canvas px.
+    Grid { spacing: f64 },
+    /// Image URL, stretched over the canvas (drawn once loaded).
+    Image(String)
+}
+
+/// Guides painted on the pad but never exported: they are redrawn with the
+/// strokes (undo, erase, resize) and left out of PNG/SVG output.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Decorations {
+    pub baseline:    Option<Baseline>,
+    /// Hint shown until the first stroke, e.g. "Sign here".
+    pub placeholder: Option<String>,
+    pub background:  Option<Background>,
+    /// CSS colour of the baseline, text and grid.
+    pub color:       String
+}
+
+impl Default for Decorations {
+    /// Baseline with a mark, no placeholder or background, grey.
+    fn default() -> Self {
+        Self {
+            baseline:    Some(Baseline::default()),
+            placeholder: None,
+            background:  None,
+            color:       "#9aa0a6".into()
+        }
+    }
+}
 
-use crate::error::SigError;
+/// Continuous pen-down..pen-up path.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Stroke {
+    pub points: Vec<StrokePoint>,
+    /// Pen the stroke was drawn with; `None` (strokes from before pen
+    /// styles) means `PenStyle::default()`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pen:    Option<PenStyle>
+}
 
 /// Encapsulates drawing logic and export of signature.
 pub struct SignaturePad {
-    canvas:   HtmlCanvasElement,
-    ctx:      CanvasRenderingContext2d,
-    drawing:  bool,
-    last_x:   f64,
-    last_y:   f64,
-    is_empty: bool
+    canvas:           HtmlCanvasElement,
+    ctx:              CanvasRenderingContext2d,
+    /// Size strokes are recorded and exported in, canvas px.
+    width:            u32,
+    height:           u32,
+    /// Backing-store pixels per canvas px (see `set_pixel_ratio`).
+    pixel_ratio:      f64,
+    view:             ViewTransform,
+    /// Touch points down (pointer id, position in view px).
+    touches:          Vec<(i32, f64, f64)>,
+    /// A pinch/pan gesture is on; touches don't draw until all are lifted.
+    gesture:          bool,
+    drawing:          bool,
+    last_x:           f64,
+    last_y:           f64,
+    is_empty:         bool,
+    strokes:          Vec<Stroke>,
+    pen:              PenStyle,
+    /// Eraser mode: pointer gestures erase instead of draw.
+    eraser:           bool,
+    eraser_radius:    f64,
+    /// An erase gesture is in progress.
+    erasing:          bool,
+    /// Points removed by the current (or last) erase gesture.
+    erased_points:    usize,
+    erase_history:    EraseHistory,
+    decorations:      Option<Decorations>,
+    /// Background image with its `onload` repaint, while set.
+    background:       Option<(HtmlImageElement, Closure<dyn FnMut()>)>,
+    /// The pad itself once shared (see `into_shared`); lets the background
+    /// `onload` repaint it.
+    this:             Weak<RefCell<SignaturePad>>,
+    /// Synthetic (`isTrusted == false`) events rejected by the bindings.
+    untrusted_events: u32
 }
 
 impl SignaturePad {
@@ -24,53 +274,559 @@ impl SignaturePad {
             .dyn_into::<CanvasRenderingContext2d>()
             .map_err(|_| SigError::NoContext2d)?;
 
-        // Sensible defaults for signature lines
-        ctx.set_line_width(2.0);
-        ctx.set_line_join("round");
-        ctx.set_line_cap("round");
-
         Ok(Self {
+            width: canvas.width(),
+            height: canvas.height(),
+            pixel_ratio: 1.0,
+            view: ViewTransform::default(),
+            touches: Vec::new(),
+            gesture: false,
             canvas,
             ctx,
             drawing: false,
             last_x: 0.0,
             last_y: 0.0,
-            is_empty: true
+            is_empty: true,
+            strokes: Vec::new(),
+            pen: PenStyle::default(),
+            eraser: false,
+            eraser_radius: DEFAULT_ERASER_RADIUS,
+            erasing: false,
+            erased_points: 0,
+            erase_history: Vec::new(),
+            decorations: None,
+            background: None,
+            this: Weak::new(),
+            untrusted_events: 0
         })
     }
 
+    /// Share the pad between event handlers. A shared pad repaints as soon
+    /// as a background image (see `Decorations`) loads; an unshared one
+    /// shows it from its next repaint on.
+    pub fn into_shared(self) -> Rc<RefCell<Self>> {
+        let pad = Rc::new(RefCell::new(self));
+        {
+            let mut inner = pad.borrow_mut();
+            inner.this = Rc::downgrade(&pad);
+            // Re-hook an image set before sharing; if it already loaded,
+            // its first `onload` found no pad to repaint
+            let onload = inner.background_onload();
+            let loaded = inner.background.as_mut().is_some_and(|(img, hook)| {
+                img.set_onload(Some(onload.as_ref().unchecked_ref()));
+                *hook = onload;
+                img.complete()
+            });
+            if loaded {
+                inner.redraw();
+            }
+        }
+        pad
+    }
+
     /// Handle pointer down: start drawing.
     pub fn pointer_down(&mut self, x: f64, y: f64) {
+        self.pointer_down_with_pressure(x, y, DEFAULT_PRESSURE);
+    }
+
+    /// Same as `pointer_down`, recording the pointer pressure. Starts an
+    /// erase gesture instead in eraser mode.
+    pub fn pointer_down_with_pressure(&mut self, x: f64, y: f64, pressure: f32) {
+        if self.eraser {
+            self.eraser_down(x, y);
+            return;
+        }
         self.drawing = true;
         self.last_x = x;
         self.last_y = y;
+        let first = self.strokes.is_empty();
+        self.strokes.push(Stroke {
+            points: vec![sample(x, y, pressure)],
+            pen:    Some(self.pen.clone())
+        });
+        if first
+            && self
+                .decorations
+                .as_ref()
+                .is_some_and(|d| d.placeholder.is_some())
+        {
+            // The placeholder goes away with the first stroke
+            self.redraw();
+        }
     }
 
     /// Handle pointer move: draw if active.
     pub fn pointer_move(&mut self, x: f64, y: f64) {
+        self.pointer_move_with_pressure(x, y, DEFAULT_PRESSURE);
+    }
+
+    /// Same as `pointer_move`, recording the pointer pressure.
+    pub fn pointer_move_with_pressure(&mut self, x: f64, y: f64, pressure: f32) {
+        if self.erasing {
+            self.erase_segment((self.last_x, self.last_y), (x, y));
+            self.last_x = x;
+            self.last_y = y;
+            return;
+        }
         if !self.drawing {
             return;
         }
-        let _ = self.ctx.begin_path();
-        let _ = self.ctx.move_to(self.last_x, self.last_y);
-        let _ = self.ctx.line_to(x, y);
-        let _ = self.ctx.stroke();
+        let Some(stroke) = self.strokes.last_mut() else {
+            return;
+        };
+        let prev = stroke.points.last().map_or(pressure, |p| p.pressure);
+        stroke.points.push(sample(x, y, pressure));
+        let pen = stroke.pen.clone().unwrap_or_default();
+        if pen.opacity() < 1.0 {
+            // Whole stroke again as one path, see `PenStyle::opacity`
+            self.last_x = x;
+            self.last_y = y;
+            self.redraw();
+            return;
+        }
+        apply_pen(&self.ctx, &pen, pen.width((prev + pressure) / 2.0));
+        self.ctx.begin_path();
+        self.ctx.move_to(self.last_x, self.last_y);
+        self.ctx.line_to(x, y);
+        self.ctx.stroke();
         self.last_x = x;
         self.last_y = y;
         self.is_empty = false;
     }
 
-    /// Handle pointer up/cancel: stop drawing.
+    /// Handle pointer up/cancel: stop drawing or erasing.
     pub fn pointer_up(&mut self) {
         self.drawing = false;
+        self.erasing = false;
+    }
+
+    /// Start an erase gesture regardless of the mode (e.g. for the stylus
+    /// eraser button, `buttons & 32`).
+    pub fn eraser_down(&mut self, x: f64, y: f64) {
+        if self.drawing {
+            return;
+        }
+        self.erasing = true;
+        self.erased_points = 0;
+        self.last_x = x;
+        self.last_y = y;
+        self.erase_segment((x, y), (x, y));
     }
 
-    /// Clear the canvas.
+    /// Eraser mode on/off.
+    pub fn set_eraser(&mut self, on: bool) {
+        self.eraser = on;
+    }
+
+    pub fn is_eraser(&self) -> bool {
+        self.eraser
+    }
+
+    /// Eraser radius, canvas px (the pen's half width is added on top).
+    pub fn set_eraser_radius(&mut self, radius: f64) {
+        self.eraser_radius = radius.max(0.0);
+    }
+
+    /// Is an erase gesture in progress?
+    pub fn is_erasing(&self) -> bool {
+        self.erasing
+    }
+
+    /// Points removed by the current (or last) erase gesture.
+    pub fn erased_points(&self) -> usize {
+        self.erased_points
+    }
+
+    /// Erase along `from`..`to`: points under the eraser are removed and
+    /// strokes are split where the eraser crosses them.
+    fn erase_segment(&mut self, from: (f64, f64), to: (f64, f64)) {
+        let Some((next, removed)) = erase_strokes(&self.strokes, from, to, self.eraser_radius)
+        else {
+            return;
+        };
+        // One undo step per gesture: snapshot before its first change
+        commit_erase(
+            &mut self.strokes,
+            &mut self.erase_history,
+            next,
+            self.erased_points == 0
+        );
+        self.erased_points += removed.max(1);
+        self.redraw();
+    }
+
+    /// Pen for the strokes drawn from now on.
+    pub fn pen_style(&self) -> &PenStyle {
+        &self.pen
+    }
+
+    /// Change the pen. Existing strokes keep the pen they were drawn with;
+    /// a stroke in progress switches at the next pen-down.
+    pub fn set_pen_style(&mut self, pen: PenStyle) {
+        self.pen = pen;
+    }
+
+    /// Is a stroke in progress?
+    pub fn is_drawing(&self) -> bool {
+        self.drawing
+    }
+
+    /// Revert the last erase gesture, else remove the last stroke, and
+    /// repaint. Returns false if nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        if self.drawing || self.erasing {
+            return false;
+        }
+        if !undo_step(&mut self.strokes, &mut self.erase_history) {
+            return false;
+        }
+        self.redraw();
+        true
+    }
+
+    /// Replace the strokes (e.g. with a restored draft) and repaint.
+    /// Ignored while a stroke is in progress.
+    pub fn load_strokes(&mut self, strokes: Vec<Stroke>) {
+        if self.drawing || self.erasing {
+            return;
+        }
+        self.strokes = strokes;
+        self.erase_history.clear();
+        self.redraw();
+    }
+
+    /// Guides to paint under the strokes; `None` removes them.
+    pub fn set_decorations(&mut self, decorations: Option<Decorations>) {
+        self.background = None;
+        if let Some(Background::Image(url)) =
+            decorations.as_ref().and_then(|d| d.background.as_ref())
+            && let Ok(img) = HtmlImageElement::new()
+        {
+            let onload = self.background_onload();
+            img.set_onload(Some(onload.as_ref().unchecked_ref()));
+            img.set_src(url);
+            self.background = Some((img, onload));
+        }
+        self.decorations = decorations;
+        self.redraw();
+    }
+
+    /// `onload` of the background image: repaint the shared pad (the
+    /// handler runs between events, so the pad isn't borrowed).
+    fn background_onload(&self) -> Closure<dyn FnMut()> {
+        let this = self.this.clone();
+        Closure::<dyn FnMut()>::new(move || {
+            if let Some(pad) = this.upgrade()
+                && let Ok(mut pad) = pad.try_borrow_mut()
+            {
+                pad.redraw();
+            }
+        })
+    }
+
+    pub fn decorations(&self) -> Option<&Decorations> {
+        self.decorations.as_ref()
+    }
+
+    /// Resize the canvas (canvas px, i.e. the export size) and repaint.
+    /// Strokes keep their coordinates.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.canvas
+            .set_width((width as f64 * self.pixel_ratio).round() as u32);
+        self.canvas
+            .set_height((height as f64 * self.pixel_ratio).round() as u32);
+        // Re-clamps the pan to the new size and repaints
+        self.set_view(self.view);
+    }
+
+    /// Size strokes are recorded and exported in, canvas px.
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Backing-store resolution: `ratio` device pixels per canvas px (e.g.
+    /// display scale times `devicePixelRatio` when the canvas is shown
+    /// enlarged). Only sharpness changes; strokes and exports stay in
+    /// canvas px.
+    pub fn set_pixel_ratio(&mut self, ratio: f64) {
+        let ratio = if ratio.is_finite() {
+            ratio.clamp(0.25, 8.0)
+        } else {
+            1.0
+        };
+        if ratio != self.pixel_ratio {
+            self.pixel_ratio = ratio;
+            self.apply_backing_size();
+        }
+    }
+
+    pub fn pixel_ratio(&self) -> f64 {
+        self.pixel_ratio
+    }
+
+    /// Map a pointer's client coordinates to stroke coordinates (canvas
+    /// px), whatever size the canvas is displayed at and however the view
+    /// is zoomed.
+    pub fn client_to_canvas(&self, client_x: f64, client_y: f64) -> (f64, f64) {
+        let (x, y) = self.client_to_view(client_x, client_y);
+        let v = self.view;
+        ((x - v.pan_x) / v.zoom, (y - v.pan_y) / v.zoom)
+    }
+
+    /// Client coordinates to canvas px as displayed (before the view
+    /// transform).
+    fn client_to_view(&self, client_x: f64, client_y: f64) -> (f64, f64) {
+        let rect = self.canvas.get_bounding_client_rect();
+        let sx = if rect.width() > 0.0 {
+            self.width as f64 / rect.width()
+        } else {
+            1.0
+        };
+        let sy = if rect.height() > 0.0 {
+            self.height as f64 / rect.height()
+        } else {
+            1.0
+        };
+        ((client_x - rect.x()) * sx, (client_y - rect.y()) * sy)
+    }
+
+    pub fn view(&self) -> ViewTransform {
+        self.view
+    }
+
+    /// Set zoom and pan (clamped so the canvas stays covered) and repaint.
+    pub fn set_view(&mut self, view: ViewTransform) {
+        let zoom = if view.zoom.is_finite() {
+            view.zoom.clamp(1.0, MAX_ZOOM)
+        } else {
+            1.0
+        };
+        let (w, h) = (self.width as f64, self.height as f64);
+        let pan = |p: f64, size: f64| {
+            if p.is_finite() {
+                p.clamp(size * (1.0 - zoom), 0.0)
+            } else {
+                0.0
+            }
+        };
+        self.view = ViewTransform {
+            zoom,
+            pan_x: pan(view.pan_x, w),
+            pan_y: pan(view.pan_y, h)
+        };
+        self.redraw();
+    }
+
+    /// Back to the unzoomed view.
+    pub fn reset_view(&mut self) {
+        self.set_view(ViewTransform::default());
+    }
+
+    /// Zoom by `factor` keeping the point at (`x`, `y`) view px in place.
+    pub fn zoom_at(&mut self, factor: f64, x: f64, y: f64) {
+        let v = self.view;
+        let zoom = (v.zoom * factor).clamp(1.0, MAX_ZOOM);
+        let (dx, dy) = ((x - v.pan_x) / v.zoom, (y - v.pan_y) / v.zoom);
+        self.set_view(ViewTransform {
+            zoom,
+            pan_x: x - dx * zoom,
+            pan_y: y - dy * zoom
+        });
+    }
+
+    /// Shift the view by (`dx`, `dy`) view px.
+    pub fn pan_by(&mut self, dx: f64, dy: f64) {
+        let v = self.view;
+        self.set_view(ViewTransform {
+            pan_x: v.pan_x + dx,
+            pan_y: v.pan_y + dy,
+            ..v
+        });
+    }
+
+    /// Register a touch point. Returns whether it may draw: a second finger
+    /// instead starts a pinch/pan gesture and takes back the stroke the
+    /// first one began.
+    pub fn touch_down(&mut self, id: i32, client_x: f64, client_y: f64) -> bool {
+        let (x, y) = self.client_to_view(client_x, client_y);
+        self.touches.retain(|t| t.0 != id);
+        self.touches.push((id, x, y));
+        if self.touches.len() < 2 && !self.gesture {
+            return true;
+        }
+        if !self.gesture {
+            self.gesture = true;
+            if self.drawing {
+                self.drawing = false;
+                self.strokes.pop();
+                self.redraw();
+            }
+            self.erasing = false;
+        }
+        false
+    }
+
+    /// Move a touch point. Returns `true` if the move belongs to a gesture
+    /// (pinch zooms, the midpoint pans) and must not draw.
+    pub fn touch_move(&mut self, id: i32, client_x: f64, client_y: f64) -> bool {
+        if !self.gesture {
+            return false;
+        }
+        let (x, y) = self.client_to_view(client_x, client_y);
+        let Some(i) = self.touches.iter().position(|t| t.0 == id) else {
+            return true;
+        };
+        let before = self.touches.clone();
+        self.touches[i] = (id, x, y);
+        if let ([a, b, ..], [a2, b2, ..]) = (before.as_slice(), self.touches.as_slice()) {
+            let dist = |p: &(i32, f64, f64), q: &(i32, f64, f64)| (p.1 - q.1).hypot(p.2 - q.2);
+            let mid =
+                |p: &(i32, f64, f64), q: &(i32, f64, f64)| ((p.1 + q.1) / 2.0, (p.2 + q.2) / 2.0);
+            let (c, c2) = (mid(a, b), mid(a2, b2));
+            let (d, d2) = (dist(a, b), dist(a2, b2));
+            // Point under the old midpoint follows the new one
+            let v = self.view;
+            let zoom = if d > 1.0 {
+                (v.zoom * d2 / d).clamp(1.0, MAX_ZOOM)
+            } else {
+                v.zoom
+            };
+            let (px, py) = ((c.0 - v.pan_x) / v.zoom, (c.1 - v.pan_y) / v.zoom);
+            self.set_view(ViewTransform {
+                zoom,
+                pan_x: c2.0 - px * zoom,
+                pan_y: c2.1 - py * zoom
+            });
+        }
+        true
+    }
+
+    /// Lift a touch point; the gesture ends with the last one.
+    pub fn touch_up(&mut self, id: i32) {
+        self.touches.retain(|t| t.0 != id);
+        if self.touches.is_empty() {
+            self.gesture = false;
+        }
+    }
+
+    /// Is a pinch/pan gesture in progress?
+    pub fn is_gesture(&self) -> bool {
+        self.gesture
+    }
+
+    fn apply_backing_size(&mut self) {
+        let r = self.pixel_ratio;
+        self.canvas
+            .set_width((self.width as f64 * r).round() as u32);
+        self.canvas
+            .set_height((self.height as f64 * r).round() as u32);
+        self.redraw();
+    }
+
+    /// Repaint the canvas: decorations, then the stroke model, through the
+    /// view transform.
+    fn redraw(&mut self) {
+        let _ = self.ctx.set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+        let (w, h) = (self.canvas.width(), self.canvas.height());
+        self.ctx.clear_rect(0.0, 0.0, w as f64, h as f64);
+        let (r, v) = (self.pixel_ratio, self.view);
+        let _ = self
+            .ctx
+            .set_transform(r * v.zoom, 0.0, 0.0, r * v.zoom, r * v.pan_x, r * v.pan_y);
+        self.draw_decorations();
+        for stroke in &self.strokes {
+            draw_stroke(&self.ctx, stroke);
+        }
+        self.ctx.set_global_alpha(1.0);
+        self.is_empty = self.strokes.iter().all(|s| s.points.len() < 2);
+    }
+
+    fn draw_decorations(&self) {
+        let Some(deco) = &self.decorations else {
+            return;
+        };
+        let ctx = &self.ctx;
+        let (w, h) = (self.width as f64, self.height as f64);
+        ctx.save();
+        ctx.set_stroke_style_str(&deco.color);
+        ctx.set_fill_style_str(&deco.color);
+        match &deco.background {
+            Some(Background::Image(_)) => {
+                if let Some((img, _)) = self.background.as_ref().filter(|(img, _)| img.complete())
+                {
+                    let _ =
+                        ctx.draw_image_with_html_image_element_and_dw_and_dh(img, 0.0, 0.0, w, h);
+                }
+            }
+            Some(Background::Grid {
+                spacing
+            }) if *spacing >= 2.0 => {
+                ctx.set_global_alpha(0.35);
+                ctx.set_line_width(1.0);
+                ctx.begin_path();
+                let mut x = *spacing;
+                while x < w {
+                    ctx.move_to(x.floor() + 0.5, 0.0);
+                    ctx.line_to(x.floor() + 0.5, h);
+                    x += spacing;
+                }
+                let mut y = *spacing;
+                while y < h {
+                    ctx.move_to(0.0, y.floor() + 0.5);
+                    ctx.line_to(w, y.floor() + 0.5);
+                    y += spacing;
+                }
+                ctx.stroke();
+                ctx.set_global_alpha(1.0);
+            }
+            _ => {}
+        }
+        let line_y = deco
+            .baseline
+            .map(|b| (b.y.clamp(0.0, 1.0) * h).floor() + 0.5);
+        if let (Some(baseline), Some(y)) = (deco.baseline, line_y) {
+            let (x0, x1) = (w * BASELINE_INSET, w * (1.0 - BASELINE_INSET));
+            ctx.set_line_width(1.0);
+            ctx.begin_path();
+            ctx.move_to(x0, y);
+            ctx.line_to(x1, y);
+            ctx.stroke();
+            if baseline.mark {
+                ctx.set_font(&format!(
+                    "{}px sans-serif",
+                    (h * 0.1).clamp(12.0, 28.0).round()
+                ));
+                ctx.set_text_align("left");
+                ctx.set_text_baseline("bottom");
+                let _ = ctx.fill_text("✕", x0, y - 4.0);
+            }
+        }
+        if let Some(text) = deco
+            .placeholder
+            .as_deref()
+            .filter(|_| self.strokes.is_empty())
+        {
+            ctx.set_global_alpha(0.6);
+            ctx.set_font(&format!(
+                "{}px sans-serif",
+                (h * 0.09).clamp(12.0, 24.0).round()
+            ));
+            ctx.set_text_align("center");
+            ctx.set_text_baseline("middle");
+            // Above the baseline, else in the middle
+            let y = line_y.map_or(h / 2.0, |y| (y - h * 0.2).max(h * 0.15));
+            let _ = ctx.fill_text(text, w / 2.0, y);
+        }
+        ctx.restore();
+    }
+
+    /// Clear the strokes (decorations stay).
     pub fn clear(&mut self) {
-        let w = self.canvas.width();
-        let h = self.canvas.height();
-        let _ = self.ctx.clear_rect(0.0, 0.0, w as f64, h as f64);
-        self.is_empty = true;
+        self.strokes.clear();
+        self.erase_history.clear();
+        self.redraw();
     }
 
     /// Is pad empty (nothing drawn)?
@@ -78,13 +834,43 @@ impl SignaturePad {
         self.is_empty
     }
 
-    /// Export as PNG data URL (for <img src="...">).
+    /// Count a rejected synthetic pointer event. Kept across `clear`.
+    pub fn note_untrusted_event(&mut self) {
+        self.untrusted_events = self.untrusted_events.saturating_add(1);
+    }
+
+    /// Number of rejected synthetic pointer events.
+    pub fn untrusted_events(&self) -> u32 {
+        self.untrusted_events
+    }
+
+    /// Recorded strokes, in drawing order.
+    pub fn strokes(&self) -> &[Stroke] {
+        &self.strokes
+    }
+
+    /// Export the strokes as a standalone SVG document, canvas px.
+    pub fn to_svg(&self) -> String {
+        strokes_to_svg(&self.strokes, self.width, self.height)
+    }
+
+    /// Export as PNG data URL (for <img src="...">). Rendered from the
+    /// strokes alone, without decorations.
     pub fn to_png_data_url(&self) -> Result<String, SigError> {
-        self.canvas
+        self.render_strokes()?
             .to_data_url()
             .map_err(|_| SigError::OpFailed("to_data_url".into()))
     }
 
+    /// Offscreen canvas of the pad's size with just the strokes on it.
+    fn render_strokes(&self) -> Result<HtmlCanvasElement, SigError> {
+        let (canvas, ctx) = offscreen_canvas(self.width, self.height)?;
+        for stroke in &self.strokes {
+            draw_stroke(&ctx, stroke);
+        }
+        Ok(canvas)
+    }
+
     /// Export raw PNG bytes (without data URL).
     pub fn to_png_bytes(&self) -> Result<Vec<u8>, SigError> {
         // Using to_blob would be nicer, but it is async-callback based.
@@ -98,12 +884,434 @@ impl SignaturePad {
     }
 }
 
-/// Minimal base64 decoder using JS at runtime to avoid extra deps.
-fn base64_decode(b64: &str) -> Result<Vec<u8>, SigError> {
-    let js = format!("Uint8Array.from(atob('{b64}'), c => c.charCodeAt(0))");
-    let val = js_sys::eval(&js).map_err(|_| SigError::OpFailed("eval".into()))?;
-    let arr = Uint8Array::new(&val);
-    let mut out = vec![0u8; arr.length() as usize];
-    arr.copy_to(&mut out[..]);
+/// Set colour, width, opacity, cap and join of `ctx` for `pen`.
+fn apply_pen(ctx: &CanvasRenderingContext2d, pen: &PenStyle, width: f64) {
+    ctx.set_stroke_style_str(&pen.color);
+    ctx.set_global_alpha(pen.opacity());
+    ctx.set_line_width(width);
+    ctx.set_line_cap(pen.cap.as_str());
+    ctx.set_line_join(pen.join.as_str());
+}
+
+/// Detached `<canvas>` of `width` x `height` px and its 2D context.
+pub(crate) fn offscreen_canvas(
+    width: u32,
+    height: u32
+) -> Result<(HtmlCanvasElement, CanvasRenderingContext2d), SigError> {
+    let canvas = web_sys::window()
+        .and_then(|w| w.document())
+        .ok_or(SigError::DomUnavailable)?
+        .create_element("canvas")
+        .map_err(|_| SigError::OpFailed("createElement(canvas)".into()))?
+        .dyn_into::<HtmlCanvasElement>()
+        .map_err(|_| SigError::OpFailed("createElement(canvas)".into()))?;
+    canvas.set_width(width);
+    canvas.set_height(height);
+    let ctx = canvas
+        .get_context("2d")
+        .map_err(|_| SigError::NoContext2d)?
+        .ok_or(SigError::NoContext2d)?
+        .dyn_into::<CanvasRenderingContext2d>()
+        .map_err(|_| SigError::NoContext2d)?;
+    Ok((canvas, ctx))
+}
+
+/// Paint one stroke: segment by segment for a pressure-dependent opaque
+/// pen, else as a single path.
+pub(crate) fn draw_stroke(ctx: &CanvasRenderingContext2d, stroke: &Stroke) {
+    let Some((first, rest)) = stroke.points.split_first() else {
+        return;
+    };
+    let pen = stroke.pen.clone().unwrap_or_default();
+    if pen.is_variable() && pen.opacity() >= 1.0 {
+        let mut prev = first;
+        for p in rest {
+            apply_pen(ctx, &pen, pen.width((prev.pressure + p.pressure) / 2.0));
+            ctx.begin_path();
+            ctx.move_to(prev.x, prev.y);
+            ctx.line_to(p.x, p.y);
+            ctx.stroke();
+            prev = p;
+        }
+        return;
+    }
+    let mean = stroke.points.iter().map(|p| p.pressure).sum::<f32>() / stroke.points.len() as f32;
+    apply_pen(ctx, &pen, pen.width(mean));
+    ctx.begin_path();
+    ctx.move_to(first.x, first.y);
+    for p in rest {
+        ctx.line_to(p.x, p.y);
+    }
+    ctx.stroke();
+}
+
+/// Strokes before each erase gesture, with the stroke count right after it
+/// (see `undo_step`).
+type EraseHistory = Vec<(usize, Vec<Stroke>)>;
+
+/// `strokes` after erasing along `from`..`to` with `radius` (plus each
+/// stroke's half pen width), and the number of points removed; `None` if
+/// the eraser touched nothing.
+fn erase_strokes(
+    strokes: &[Stroke],
+    from: (f64, f64),
+    to: (f64, f64),
+    radius: f64
+) -> Option<(Vec<Stroke>, usize)> {
+    let mut changed = false;
+    let mut removed = 0;
+    let mut next = Vec::with_capacity(strokes.len());
+    for stroke in strokes {
+        let reach = radius + stroke.pen.as_ref().map_or(1.0, |pen| pen.max_width / 2.0);
+        match split_stroke(stroke, from, to, reach) {
+            Some((parts, n)) => {
+                changed = true;
+                removed += n;
+                next.extend(parts);
+            }
+            None => next.push(stroke.clone())
+        }
+    }
+    changed.then_some((next, removed))
+}
+
+/// Replace `strokes` with `next`, the result of an erase step. The first
+/// step of a gesture snapshots the strokes for `undo_step`.
+fn commit_erase(
+    strokes: &mut Vec<Stroke>,
+    history: &mut EraseHistory,
+    next: Vec<Stroke>,
+    first_step: bool
+) {
+    if first_step {
+        history.push((0, std::mem::replace(strokes, next)));
+    } else {
+        *strokes = next;
+    }
+    if let Some(last) = history.last_mut() {
+        last.0 = strokes.len();
+    }
+}
+
+/// Revert the last erase gesture if nothing was drawn since (the recorded
+/// stroke count tells), else remove the last stroke. Returns false if there
+/// is nothing to undo.
+fn undo_step(strokes: &mut Vec<Stroke>, history: &mut EraseHistory) -> bool {
+    if history
+        .last()
+        .is_some_and(|(count, _)| *count == strokes.len())
+    {
+        if let Some((_, before)) = history.pop() {
+            *strokes = before;
+        }
+        true
+    } else {
+        strokes.pop().is_some()
+    }
+}
+
+/// `stroke` with everything within `reach` of the eraser path `from`..`to`
+/// cut out, and the number of points removed; `None` if untouched. Pieces
+/// left with a single point are dropped (they would render as dots).
+fn split_stroke(
+    stroke: &Stroke,
+    from: (f64, f64),
+    to: (f64, f64),
+    reach: f64
+) -> Option<(Vec<Stroke>, usize)> {
+    let pts = &stroke.points;
+    let tap = pts.len() == 1;
+    let mut parts = Vec::new();
+    let mut run: Vec<StrokePoint> = Vec::new();
+    let mut removed = 0;
+    let mut cut = false;
+    let mut end_run = |run: &mut Vec<StrokePoint>, removed: &mut usize| {
+        if run.len() > 1 || (tap && run.len() == 1) {
+            parts.push(Stroke {
+                points: std::mem::take(run),
+                pen:    stroke.pen.clone()
+            });
+        } else {
+            *removed += run.len();
+            run.clear();
+        }
+    };
+    for (i, p) in pts.iter().enumerate() {
+        if point_segment_distance((p.x, p.y), from, to) <= reach {
+            removed += 1;
+            cut = true;
+            end_run(&mut run, &mut removed);
+            continue;
+        }
+        run.push(p.clone());
+        if let Some(q) = pts.get(i + 1)
+            && segment_distance((p.x, p.y), (q.x, q.y), from, to) <= reach
+        {
+            cut = true;
+            end_run(&mut run, &mut removed);
+        }
+    }
+    if !cut {
+        return None;
+    }
+    end_run(&mut run, &mut removed);
+    Some((parts, removed))
+}
+
+fn point_segment_distance(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let len2 = dx * dx + dy * dy;
+    let t = if len2 > 0.0 {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / len2).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    ((p.0 - a.0 - t * dx).powi(2) + (p.1 - a.1 - t * dy).powi(2)).sqrt()
+}
+
+/// Shortest distance between segments `a`..`b` and `c`..`d`.
+fn segment_distance(a: (f64, f64), b: (f64, f64), c: (f64, f64), d: (f64, f64)) -> f64 {
+    let cross = |o: (f64, f64), p: (f64, f64), q: (f64, f64)| {
+        (p.0 - o.0) * (q.1 - o.1) - (p.1 - o.1) * (q.0 - o.0)
+    };
+    let (d1, d2) = (cross(a, b, c), cross(a, b, d));
+    let (d3, d4) = (cross(c, d, a), cross(c, d, b));
+    if d1 * d2 < 0.0 && d3 * d4 < 0.0 {
+        return 0.0;
+    }
+    point_segment_distance(a, c, d)
+        .min(point_segment_distance(b, c, d))
+        .min(point_segment_distance(c, a, b))
+        .min(point_segment_distance(d, a, b))
+}
+
+/// Standalone SVG document of `strokes` on a `width` x `height` canvas.
+pub(crate) fn strokes_to_svg(strokes: &[Stroke], width: u32, height: u32) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"0 0 {width} {height}\">\n{}</svg>\n",
+        svg_strokes(strokes)
+    )
+}
+
+/// `<path>` elements for `strokes`, in canvas px; same rules as the canvas
+/// (per-segment widths for opaque pressure-dependent pens).
+pub(crate) fn svg_strokes(strokes: &[Stroke]) -> String {
+    let mut out = String::new();
+    for stroke in strokes {
+        let Some((first, rest)) = stroke.points.split_first() else {
+            continue;
+        };
+        let pen = stroke.pen.clone().unwrap_or_default();
+        let paint = format!(
+            "fill=\"none\" stroke=\"{}\" stroke-opacity=\"{}\" stroke-linecap=\"{}\" \
+             stroke-linejoin=\"{}\"",
+            svg_escape(&pen.color),
+            num(pen.opacity()),
+            pen.cap.as_str(),
+            pen.join.as_str()
+        );
+        let xy = |p: &StrokePoint| format!("{} {}", num(p.x), num(p.y));
+        if pen.is_variable() && pen.opacity() >= 1.0 && !rest.is_empty() {
+            let _ = writeln!(out, "<g {paint}>");
+            let mut prev = first;
+            for p in rest {
+                let width = pen.width((prev.pressure + p.pressure) / 2.0);
+                let _ = writeln!(
+                    out,
+                    "<path d=\"M{} L{}\" stroke-width=\"{}\"/>",
+                    xy(prev),
+                    xy(p),
+                    num(width)
+                );
+                prev = p;
+            }
+            out.push_str("</g>\n");
+            continue;
+        }
+        let mean =
+            stroke.points.iter().map(|p| p.pressure).sum::<f32>() / stroke.points.len() as f32;
+        let mut d = format!("M{}", xy(first));
+        if rest.is_empty() {
+            // Tap: zero-length segment, the round cap draws a dot
+            let _ = write!(d, " L{}", xy(first));
+        }
+        for p in rest {
+            let _ = write!(d, " L{}", xy(p));
+        }
+        let _ = writeln!(
+            out,
+            "<path d=\"{d}\" {paint} stroke-width=\"{}\"/>",
+            num(pen.width(mean))
+        );
+    }
+    out
+}
+
+pub(crate) fn svg_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('"', "&quot;")
+}
+
+fn sample(x: f64, y: f64, pressure: f32) -> StrokePoint {
+    StrokePoint {
+        x,
+        y,
+        t: js_sys::Date::now(),
+        pressure
+    }
+}
+
+/// Standard (RFC 4648) base64 decoder; padding is optional and ASCII
+/// whitespace is skipped, as with `atob`. Pure Rust: data URLs come from
+/// stored and received envelopes, so they never reach JS as code.
+pub(crate) fn base64_decode(b64: &str) -> Result<Vec<u8>, SigError> {
+    let err = || SigError::OpFailed("base64: invalid input".into());
+    let data = b64.trim_end_matches(|c: char| c == '=' || c.is_ascii_whitespace());
+    let mut out = Vec::with_capacity(data.len() / 4 * 3 + 2);
+    let (mut acc, mut bits) = (0u32, 0u32);
+    for c in data.bytes() {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            c if c.is_ascii_whitespace() => continue,
+            _ => return Err(err())
+        };
+        acc = (acc << 6) | v as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    // A lone trailing character can't complete a byte
+    if bits >= 6 {
+        return Err(err());
+    }
     Ok(out)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pen_colour_normalizes_to_hex() {
+        let pen = |color: &str| PenStyle::solid(color, 2.0);
+        assert_eq!(pen("#1A3D9C").hex(), "#1a3d9c");
+        assert_eq!(pen("#abc").hex(), "#aabbcc");
+        assert_eq!(pen(" #00ff00 ").rgb(), (0, 255, 0));
+        assert_eq!(pen("blue").hex(), "#000000");
+        assert_eq!(pen("#12345").hex(), "#000000");
+    }
+
+    /// Horizontal stroke at `y` from `x0`, one point every 10 px.
+    fn line(x0: f64, y: f64, points: usize) -> Stroke {
+        Stroke {
+            points: (0..points)
+                .map(|i| StrokePoint {
+                    x: x0 + i as f64 * 10.0,
+                    y,
+                    t: i as f64 * 8.0,
+                    pressure: DEFAULT_PRESSURE
+                })
+                .collect(),
+            pen:    None
+        }
+    }
+
+    fn xs(stroke: &Stroke) -> Vec<f64> {
+        stroke.points.iter().map(|p| p.x).collect()
+    }
+
+    #[test]
+    fn eraser_across_the_middle_splits_the_stroke() {
+        let stroke = line(0.0, 50.0, 11);
+        let (parts, removed) = split_stroke(&stroke, (50.0, 0.0), (50.0, 100.0), 3.0).unwrap();
+        assert_eq!(removed, 1);
+        assert_eq!(parts.len(), 2);
+        assert_eq!(xs(&parts[0]), [0.0, 10.0, 20.0, 30.0, 40.0]);
+        assert_eq!(xs(&parts[1]), [60.0, 70.0, 80.0, 90.0, 100.0]);
+    }
+
+    #[test]
+    fn eraser_between_samples_cuts_the_segment() {
+        let stroke = line(0.0, 50.0, 11);
+        // Crosses between x = 40 and x = 50 without covering either point
+        let (parts, removed) = split_stroke(&stroke, (45.0, 0.0), (45.0, 100.0), 2.0).unwrap();
+        assert_eq!(removed, 0);
+        assert_eq!(xs(&parts[0]).last(), Some(&40.0));
+        assert_eq!(xs(&parts[1]).first(), Some(&50.0));
+    }
+
+    #[test]
+    fn eraser_over_the_whole_stroke_removes_it() {
+        let stroke = line(0.0, 50.0, 5);
+        let (parts, removed) = split_stroke(&stroke, (-5.0, 50.0), (45.0, 50.0), 3.0).unwrap();
+        assert!(parts.is_empty());
+        assert_eq!(removed, 5);
+        // Leftover single points are dropped too
+        let (parts, removed) = split_stroke(&stroke, (10.0, 50.0), (40.0, 50.0), 3.0).unwrap();
+        assert!(parts.is_empty());
+        assert_eq!(removed, 5);
+    }
+
+    #[test]
+    fn eraser_elsewhere_leaves_the_stroke_alone() {
+        let strokes = [line(0.0, 50.0, 11)];
+        assert!(split_stroke(&strokes[0], (0.0, 80.0), (100.0, 80.0), 3.0).is_none());
+        assert!(erase_strokes(&strokes, (0.0, 80.0), (100.0, 80.0), 2.0).is_none());
+    }
+
+    #[test]
+    fn undo_after_erase_then_draw() {
+        let a = line(0.0, 50.0, 11);
+        let b = line(0.0, 120.0, 4);
+        let mut strokes = vec![a.clone()];
+        let mut history = EraseHistory::new();
+
+        // One gesture in two steps: split `a`, then trim its right half
+        let (next, _) = erase_strokes(&strokes, (50.0, 0.0), (50.0, 100.0), 2.0).unwrap();
+        commit_erase(&mut strokes, &mut history, next, true);
+        let (next, _) = erase_strokes(&strokes, (90.0, 0.0), (90.0, 100.0), 2.0).unwrap();
+        commit_erase(&mut strokes, &mut history, next, false);
+        assert_eq!(strokes.len(), 2);
+        assert_eq!(history.len(), 1);
+        let erased = strokes.clone();
+
+        // Drawn after the erase: undone first, then the whole gesture
+        strokes.push(b);
+        assert!(undo_step(&mut strokes, &mut history));
+        assert_eq!(strokes, erased);
+        assert!(undo_step(&mut strokes, &mut history));
+        assert_eq!(strokes, [a]);
+        assert!(history.is_empty());
+        assert!(undo_step(&mut strokes, &mut history));
+        assert!(strokes.is_empty());
+        assert!(!undo_step(&mut strokes, &mut history));
+    }
+
+    #[test]
+    fn base64_decodes_padded_and_unpadded_input() {
+        assert_eq!(base64_decode("aGVsbG8=").unwrap(), b"hello");
+        assert_eq!(base64_decode("aGVsbG8").unwrap(), b"hello");
+        assert_eq!(base64_decode("aGVs\nbG8h").unwrap(), b"hello!");
+        assert_eq!(base64_decode("+/8=").unwrap(), [0xfb, 0xff]);
+        assert_eq!(base64_decode("").unwrap(), b"");
+        assert!(base64_decode("aGVsb").is_err());
+    }
+
+    #[test]
+    fn base64_rejects_anything_outside_the_alphabet() {
+        let data_url = "data:image/png;base64,iVBOR');alert(1);('";
+        let b64 = data_url.strip_prefix("data:image/png;base64,").unwrap();
+        assert!(base64_decode(b64).is_err());
+        assert!(base64_decode("aGVs=bG8=").is_err());
+        assert!(base64_decode("aGVs-bG8").is_err());
+    }
+}
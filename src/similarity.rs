@@ -0,0 +1,294 @@
+// src/similarity.rs
+
+use serde::{Deserialize, Serialize};
+
+use crate::signature_core::Stroke;
+
+/// Longer inputs are uniformly downsampled to keep DTW at O(n²) with a small n.
+const MAX_SAMPLES: usize = 256;
+
+/// Mean per-step DTW distance at which a feature's similarity drops to 1/e.
+const POSITION_SCALE: f64 = 0.08;
+const VELOCITY_SCALE: f64 = 0.6;
+const PRESSURE_SCALE: f64 = 0.15;
+
+/// Relative weight of each feature in the overall score.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SimilarityWeights {
+    pub position: f64,
+    pub velocity: f64,
+    pub pressure: f64
+}
+
+impl Default for SimilarityWeights {
+    fn default() -> Self {
+        Self {
+            position: 0.5,
+            velocity: 0.3,
+            pressure: 0.2
+        }
+    }
+}
+
+/// Result of comparing a candidate signature against a reference.
+/// All scores are in `0.0..=1.0`, 1.0 meaning identical.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SimilarityReport {
+    /// Weighted combination of the per-feature scores.
+    pub score:    f64,
+    /// Shape, after translating to the centroid and scaling to unit size.
+    pub position: f64,
+    /// Velocity profile, normalized by mean speed.
+    pub velocity: f64,
+    /// Pressure profile; `None` if either side has no pressure data (mouse).
+    pub pressure: Option<f64>
+}
+
+impl SimilarityReport {
+    /// Convenience: `score >= threshold`.
+    pub fn is_match(&self, threshold: f64) -> bool {
+        self.score >= threshold
+    }
+}
+
+/// Compare two stroke sets with dynamic time warping using default weights.
+pub fn compare_strokes(reference: &[Stroke], candidate: &[Stroke]) -> SimilarityReport {
+    compare_strokes_with(reference, candidate, SimilarityWeights::default())
+}
+
+/// Compare two stroke sets with dynamic time warping over normalized
+/// position, velocity and pressure.
+pub fn compare_strokes_with(
+    reference: &[Stroke],
+    candidate: &[Stroke],
+    weights: SimilarityWeights
+) -> SimilarityReport {
+    let a = Features::extract(reference);
+    let b = Features::extract(candidate);
+    if a.position.is_empty() || b.position.is_empty() {
+        return SimilarityReport {
+            score:    0.0,
+            position: 0.0,
+            velocity: 0.0,
+            pressure: None
+        };
+    }
+
+    let position = similarity(dtw(&a.position, &b.position), POSITION_SCALE);
+    let velocity = similarity(dtw(&a.velocity, &b.velocity), VELOCITY_SCALE);
+    let pressure = (a.has_pressure && b.has_pressure)
+        .then(|| similarity(dtw(&a.pressure, &b.pressure), PRESSURE_SCALE));
+
+    let mut total = weights.position * position + weights.velocity * velocity;
+    let mut weight_sum = weights.position + weights.velocity;
+    if let Some(p) = pressure {
+        total += weights.pressure * p;
+        weight_sum += weights.pressure;
+    }
+    let score = if weight_sum > 0.0 {
+        total / weight_sum
+    } else {
+        0.0
+    };
+
+    SimilarityReport {
+        score,
+        position,
+        velocity,
+        pressure
+    }
+}
+
+/// Per-sample feature sequences of a signature.
+struct Features {
+    position:     Vec<[f64; 2]>,
+    velocity:     Vec<[f64; 2]>,
+    pressure:     Vec<[f64; 1]>,
+    has_pressure: bool
+}
+
+impl Features {
+    fn extract(strokes: &[Stroke]) -> Self {
+        let mut position = Vec::new();
+        let mut velocity = Vec::new();
+        let mut pressure = Vec::new();
+
+        for stroke in strokes {
+            for (i, p) in stroke.points.iter().enumerate() {
+                let v = match i.checked_sub(1).map(|j| &stroke.points[j]) {
+                    Some(prev) if p.t > prev.t => {
+                        let dt = p.t - prev.t;
+                        [(p.x - prev.x) / dt, (p.y - prev.y) / dt]
+                    }
+                    _ => [0.0, 0.0]
+                };
+                position.push([p.x, p.y]);
+                velocity.push(v);
+                pressure.push([p.pressure as f64]);
+            }
+        }
+
+        // Translate to centroid, scale to unit bounding box
+        if !position.is_empty() {
+            let n = position.len() as f64;
+            let cx = position.iter().map(|p| p[0]).sum::<f64>() / n;
+            let cy = position.iter().map(|p| p[1]).sum::<f64>() / n;
+            let (mut min_x, mut max_x, mut min_y, mut max_y) =
+                (f64::MAX, f64::MIN, f64::MAX, f64::MIN);
+            for p in &position {
+                min_x = min_x.min(p[0]);
+                max_x = max_x.max(p[0]);
+                min_y = min_y.min(p[1]);
+                max_y = max_y.max(p[1]);
+            }
+            let size = (max_x - min_x).max(max_y - min_y).max(f64::EPSILON);
+            for p in &mut position {
+                p[0] = (p[0] - cx) / size;
+                p[1] = (p[1] - cy) / size;
+            }
+
+            // Velocity in units of mean speed: independent of scale and tempo
+            let mean_speed = velocity.iter().map(|v| v[0].hypot(v[1])).sum::<f64>() / n;
+            if mean_speed > f64::EPSILON {
+                for v in &mut velocity {
+                    v[0] /= mean_speed;
+                    v[1] /= mean_speed;
+                }
+            }
+        }
+
+        let has_pressure = pressure
+            .first()
+            .is_some_and(|first| pressure.iter().any(|p| (p[0] - first[0]).abs() > 1e-3));
+
+        Self {
+            position: downsample(position),
+            velocity: downsample(velocity),
+            pressure: downsample(pressure),
+            has_pressure
+        }
+    }
+}
+
+fn downsample<T: Copy>(seq: Vec<T>) -> Vec<T> {
+    if seq.len() <= MAX_SAMPLES {
+        return seq;
+    }
+    let step = seq.len() as f64 / MAX_SAMPLES as f64;
+    (0..MAX_SAMPLES)
+        .map(|i| seq[(i as f64 * step) as usize])
+        .collect()
+}
+
+/// Dynamic time warping with Euclidean local cost. Returns the accumulated
+/// cost divided by `n + m`, so values are comparable across lengths.
+fn dtw<const D: usize>(a: &[[f64; D]], b: &[[f64; D]]) -> f64 {
+    let m = b.len();
+    let mut prev = vec![f64::INFINITY; m + 1];
+    let mut curr = vec![f64::INFINITY; m + 1];
+    prev[0] = 0.0;
+
+    for x in a {
+        curr[0] = f64::INFINITY;
+        for (j, y) in b.iter().enumerate() {
+            let cost = x
+                .iter()
+                .zip(y)
+                .map(|(p, q)| (p - q) * (p - q))
+                .sum::<f64>()
+                .sqrt();
+            curr[j + 1] = cost + prev[j].min(prev[j + 1]).min(curr[j]);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    prev[m] / (a.len() + m) as f64
+}
+
+fn similarity(distance: f64, scale: f64) -> f64 {
+    (-distance / scale).exp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signature_core::StrokePoint;
+
+    /// One stroke through `f(s)` for `s` in `0..=1`, sampled every 10 ms.
+    fn stroke(samples: usize, f: impl Fn(f64) -> (f64, f64, f32)) -> Stroke {
+        Stroke {
+            points: (0..samples)
+                .map(|i| {
+                    let s = i as f64 / (samples - 1) as f64;
+                    let (x, y, pressure) = f(s);
+                    StrokePoint {
+                        x,
+                        y,
+                        t: 1000.0 + i as f64 * 10.0,
+                        pressure
+                    }
+                })
+                .collect(),
+            pen:    None
+        }
+    }
+
+    /// A looping "signature" at (`dx`, `dy`), `size` px wide.
+    fn loops(dx: f64, dy: f64, size: f64, samples: usize) -> Vec<Stroke> {
+        vec![stroke(samples, |s| {
+            let a = s * std::f64::consts::TAU * 3.0;
+            (
+                dx + size * (s + 0.1 * a.cos()),
+                dy + size * 0.3 * a.sin(),
+                (0.4 + 0.3 * s) as f32
+            )
+        })]
+    }
+
+    #[test]
+    fn dtw_of_identical_sequences_is_zero() {
+        let a = [[0.0, 0.0], [1.0, 0.5], [2.0, 1.0]];
+        assert_eq!(dtw(&a, &a), 0.0);
+        // Repeating a sample warps onto the same path for free
+        let b = [[0.0, 0.0], [1.0, 0.5], [1.0, 0.5], [2.0, 1.0]];
+        assert_eq!(dtw(&a, &b), 0.0);
+        assert!(dtw(&a, &[[5.0, 5.0]]) > 1.0);
+    }
+
+    #[test]
+    fn identical_signatures_score_one() {
+        let reference = loops(50.0, 80.0, 200.0, 120);
+        let report = compare_strokes(&reference, &reference);
+        assert!((report.score - 1.0).abs() < 1e-9, "{report:?}");
+        assert_eq!(report.pressure, Some(1.0));
+    }
+
+    #[test]
+    fn moved_scaled_and_resampled_signature_still_matches() {
+        let reference = loops(50.0, 80.0, 200.0, 120);
+        let candidate = loops(300.0, 10.0, 120.0, 90);
+        let report = compare_strokes(&reference, &candidate);
+        assert!(report.position > 0.9, "{report:?}");
+        assert!(report.is_match(0.8), "{report:?}");
+    }
+
+    #[test]
+    fn unrelated_signature_does_not_match() {
+        let reference = loops(50.0, 80.0, 200.0, 120);
+        // A zig-zag with a pressure profile falling instead of rising
+        let candidate = vec![stroke(120, |s| {
+            let zig = ((s * 8.0).fract() - 0.5).abs();
+            (200.0 * zig, 200.0 * s, (0.9 - 0.6 * s) as f32)
+        })];
+        let report = compare_strokes(&reference, &candidate);
+        assert!(report.score < 0.5, "{report:?}");
+        assert!(!report.is_match(0.8));
+    }
+
+    #[test]
+    fn empty_input_scores_zero() {
+        let reference = loops(0.0, 0.0, 100.0, 50);
+        assert_eq!(compare_strokes(&reference, &[]).score, 0.0);
+        assert_eq!(compare_strokes(&[], &reference).pressure, None);
+    }
+}
@@ -3,7 +3,17 @@
 use wasm_bindgen::JsCast;
 use web_sys::{Document, Element, HtmlCanvasElement, HtmlElement, HtmlImageElement, Window};
 
-use crate::{error::SigError, signature_core::SignaturePad};
+use crate::{
+    audit::{AuditEntry, AuditEvent, AuditLog},
+    envelope::{SignedEnvelope, SignerMetadata},
+    error::SigError,
+    pdf::{ContractPdf, TextRun},
+    placement::{
+        AnchorLayout, AnchorTarget, InteractiveOptions, PlacementGeometry, PlacementHandle
+    },
+    reading::{ReadGate, ReadProgress, ReadTracker},
+    signature_core::SignaturePad
+};
 
 /// Append inline style pairs without clobbering existing rules.
 /// Very simple: appends `key:value;` if key is not present.
@@ -26,6 +36,65 @@ fn merge_inline_style(el: &Element, pairs: &[(&str, &str)]) -> Result<(), SigErr
         .map_err(|_| SigError::OpFailed("setAttribute(style)".into()))
 }
 
+/// Overwrite inline style properties (unlike `merge_inline_style`, which
+/// keeps existing values).
+pub(crate) fn set_inline_style(el: &HtmlElement, pairs: &[(&str, &str)]) -> Result<(), SigError> {
+    let style = el.style();
+    for (k, v) in pairs {
+        style
+            .set_property(k, v)
+            .map_err(|_| SigError::OpFailed(format!("style.setProperty({k})")))?;
+    }
+    Ok(())
+}
+
+/// Border box of `el` in `container`'s content coordinates (scroll offsets
+/// included, same space as `compute_anchor_offset`).
+pub(crate) fn element_box(container: &HtmlElement, el: &Element) -> PlacementGeometry {
+    let cr = container.get_bounding_client_rect();
+    let er = el.get_bounding_client_rect();
+    PlacementGeometry {
+        x:      (er.left() - cr.left()) + container.scroll_left() as f64,
+        y:      (er.top() - cr.top()) + container.scroll_top() as f64,
+        width:  er.width(),
+        height: er.height()
+    }
+}
+
+/// Top-left offsets of every `selector` match inside `container`.
+pub(crate) fn anchor_offsets(container: &HtmlElement, selector: &str) -> Vec<(f64, f64)> {
+    let Ok(list) = container.query_selector_all(selector) else {
+        return Vec::new();
+    };
+    (0..list.length())
+        .filter_map(|i| list.item(i))
+        .filter_map(|n| n.dyn_into::<Element>().ok())
+        .map(|el| {
+            let b = element_box(container, &el);
+            (b.x, b.y)
+        })
+        .collect()
+}
+
+/// Find the anchor element: by id anywhere in the document, by
+/// `data-signature-field` inside the container.
+fn find_anchor(
+    document: &Document,
+    container: &Element,
+    target: &AnchorTarget
+) -> Option<Element> {
+    match target {
+        AnchorTarget::Id(id) => document.get_element_by_id(id),
+        AnchorTarget::Field(name) => {
+            let name = name.replace('\\', "\\\\").replace('"', "\\\"");
+            container
+                .query_selector(&format!("[data-signature-field=\"{name}\"]"))
+                .ok()
+                .flatten()
+        }
+    }
+}
+
 /// Compute (left, top) inside a scrollable container using an anchor element.
 /// Returns None if anchor/container not found or not HtmlElement.
 fn compute_anchor_offset(
@@ -47,11 +116,146 @@ fn compute_anchor_offset(
     Some((left.round() as i32, top.round() as i32))
 }
 
+/// Collect the visible text of `node`'s subtree as word-merged line runs, in
+/// `container`'s content coordinates.
+fn collect_text_runs(
+    window: &Window,
+    document: &Document,
+    container: &HtmlElement,
+    node: &web_sys::Node,
+    runs: &mut Vec<TextRun>
+) {
+    if node.node_type() == web_sys::Node::TEXT_NODE {
+        let Some(parent) = node.parent_element() else {
+            return;
+        };
+        let Ok(Some(style)) = window.get_computed_style(&parent) else {
+            return;
+        };
+        if style.get_property_value("visibility").as_deref() == Ok("hidden") {
+            return;
+        }
+        let size = style
+            .get_property_value("font-size")
+            .ok()
+            .and_then(|v| v.trim_end_matches("px").parse::<f64>().ok())
+            .unwrap_or(16.0);
+        let Ok(range) = document.create_range() else {
+            return;
+        };
+        let text = node.node_value().unwrap_or_default();
+        let cr = container.get_bounding_client_rect();
+
+        // Range offsets are UTF-16 code units
+        let mut word = String::new();
+        let mut start = 0u32;
+        let mut pos = 0u32;
+        for c in text.chars().chain(std::iter::once(' ')) {
+            if c.is_whitespace() {
+                if !word.is_empty()
+                    && range.set_start(node, start).is_ok()
+                    && range.set_end(node, pos).is_ok()
+                {
+                    let r = range.get_bounding_client_rect();
+                    if r.width() > 0.0 && r.height() > 0.0 {
+                        let x = (r.left() - cr.left()) + container.scroll_left() as f64;
+                        let y = (r.top() - cr.top()) + container.scroll_top() as f64;
+                        push_word(runs, std::mem::take(&mut word), x, y, r.width(), size);
+                    }
+                }
+                word.clear();
+                start = pos + c.len_utf16() as u32;
+            } else {
+                word.push(c);
+            }
+            pos += c.len_utf16() as u32;
+        }
+        return;
+    }
+
+    let children = node.child_nodes();
+    for i in 0..children.length() {
+        if let Some(child) = children.item(i) {
+            collect_text_runs(window, document, container, &child, runs);
+        }
+    }
+}
+
+/// Append a word to the last run if it continues the same line, else start
+/// a new run.
+fn push_word(runs: &mut Vec<TextRun>, word: String, x: f64, y: f64, width: f64, size: f64) {
+    if let Some(last) = runs.last_mut() {
+        let gap = x - (last.x + last.width);
+        if (last.y - y).abs() < 0.5 && last.size == size && gap >= 0.0 && gap < size {
+            last.text.push(' ');
+            last.text.push_str(&word);
+            last.width = x + width - last.x;
+            return;
+        }
+    }
+    runs.push(TextRun {
+        text: word,
+        x,
+        y,
+        width,
+        size
+    });
+}
+
+/// Author's inline `visibility` of a hidden anchor placeholder, kept so
+/// `DomBindings::remove_signature` can put it back.
+const PREV_VISIBILITY_ATTR: &str = "data-cs-prev-visibility";
+
+/// Element ids of one signature field. Without a field id these are the
+/// legacy single-pad ids (`signature-img`, `sign-open-btn`, ...); with one,
+/// every id gets a `-{field_id}` suffix so several pads (buyer, seller,
+/// witness, initials) can share a page.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FieldIds {
+    suffix: String
+}
+
+impl FieldIds {
+    pub fn new(field_id: Option<&str>) -> Self {
+        Self {
+            suffix: field_id.map(|f| format!("-{f}")).unwrap_or_default()
+        }
+    }
+
+    /// Placed `<img>` inside the contract container.
+    pub fn img(&self) -> String {
+        format!("signature-img{}", self.suffix)
+    }
+
+    pub fn canvas(&self) -> String {
+        format!("signature-canvas{}", self.suffix)
+    }
+
+    pub fn open_btn(&self) -> String {
+        format!("sign-open-btn{}", self.suffix)
+    }
+
+    pub fn confirm_btn(&self) -> String {
+        format!("sign-confirm-btn{}", self.suffix)
+    }
+
+    pub fn clear_btn(&self) -> String {
+        format!("sign-clear-btn{}", self.suffix)
+    }
+
+    /// Inline "sign" button of pads without a modal (Leptos).
+    pub fn sign_btn(&self) -> String {
+        format!("sign-btn{}", self.suffix)
+    }
+}
+
 /// RAII handle that owns the SignaturePad and its JS listeners.
 /// On drop, listeners are removed.
 pub struct SignatureHandle {
     canvas:   HtmlCanvasElement,
     pad:      std::rc::Rc<std::cell::RefCell<SignaturePad>>,
+    /// Hash-chained log of the strokes drawn through the listeners.
+    audit:    std::rc::Rc<std::cell::RefCell<HandleAudit>>,
     on_down:  Option<wasm_bindgen::closure::Closure<dyn FnMut(web_sys::PointerEvent)>>,
     on_move:  Option<wasm_bindgen::closure::Closure<dyn FnMut(web_sys::PointerEvent)>>,
     on_up:    Option<wasm_bindgen::closure::Closure<dyn FnMut(web_sys::PointerEvent)>>,
@@ -61,19 +265,46 @@ pub struct SignatureHandle {
 impl SignatureHandle {
     /// Initialize on a given canvas element.
     pub fn new(canvas: HtmlCanvasElement) -> Result<Self, SigError> {
-        let pad_core = SignaturePad::new(canvas.clone())?;
-        let pad = std::rc::Rc::new(std::cell::RefCell::new(pad_core));
+        let pad = SignaturePad::new(canvas.clone())?.into_shared();
+        let audit = std::rc::Rc::new(std::cell::RefCell::new(HandleAudit::default()));
 
         // pointerdown
         let canvas_for_listen = canvas.clone();
-        let canvas_in_cb = canvas.clone();
         let pad_down = std::rc::Rc::clone(&pad);
+        let audit_down = std::rc::Rc::clone(&audit);
         let on_down =
             wasm_bindgen::closure::Closure::wrap(Box::new(move |e: web_sys::PointerEvent| {
-                let rect = canvas_in_cb.get_bounding_client_rect();
-                let x = e.client_x() as f64 - rect.x();
-                let y = e.client_y() as f64 - rect.y();
-                pad_down.borrow_mut().pointer_down(x, y);
+                if !e.is_trusted() {
+                    pad_down.borrow_mut().note_untrusted_event();
+                    return;
+                }
+                let mut pad = pad_down.borrow_mut();
+                let mut audit = audit_down.borrow_mut();
+                let pointer_type = e.pointer_type();
+                let (cx, cy) = (e.client_x() as f64, e.client_y() as f64);
+                if pointer_type == "touch" {
+                    let drawing = pad.is_drawing();
+                    if !pad.touch_down(e.pointer_id(), cx, cy) {
+                        if drawing && !pad.is_drawing() {
+                            audit.record(AuditEvent::StrokeCancelled, Some(&pointer_type));
+                        }
+                        return;
+                    }
+                }
+                let (x, y) = pad.client_to_canvas(cx, cy);
+                // Stylus eraser end / barrel eraser button
+                if e.buttons() & 32 != 0 || pad.is_eraser() {
+                    pad.eraser_down(x, y);
+                    return;
+                }
+                pad.pointer_down_with_pressure(x, y, e.pressure());
+                audit.record(
+                    AuditEvent::StrokeStarted {
+                        x,
+                        y
+                    },
+                    Some(&pointer_type)
+                );
             }) as Box<dyn FnMut(_)>);
         canvas_for_listen
             .add_event_listener_with_callback("pointerdown", on_down.as_ref().unchecked_ref())
@@ -81,14 +312,20 @@ impl SignatureHandle {
 
         // pointermove
         let canvas_for_listen = canvas.clone();
-        let canvas_in_cb = canvas.clone();
         let pad_move = std::rc::Rc::clone(&pad);
         let on_move =
             wasm_bindgen::closure::Closure::wrap(Box::new(move |e: web_sys::PointerEvent| {
-                let rect = canvas_in_cb.get_bounding_client_rect();
-                let x = e.client_x() as f64 - rect.x();
-                let y = e.client_y() as f64 - rect.y();
-                pad_move.borrow_mut().pointer_move(x, y);
+                if !e.is_trusted() {
+                    pad_move.borrow_mut().note_untrusted_event();
+                    return;
+                }
+                let mut pad = pad_move.borrow_mut();
+                let (cx, cy) = (e.client_x() as f64, e.client_y() as f64);
+                if e.pointer_type() == "touch" && pad.touch_move(e.pointer_id(), cx, cy) {
+                    return;
+                }
+                let (x, y) = pad.client_to_canvas(cx, cy);
+                pad.pointer_move_with_pressure(x, y, e.pressure());
             }) as Box<dyn FnMut(_)>);
         canvas_for_listen
             .add_event_listener_with_callback("pointermove", on_move.as_ref().unchecked_ref())
@@ -97,9 +334,18 @@ impl SignatureHandle {
         // pointerup
         let canvas_for_listen = canvas.clone();
         let pad_up = std::rc::Rc::clone(&pad);
+        let audit_up = std::rc::Rc::clone(&audit);
         let on_up =
-            wasm_bindgen::closure::Closure::wrap(Box::new(move |_e: web_sys::PointerEvent| {
-                pad_up.borrow_mut().pointer_up();
+            wasm_bindgen::closure::Closure::wrap(Box::new(move |e: web_sys::PointerEvent| {
+                let mut pad = pad_up.borrow_mut();
+                if !e.is_trusted() {
+                    pad.note_untrusted_event();
+                    return;
+                }
+                if e.pointer_type() == "touch" {
+                    pad.touch_up(e.pointer_id());
+                }
+                end_stroke(&mut pad, &mut audit_up.borrow_mut(), &e.pointer_type());
             }) as Box<dyn FnMut(_)>);
         canvas_for_listen
             .add_event_listener_with_callback("pointerup", on_up.as_ref().unchecked_ref())
@@ -108,9 +354,18 @@ impl SignatureHandle {
         // pointerleave
         let canvas_for_listen = canvas.clone();
         let pad_leave = std::rc::Rc::clone(&pad);
+        let audit_leave = std::rc::Rc::clone(&audit);
         let on_leave =
-            wasm_bindgen::closure::Closure::wrap(Box::new(move |_e: web_sys::PointerEvent| {
-                pad_leave.borrow_mut().pointer_up();
+            wasm_bindgen::closure::Closure::wrap(Box::new(move |e: web_sys::PointerEvent| {
+                let mut pad = pad_leave.borrow_mut();
+                if !e.is_trusted() {
+                    pad.note_untrusted_event();
+                    return;
+                }
+                if e.pointer_type() == "touch" {
+                    pad.touch_up(e.pointer_id());
+                }
+                end_stroke(&mut pad, &mut audit_leave.borrow_mut(), &e.pointer_type());
             }) as Box<dyn FnMut(_)>);
         canvas_for_listen
             .add_event_listener_with_callback("pointerleave", on_leave.as_ref().unchecked_ref())
@@ -119,6 +374,7 @@ impl SignatureHandle {
         Ok(Self {
             canvas,
             pad,
+            audit,
             on_down: Some(on_down),
             on_move: Some(on_move),
             on_up: Some(on_up),
@@ -132,9 +388,71 @@ impl SignatureHandle {
 
     pub fn clear(&self) {
         self.pad.borrow_mut().clear();
+        self.audit.borrow_mut().record(AuditEvent::Cleared, None);
+    }
+
+    /// Audit log of the session so far (see `verify_audit_chain`).
+    pub fn audit_trail(&self) -> Vec<AuditEntry> {
+        self.audit.borrow().log.entries().to_vec()
+    }
+
+    /// Record the confirmation and capture the envelope, audit trail
+    /// included, against the text of `container_id`. Fails if an event of
+    /// the session could not be recorded.
+    pub fn capture(
+        &self,
+        container_id: &str,
+        signer: SignerMetadata
+    ) -> Result<SignedEnvelope, SigError> {
+        if let Some(e) = &self.audit.borrow().failed {
+            return Err(SigError::InvalidState(format!(
+                "audit trail incomplete: {e}"
+            )));
+        }
+        let mut envelope = SignedEnvelope::capture(&self.pad.borrow(), container_id, signer)?;
+        self.audit
+            .borrow_mut()
+            .log
+            .record(AuditEvent::Confirmed, None)?;
+        envelope.audit_trail = self.audit_trail();
+        Ok(envelope)
+    }
+}
+
+/// Audit log of a `SignatureHandle`. Its listeners can't report errors, so
+/// the first event that failed to record is kept for `capture` to refuse.
+#[derive(Default)]
+struct HandleAudit {
+    log:    AuditLog,
+    failed: Option<SigError>
+}
+
+impl HandleAudit {
+    fn record(&mut self, event: AuditEvent, pointer_type: Option<&str>) {
+        if let Err(e) = self.log.record(event, pointer_type) {
+            self.failed.get_or_insert(e);
+        }
     }
 }
 
+/// Finish the stroke or erase gesture in progress, if any, and log it.
+fn end_stroke(pad: &mut SignaturePad, audit: &mut HandleAudit, pointer_type: &str) {
+    let (drawing, erasing) = (pad.is_drawing(), pad.is_erasing());
+    pad.pointer_up();
+    let event = if erasing && pad.erased_points() > 0 {
+        AuditEvent::Erased {
+            points: pad.erased_points()
+        }
+    } else if drawing {
+        AuditEvent::StrokeEnded {
+            points: pad.strokes().last().map_or(0, |s| s.points.len())
+        }
+    } else {
+        return;
+    };
+    audit.record(event, Some(pointer_type));
+}
+
 impl Drop for SignatureHandle {
     fn drop(&mut self) {
         if let Some(cb) = self.on_down.take() {
@@ -160,6 +478,297 @@ impl Drop for SignatureHandle {
     }
 }
 
+/// Class of initials images stamped into anchors.
+const INITIALS_IMG_CLASS: &str = "cs-initials-img";
+
+/// Selector of the initials images of one field; each image carries its
+/// field id in `data-field` (empty for single-field pages).
+fn initials_img_selector(field_id: Option<&str>) -> String {
+    let value = field_id
+        .unwrap_or_default()
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"");
+    format!("img.{INITIALS_IMG_CLASS}[data-field=\"{value}\"]")
+}
+
+/// Keeps initials stamped into every anchor matching a selector while the
+/// contract DOM changes (pages re-rendered, anchors added). The images live
+/// inside their anchors, so plain reflow moves them along for free. Dropping
+/// the handle stops tracking; stamped images stay.
+pub struct InitialsHandle {
+    observer:   web_sys::MutationObserver,
+    filled:     std::rc::Rc<std::cell::RefCell<Vec<String>>>,
+    _on_mutate:
+        wasm_bindgen::closure::Closure<dyn FnMut(js_sys::Array, web_sys::MutationObserver)>
+}
+
+impl InitialsHandle {
+    /// Labels of the anchors currently holding initials.
+    pub fn filled(&self) -> Vec<String> {
+        self.filled.borrow().clone()
+    }
+}
+
+impl Drop for InitialsHandle {
+    fn drop(&mut self) {
+        self.observer.disconnect();
+    }
+}
+
+/// Stamp `<img class="cs-initials-img" data-field=..>` into every `selector`
+/// match inside `container`. Returns the anchor labels: `data-initials-anchor`
+/// value, else element id, else `#<index>`.
+fn stamp_initials_into(
+    container: &Element,
+    field_id: Option<&str>,
+    selector: &str,
+    data_url: &str,
+    width: i32
+) -> Result<Vec<String>, SigError> {
+    let document = container.owner_document().ok_or(SigError::DomUnavailable)?;
+    let anchors = container
+        .query_selector_all(selector)
+        .map_err(|_| SigError::OpFailed(format!("querySelectorAll({selector})")))?;
+
+    let mut filled = Vec::new();
+    for i in 0..anchors.length() {
+        let Some(anchor) = anchors.item(i).and_then(|n| n.dyn_into::<Element>().ok()) else {
+            continue;
+        };
+        merge_inline_style(&anchor, &[("position", "relative")])?;
+
+        let existing = anchor
+            .query_selector(&format!(":scope > {}", initials_img_selector(field_id)))
+            .ok()
+            .flatten();
+        let img: HtmlImageElement = match existing {
+            Some(el) => el
+                .dyn_into::<HtmlImageElement>()
+                .map_err(|_| SigError::OpFailed("initials element is not <img>".into()))?,
+            None => {
+                let img = document
+                    .create_element("img")
+                    .map_err(|_| SigError::OpFailed("createElement(img)".into()))?
+                    .dyn_into::<HtmlImageElement>()
+                    .map_err(|_| SigError::OpFailed("created element is not <img>".into()))?;
+                img.set_class_name(INITIALS_IMG_CLASS);
+                img.set_attribute("data-field", field_id.unwrap_or_default())
+                    .map_err(|_| SigError::OpFailed("setAttribute(data-field)".into()))?;
+                img.set_alt("initials");
+                merge_inline_style(
+                    img.as_ref(),
+                    &[
+                        ("position", "absolute"),
+                        ("left", "0"),
+                        ("top", "0"),
+                        ("width", &format!("{width}px")),
+                        ("height", "auto"),
+                        ("z-index", "1"),
+                        ("pointer-events", "none")
+                    ]
+                )?;
+                anchor
+                    .append_child(&img)
+                    .map_err(|_| SigError::OpFailed("appendChild(img)".into()))?;
+                img
+            }
+        };
+        if img.src() != data_url {
+            img.set_src(data_url);
+        }
+
+        let label = anchor
+            .get_attribute("data-initials-anchor")
+            .filter(|v| !v.is_empty())
+            .or_else(|| Some(anchor.id()).filter(|id| !id.is_empty()))
+            .unwrap_or_else(|| format!("#{i}"));
+        filled.push(label);
+    }
+    Ok(filled)
+}
+
+/// Lay `<img id=img_id>` out against the current box of the anchor. Returns
+/// the new geometry, or None if the container, anchor or image is gone.
+fn layout_at_anchor(
+    document: &Document,
+    container_id: &str,
+    target: &AnchorTarget,
+    img_id: &str,
+    width: f64,
+    ratio: f64,
+    layout: &AnchorLayout
+) -> Option<PlacementGeometry> {
+    let container = document
+        .get_element_by_id(container_id)?
+        .dyn_into::<HtmlElement>()
+        .ok()?;
+    let anchor = find_anchor(document, &container, target)?;
+    let img = document
+        .get_element_by_id(img_id)?
+        .dyn_into::<HtmlElement>()
+        .ok()?;
+    let g = layout.resolve(element_box(&container, &anchor), width, ratio);
+    set_inline_style(
+        &img,
+        &[
+            ("left", &format!("{}px", g.x.round())),
+            ("top", &format!("{}px", g.y.round())),
+            ("width", &format!("{}px", g.width.round())),
+            ("height", &format!("{}px", g.height.round())),
+            ("object-fit", layout.fit.object_fit())
+        ]
+    )
+    .ok()?;
+    Some(g)
+}
+
+/// Print layout events that can move the anchor without resizing anything
+/// we observe. `beforeprint` still sees the screen layout in most browsers,
+/// so the tracker also re-measures when `(print)` starts or stops matching,
+/// which happens with print styles applied.
+const PRINT_EVENTS: [&str; 2] = ["beforeprint", "afterprint"];
+const PRINT_QUERY: &str = "print";
+
+/// RAII handle keeping a placed signature on its anchor while the contract
+/// reflows (container/anchor resize, late fonts, content changes, print).
+/// On drop, observers and listeners are removed; the image stays where it is.
+pub struct AnchorTracker {
+    resize:      web_sys::ResizeObserver,
+    mutation:    web_sys::MutationObserver,
+    window:      Window,
+    _on_resize:  wasm_bindgen::closure::Closure<dyn FnMut(js_sys::Array, web_sys::ResizeObserver)>,
+    _on_mutate:
+        wasm_bindgen::closure::Closure<dyn FnMut(js_sys::Array, web_sys::MutationObserver)>,
+    /// `matchMedia("print")`, if the browser has it.
+    print_query: Option<web_sys::MediaQueryList>,
+    on_print:    wasm_bindgen::closure::Closure<dyn FnMut(web_sys::Event)>
+}
+
+impl Drop for AnchorTracker {
+    fn drop(&mut self) {
+        self.resize.disconnect();
+        self.mutation.disconnect();
+        let cb = self.on_print.as_ref().unchecked_ref();
+        for event in PRINT_EVENTS {
+            let _ = self.window.remove_event_listener_with_callback(event, cb);
+        }
+        if let Some(query) = &self.print_query {
+            let _ = query.remove_event_listener_with_callback("change", cb);
+        }
+    }
+}
+
+/// Page styles overridden while signing fullscreen: no page scroll, no
+/// pull-to-refresh / overscroll navigation.
+const SCROLL_LOCK: [(&str, &str); 2] = [("overflow", "hidden"), ("overscroll-behavior", "none")];
+
+/// RAII handle for the phone signing mode: the page goes fullscreen, the
+/// screen is locked to landscape where the browser allows it (once
+/// fullscreen is granted), and page scrolling is disabled. On drop,
+/// everything is put back.
+pub struct FullscreenSigning {
+    document:      Document,
+    /// Element, property, previous value and priority.
+    saved:         Vec<(HtmlElement, &'static str, String, String)>,
+    on_fullscreen: wasm_bindgen::closure::Closure<dyn FnMut(web_sys::Event)>,
+    /// Whether we made the page fullscreen (and so should leave it).
+    requested:     bool
+}
+
+impl FullscreenSigning {
+    /// Enter the mode. Fullscreen needs a user gesture, so call this from a
+    /// click handler; where it is refused (e.g. iOS Safari), only the scroll
+    /// lock applies.
+    pub fn enter() -> Result<Self, SigError> {
+        let window = web_sys::window().ok_or(SigError::DomUnavailable)?;
+        let document = window.document().ok_or(SigError::DomUnavailable)?;
+
+        let mut saved = Vec::new();
+        let roots = [
+            document
+                .document_element()
+                .and_then(|el| el.dyn_into::<HtmlElement>().ok()),
+            document.body()
+        ];
+        for el in roots.into_iter().flatten() {
+            let style = el.style();
+            for (prop, value) in SCROLL_LOCK {
+                let prev = style.get_property_value(prop).unwrap_or_default();
+                let priority = style.get_property_priority(prop);
+                let _ = style.set_property_with_priority(prop, value, "important");
+                saved.push((el.clone(), prop, prev, priority));
+            }
+        }
+
+        // Orientation can only be locked in fullscreen: wait for it
+        let on_fullscreen = {
+            let document = document.clone();
+            wasm_bindgen::closure::Closure::<dyn FnMut(web_sys::Event)>::new(
+                move |_e: web_sys::Event| {
+                    if document.fullscreen_element().is_none() {
+                        return;
+                    }
+                    if let Ok(orientation) = window.screen().map(|s| s.orientation())
+                        && let Ok(promise) =
+                            orientation.lock(web_sys::OrientationLockType::Landscape)
+                    {
+                        // Rejected on desktops and some phones; the UI hints instead
+                        wasm_bindgen_futures::spawn_local(async move {
+                            let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+                        });
+                    }
+                }
+            )
+        };
+        let _ = document.add_event_listener_with_callback(
+            "fullscreenchange",
+            on_fullscreen.as_ref().unchecked_ref()
+        );
+
+        let requested = document.fullscreen_element().is_none()
+            && document
+                .document_element()
+                .is_some_and(|el| el.request_fullscreen().is_ok());
+
+        Ok(Self {
+            document,
+            saved,
+            on_fullscreen,
+            requested
+        })
+    }
+
+    /// Is the page fullscreen right now?
+    pub fn is_fullscreen(&self) -> bool {
+        self.document.fullscreen_element().is_some()
+    }
+}
+
+impl Drop for FullscreenSigning {
+    fn drop(&mut self) {
+        let _ = self.document.remove_event_listener_with_callback(
+            "fullscreenchange",
+            self.on_fullscreen.as_ref().unchecked_ref()
+        );
+        for (el, prop, prev, priority) in self.saved.drain(..) {
+            let style = el.style();
+            if prev.is_empty() {
+                let _ = style.remove_property(prop);
+            } else {
+                let _ = style.set_property_with_priority(prop, &prev, &priority);
+            }
+        }
+        if let Some(window) = web_sys::window()
+            && let Ok(screen) = window.screen()
+        {
+            let _ = screen.orientation().unlock();
+        }
+        if self.requested && self.document.fullscreen_element().is_some() {
+            self.document.exit_fullscreen();
+        }
+    }
+}
+
 /// DOM helpers. Stateless. All state lives in `SignatureHandle`.
 pub struct DomBindings;
 
@@ -178,6 +787,18 @@ impl DomBindings {
         SignatureHandle::new(canvas)
     }
 
+    /// Text content of the contract container (what the signer was shown).
+    pub fn contract_text(container_id: &str) -> Result<String, SigError> {
+        let document = web_sys::window()
+            .ok_or(SigError::DomUnavailable)?
+            .document()
+            .ok_or(SigError::DomUnavailable)?;
+        let container = document
+            .get_element_by_id(container_id)
+            .ok_or_else(|| SigError::ElementNotFound(container_id.to_string()))?;
+        Ok(container.text_content().unwrap_or_default())
+    }
+
     /// Public API used from `yew.rs`:
     /// Insert/update `<img id="signature-img">` at (x, y) inside
     /// `container_id`.
@@ -191,6 +812,20 @@ impl DomBindings {
         Self::place_signature_img_with_id(container_id, "signature-img", data_url, x, y, width)
     }
 
+    /// Same as `place_signature_img`, but into the `<img>` of one field
+    /// (see `FieldIds`), so several fields don't overwrite each other.
+    pub fn place_signature_img_for_field(
+        container_id: &str,
+        field_id: Option<&str>,
+        data_url: &str,
+        x: i32,
+        y: i32,
+        width: i32
+    ) -> Result<(), SigError> {
+        let img_id = FieldIds::new(field_id).img();
+        Self::place_signature_img_with_id(container_id, &img_id, data_url, x, y, width)
+    }
+
     /// Convenience: place by anchor; falls back to (x,y) if anchor not found.
     pub fn place_signature_img_by_anchor(
         container_id: &str,
@@ -199,12 +834,372 @@ impl DomBindings {
         fallback_x: i32,
         fallback_y: i32,
         width: i32
+    ) -> Result<(), SigError> {
+        Self::place_signature_img_by_anchor_for_field(
+            container_id,
+            None,
+            anchor_id,
+            data_url,
+            fallback_x,
+            fallback_y,
+            width
+        )
+    }
+
+    /// Field-scoped variant of `place_signature_img_by_anchor`.
+    pub fn place_signature_img_by_anchor_for_field(
+        container_id: &str,
+        field_id: Option<&str>,
+        anchor_id: &str,
+        data_url: &str,
+        fallback_x: i32,
+        fallback_y: i32,
+        width: i32
     ) -> Result<(), SigError> {
         let window: Window = web_sys::window().ok_or(SigError::DomUnavailable)?;
         let document: Document = window.document().ok_or(SigError::DomUnavailable)?;
         let (x, y) = compute_anchor_offset(&document, container_id, anchor_id)
             .unwrap_or((fallback_x, fallback_y));
-        Self::place_signature_img_with_id(container_id, "signature-img", data_url, x, y, width)
+        let img_id = FieldIds::new(field_id).img();
+        Self::place_signature_img_with_id(container_id, &img_id, data_url, x, y, width)
+    }
+
+    /// Lay out the contract's visible text for `ContractPdf`, exactly where
+    /// the browser rendered it. Add the signatures (`PdfSignature`) and,
+    /// for non-Latin text, a `TrueTypeFont` before calling `to_bytes`.
+    pub fn contract_pdf(container_id: &str) -> Result<ContractPdf, SigError> {
+        let window: Window = web_sys::window().ok_or(SigError::DomUnavailable)?;
+        let document: Document = window.document().ok_or(SigError::DomUnavailable)?;
+        let container = Self::container(container_id)?
+            .dyn_into::<HtmlElement>()
+            .map_err(|_| SigError::ElementNotFound(container_id.to_string()))?;
+
+        let mut pdf = ContractPdf::new(container.scroll_width() as f64);
+        collect_text_runs(&window, &document, &container, &container, &mut pdf.texts);
+        Ok(pdf)
+    }
+
+    /// Hide an anchor placeholder under a placed signature. Only the inline
+    /// `visibility` is touched (the box stays, so the text doesn't reflow);
+    /// its previous value is restored by `remove_signature`.
+    pub fn hide_anchor_placeholder(anchor_id: &str) -> Result<(), SigError> {
+        let el = web_sys::window()
+            .ok_or(SigError::DomUnavailable)?
+            .document()
+            .ok_or(SigError::DomUnavailable)?
+            .get_element_by_id(anchor_id)
+            .and_then(|e| e.dyn_into::<HtmlElement>().ok())
+            .ok_or_else(|| SigError::ElementNotFound(anchor_id.to_string()))?;
+        if !el.has_attribute(PREV_VISIBILITY_ATTR) {
+            let prev = el
+                .style()
+                .get_property_value("visibility")
+                .unwrap_or_default();
+            el.set_attribute(PREV_VISIBILITY_ATTR, &prev).map_err(|_| {
+                SigError::OpFailed(format!("setAttribute({PREV_VISIBILITY_ATTR})"))
+            })?;
+        }
+        set_inline_style(&el, &[("visibility", "hidden")])
+    }
+
+    /// Take a field's placed image off the contract and restore the anchor
+    /// placeholder hidden by `hide_anchor_placeholder`, if any. Returns false
+    /// if there was no image.
+    pub fn remove_signature(
+        container_id: &str,
+        field_id: Option<&str>,
+        anchor_id: Option<&str>
+    ) -> Result<bool, SigError> {
+        let container = Self::container(container_id)?;
+        let document = container.owner_document().ok_or(SigError::DomUnavailable)?;
+
+        if let Some(anchor) = anchor_id
+            .and_then(|id| document.get_element_by_id(id))
+            .and_then(|e| e.dyn_into::<HtmlElement>().ok())
+            && let Some(prev) = anchor.get_attribute(PREV_VISIBILITY_ATTR)
+        {
+            let style = anchor.style();
+            let restored = if prev.is_empty() {
+                style.remove_property("visibility").map(|_| ())
+            } else {
+                style.set_property("visibility", &prev)
+            };
+            restored.map_err(|_| SigError::OpFailed("restore anchor visibility".into()))?;
+            let _ = anchor.remove_attribute(PREV_VISIBILITY_ATTR);
+        }
+
+        let img_id = FieldIds::new(field_id).img();
+        match document.get_element_by_id(&img_id) {
+            Some(img) if container.contains(Some(&img)) => {
+                img.remove();
+                Ok(true)
+            }
+            _ => Ok(false)
+        }
+    }
+
+    /// Remove the initials images `field_id` stamped into the contract;
+    /// other fields' initials stay. Drop the `InitialsHandle` first, or it
+    /// stamps them again. Returns how many were removed.
+    pub fn remove_initials(container_id: &str, field_id: Option<&str>) -> Result<usize, SigError> {
+        let container = Self::container(container_id)?;
+        let list = container
+            .query_selector_all(&initials_img_selector(field_id))
+            .map_err(|_| SigError::OpFailed("querySelectorAll(initials)".into()))?;
+        let imgs: Vec<Element> = (0..list.length())
+            .filter_map(|i| list.item(i))
+            .filter_map(|n| n.dyn_into::<Element>().ok())
+            .collect();
+        for img in &imgs {
+            img.remove();
+        }
+        Ok(imgs.len())
+    }
+
+    /// Initials mode: stamp a copy of the signature (scaled to `width` px) at
+    /// every element matching `selector` (e.g. `[data-initials-anchor]`)
+    /// inside `container_id`, tagged with `field_id`. Returns the labels of
+    /// the filled anchors.
+    pub fn stamp_initials(
+        container_id: &str,
+        field_id: Option<&str>,
+        selector: &str,
+        data_url: &str,
+        width: i32
+    ) -> Result<Vec<String>, SigError> {
+        let container = Self::container(container_id)?;
+        stamp_initials_into(&container, field_id, selector, data_url, width)
+    }
+
+    /// Like `stamp_initials`, and keeps stamping anchors that appear later.
+    /// `on_change` receives the filled anchor labels whenever they change.
+    pub fn track_initials(
+        container_id: &str,
+        field_id: Option<&str>,
+        selector: &str,
+        data_url: &str,
+        width: i32,
+        mut on_change: impl FnMut(&[String]) + 'static
+    ) -> Result<InitialsHandle, SigError> {
+        let container = Self::container(container_id)?;
+        let first = stamp_initials_into(&container, field_id, selector, data_url, width)?;
+        on_change(&first);
+        let filled = std::rc::Rc::new(std::cell::RefCell::new(first));
+
+        let filled_in_cb = std::rc::Rc::clone(&filled);
+        let container_in_cb = container.clone();
+        let field_id = field_id.map(str::to_string);
+        let selector = selector.to_string();
+        let data_url = data_url.to_string();
+        let on_mutate = wasm_bindgen::closure::Closure::wrap(Box::new(
+            move |_records: js_sys::Array, _observer: web_sys::MutationObserver| {
+                // Our own <img> insertions trigger one more round, which then
+                // finds nothing to change.
+                if let Ok(now) = stamp_initials_into(
+                    &container_in_cb,
+                    field_id.as_deref(),
+                    &selector,
+                    &data_url,
+                    width
+                ) && *filled_in_cb.borrow() != now
+                {
+                    on_change(&now);
+                    *filled_in_cb.borrow_mut() = now;
+                }
+            }
+        )
+            as Box<dyn FnMut(js_sys::Array, web_sys::MutationObserver)>);
+
+        let observer = web_sys::MutationObserver::new(on_mutate.as_ref().unchecked_ref())
+            .map_err(|_| SigError::OpFailed("new MutationObserver".into()))?;
+        let init = web_sys::MutationObserverInit::new();
+        init.set_child_list(true);
+        init.set_subtree(true);
+        observer
+            .observe_with_options(&container, &init)
+            .map_err(|_| SigError::OpFailed("MutationObserver.observe".into()))?;
+
+        Ok(InitialsHandle {
+            observer,
+            filled,
+            _on_mutate: on_mutate
+        })
+    }
+
+    /// Place a field's image against an anchor element, aligned and sized by
+    /// `layout`. `ratio` is the image aspect (height / width) and `width`
+    /// the requested width (ignored by the `Contain`/`Cover` fit modes).
+    pub fn place_signature_img_at_anchor(
+        container_id: &str,
+        field_id: Option<&str>,
+        target: &AnchorTarget,
+        data_url: &str,
+        width: f64,
+        ratio: f64,
+        layout: &AnchorLayout
+    ) -> Result<PlacementGeometry, SigError> {
+        let document = web_sys::window()
+            .ok_or(SigError::DomUnavailable)?
+            .document()
+            .ok_or(SigError::DomUnavailable)?;
+        let container = Self::container(container_id)?;
+        find_anchor(&document, &container, target)
+            .ok_or_else(|| SigError::ElementNotFound(target.label()))?;
+
+        let img_id = FieldIds::new(field_id).img();
+        Self::place_signature_img_with_id(container_id, &img_id, data_url, 0, 0, width as i32)?;
+        layout_at_anchor(
+            &document,
+            container_id,
+            target,
+            &img_id,
+            width,
+            ratio,
+            layout
+        )
+        .ok_or_else(|| SigError::OpFailed("layout at anchor".into()))
+    }
+
+    /// Keep a field's image (see `place_signature_img_at_anchor`) laid out
+    /// against its anchor whenever the page reflows. `on_move` receives the
+    /// new geometry after each realignment that moved the image.
+    pub fn track_anchor(
+        container_id: &str,
+        field_id: Option<&str>,
+        target: AnchorTarget,
+        width: f64,
+        ratio: f64,
+        layout: AnchorLayout,
+        mut on_move: impl FnMut(PlacementGeometry) + 'static
+    ) -> Result<AnchorTracker, SigError> {
+        let window: Window = web_sys::window().ok_or(SigError::DomUnavailable)?;
+        let document: Document = window.document().ok_or(SigError::DomUnavailable)?;
+        let container = Self::container(container_id)?;
+        let anchor = find_anchor(&document, &container, &target)
+            .ok_or_else(|| SigError::ElementNotFound(target.label()))?;
+
+        let container_id = container_id.to_string();
+        let img_id = FieldIds::new(field_id).img();
+        let relayout = move || {
+            layout_at_anchor(
+                &document,
+                &container_id,
+                &target,
+                &img_id,
+                width,
+                ratio,
+                &layout
+            )
+        };
+        let mut last = relayout();
+        let realign = std::rc::Rc::new(std::cell::RefCell::new(move || {
+            let now = relayout();
+            if let Some(g) = now
+                && now != last
+            {
+                last = now;
+                on_move(g);
+            }
+        }));
+
+        let realign_cb = std::rc::Rc::clone(&realign);
+        let on_resize = wasm_bindgen::closure::Closure::wrap(Box::new(
+            move |_entries: js_sys::Array, _observer: web_sys::ResizeObserver| {
+                (realign_cb.borrow_mut())();
+            }
+        )
+            as Box<dyn FnMut(js_sys::Array, web_sys::ResizeObserver)>);
+        let resize = web_sys::ResizeObserver::new(on_resize.as_ref().unchecked_ref())
+            .map_err(|_| SigError::OpFailed("new ResizeObserver".into()))?;
+        resize.observe(&container);
+        resize.observe(&anchor);
+
+        // Text edits and inserted content shift the anchor without resizing
+        // the container. Our own style writes are attribute changes and are
+        // not observed.
+        let realign_cb = std::rc::Rc::clone(&realign);
+        let on_mutate = wasm_bindgen::closure::Closure::wrap(Box::new(
+            move |_records: js_sys::Array, _observer: web_sys::MutationObserver| {
+                (realign_cb.borrow_mut())();
+            }
+        )
+            as Box<dyn FnMut(js_sys::Array, web_sys::MutationObserver)>);
+        let mutation = web_sys::MutationObserver::new(on_mutate.as_ref().unchecked_ref())
+            .map_err(|_| SigError::OpFailed("new MutationObserver".into()))?;
+        let init = web_sys::MutationObserverInit::new();
+        init.set_child_list(true);
+        init.set_subtree(true);
+        init.set_character_data(true);
+        mutation
+            .observe_with_options(&container, &init)
+            .map_err(|_| SigError::OpFailed("MutationObserver.observe".into()))?;
+
+        let on_print = wasm_bindgen::closure::Closure::wrap(Box::new(move |_e: web_sys::Event| {
+            (realign.borrow_mut())();
+        })
+            as Box<dyn FnMut(web_sys::Event)>);
+        for event in PRINT_EVENTS {
+            window
+                .add_event_listener_with_callback(event, on_print.as_ref().unchecked_ref())
+                .map_err(|_| SigError::OpFailed(format!("addEventListener({event})")))?;
+        }
+        let print_query = window.match_media(PRINT_QUERY).ok().flatten();
+        if let Some(query) = &print_query {
+            query
+                .add_event_listener_with_callback("change", on_print.as_ref().unchecked_ref())
+                .map_err(|_| SigError::OpFailed("addEventListener(change)".into()))?;
+        }
+
+        Ok(AnchorTracker {
+            resize,
+            mutation,
+            window,
+            _on_resize: on_resize,
+            _on_mutate: on_mutate,
+            print_query,
+            on_print
+        })
+    }
+
+    /// Make a field's placed image draggable and resizable inside
+    /// `container_id` (see `PlacementHandle`).
+    pub fn make_placement_interactive(
+        container_id: &str,
+        field_id: Option<&str>,
+        options: InteractiveOptions,
+        on_change: impl FnMut(PlacementGeometry) + 'static
+    ) -> Result<PlacementHandle, SigError> {
+        let container = Self::container(container_id)?
+            .dyn_into::<HtmlElement>()
+            .map_err(|_| SigError::ElementNotFound(container_id.to_string()))?;
+        let img_id = FieldIds::new(field_id).img();
+        let img = container
+            .owner_document()
+            .and_then(|d| d.get_element_by_id(&img_id))
+            .and_then(|el| el.dyn_into::<HtmlElement>().ok())
+            .ok_or(SigError::ElementNotFound(img_id))?;
+        PlacementHandle::new(container, img, options, on_change)
+    }
+
+    /// Track how far the signer has read `container_id` (see `ReadGate`).
+    pub fn track_reading(
+        container_id: &str,
+        gate: ReadGate,
+        on_progress: impl FnMut(ReadProgress) + 'static
+    ) -> Result<ReadTracker, SigError> {
+        let container = Self::container(container_id)?
+            .dyn_into::<HtmlElement>()
+            .map_err(|_| SigError::ElementNotFound(container_id.to_string()))?;
+        ReadTracker::new(container, gate, on_progress)
+    }
+
+    fn container(container_id: &str) -> Result<Element, SigError> {
+        web_sys::window()
+            .ok_or(SigError::DomUnavailable)?
+            .document()
+            .ok_or(SigError::DomUnavailable)?
+            .get_element_by_id(container_id)
+            .ok_or_else(|| SigError::ElementNotFound(container_id.to_string()))
     }
 
     /// Internal helper that ensures container is positioning context and reuses
@@ -7,14 +7,59 @@ use std::{cell::RefCell, rc::Rc};
 #[cfg(feature = "yew")]
 use wasm_bindgen::JsCast;
 #[cfg(feature = "yew")]
-use web_sys::HtmlCanvasElement;
+use web_sys::{HtmlCanvasElement, HtmlInputElement};
 #[cfg(feature = "yew")]
 use yew::{
-    function_component, html, use_effect_with, use_node_ref, use_state, Callback, Html, Properties
+    function_component, html, use_effect_with, use_mut_ref, use_node_ref, use_state, Callback,
+    Html, Properties, TargetCast
 };
 
 #[cfg(feature = "yew")]
-use crate::signature_core::SignaturePad;
+use crate::{
+    audit::{AuditEntry, AuditEvent, AuditLog},
+    compose::Composition,
+    consent::ConsentStep,
+    draft::{Draft, DraftStore, RestoredEnvelope},
+    envelope::{SignedEnvelope, SignerMetadata},
+    error::SigError,
+    placement::{
+        AnchorLayout, AnchorTarget, InteractiveOptions, PlacementGeometry, PlacementHandle,
+        PlacementMode
+    },
+    profile::{ProfileStore, SavedSignature, SignatureOrigin},
+    reading::{ReadGate, ReadProgress, ReadTracker},
+    session::SigningSession,
+    signature_core::{Decorations, PenStyle, SignaturePad, Stroke},
+    similarity::{compare_strokes, SimilarityReport},
+    ui_common::{AnchorTracker, DomBindings, FieldIds, FullscreenSigning, InitialsHandle}
+};
+
+/// Height kept free for the title and controls in fullscreen mode, px.
+#[cfg(feature = "yew")]
+const FULLSCREEN_RESERVED_HEIGHT: f64 = 150.0;
+/// Side margin of the canvas in fullscreen mode, px.
+#[cfg(feature = "yew")]
+const FULLSCREEN_MARGIN: f64 = 12.0;
+/// Inline layout of the modal panel in fullscreen mode, so it works without
+/// any stylesheet; `.cs-modal--fullscreen` stays available for theming.
+#[cfg(feature = "yew")]
+const FULLSCREEN_PANEL_STYLE: &str = "width:100vw;height:100dvh;min-width:0;max-width:none;\
+                                      margin:0;border-radius:0;box-sizing:border-box;\
+                                      display:flex;flex-direction:column;align-items:center;\
+                                      overflow-y:auto;animation:none";
+
+/// When the modal switches to fullscreen signing (see
+/// `FullscreenSigning`).
+#[cfg(feature = "yew")]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum MobileMode {
+    /// Always the regular modal.
+    #[default]
+    Off,
+    /// On touch-first devices, or when the canvas doesn't fit the viewport.
+    Auto,
+    Always
+}
 
 /// Visual customization for the signature canvas area.
 #[cfg(feature = "yew")]
@@ -27,7 +72,8 @@ pub struct SignatureStyle {
     /// Canvas width/height in CSS pixels
     pub width:      u32,
     pub height:     u32,
-    /// Pen stroke width (kept for future API; core defaults to 2.0)
+    /// Width of the default (black) pen, canvas px; see
+    /// `SignatureProps::pen`
     pub line_width: f64
 }
 
@@ -52,7 +98,8 @@ pub struct SignatureProps {
     /// positioning inside).
     pub contract_container_id: String,
 
-    /// Fallback coordinates (used if anchor_id is None or anchor not found).
+    /// Fallback coordinates (used if no anchor resolves). `place_width` is
+    /// also the image width at an anchor unless `anchor_layout` fits it.
     pub place_x:     i32,
     pub place_y:     i32,
     pub place_width: i32,
@@ -61,47 +108,550 @@ pub struct SignatureProps {
     #[prop_or(true)]
     pub enabled: bool,
 
+    /// Built-in reading requirement on `contract_container_id`; signing
+    /// stays locked (on top of `enabled`) until it is met.
+    #[prop_or_default]
+    pub read_gate: ReadGate,
+
+    /// Called with the reading progress while `read_gate` is tracked.
+    #[prop_or_default]
+    pub on_read_progress: Option<Callback<ReadProgress>>,
+
+    /// Called with errors the component can't show itself, e.g. when the
+    /// read gate can't track `contract_container_id` (signing then stays
+    /// locked).
+    #[prop_or_default]
+    pub on_error: Option<Callback<SigError>>,
+
+    /// Consent checkbox in the modal (e-signature consent); recorded in
+    /// `SignedEnvelope::consent` with the time it was given.
+    #[prop_or_default]
+    pub consent: Option<ConsentStep>,
+
     /// Visual style of the signature canvas.
     #[prop_or_default]
     pub style: Option<SignatureStyle>,
 
+    /// Fullscreen, landscape signing on phones. The canvas is enlarged to
+    /// the viewport keeping the aspect ratio of `style`; strokes and exports
+    /// stay in `style.width` x `style.height` px. Off by default.
+    #[prop_or_default]
+    pub mobile: MobileMode,
+
+    /// Initial pen (e.g. blue ink for originals). Defaults to black at
+    /// `style.line_width`.
+    #[prop_or_default]
+    pub pen: Option<PenStyle>,
+
+    /// Baseline, placeholder and background painted on the pad; never part
+    /// of the exported signature.
+    #[prop_or_default]
+    pub decorations: Option<Decorations>,
+
+    /// Show colour and width controls for the pen in the modal.
+    #[prop_or_default]
+    pub pen_picker: bool,
+
+    /// Field this pad fills (e.g. "buyer", "seller", "witness"). Namespaces
+    /// the ids of the placed image, canvas and buttons so several pads can
+    /// live on one page. `None` keeps the legacy single-pad ids.
+    #[prop_or_default]
+    pub field_id: Option<String>,
+
+    /// Initials mode: instead of one placement, stamp the signature into
+    /// every element matching this selector (e.g. `[data-initials-anchor]`)
+    /// inside the contract container, and keep stamping anchors that appear
+    /// later.
+    #[prop_or_default]
+    pub initials_selector: Option<String>,
+
+    /// Width in px of each stamped initials image.
+    #[prop_or(80)]
+    pub initials_width: i32,
+
+    /// Called with the labels of the filled initials anchors whenever they
+    /// change.
+    #[prop_or_default]
+    pub on_initials: Option<Callback<Vec<String>>>,
+
+    /// `Interactive` lets the signer drag/resize the placed image; the
+    /// envelope is emitted only after they lock the placement.
+    #[prop_or_default]
+    pub placement_mode: PlacementMode,
+
+    /// Snapping and size limits for `PlacementMode::Interactive`.
+    #[prop_or_default]
+    pub interactive_options: InteractiveOptions,
+
+    /// Called with the image geometry after every drag/resize.
+    #[prop_or_default]
+    pub on_placement: Option<Callback<PlacementGeometry>>,
+
     /// Optional anchor element id inside the contract container.
     /// If present and resolved, we place the signature aligned to this anchor.
+    /// Without it, a `data-signature-field="<field_id>"` element is used.
     #[prop_or_default]
     pub anchor_id: Option<String>,
 
-    /// Optional callback called with data URL of the signature PNG.
+    /// Alignment, offsets and fit of the image relative to its anchor.
     #[prop_or_default]
-    pub on_signed: Option<Callback<String>>
+    pub anchor_layout: AnchorLayout,
+
+    /// Enrolled reference signature of this signer. If present, the drawn
+    /// signature is compared against it and the report is put into the
+    /// envelope (`SignedEnvelope::similarity`).
+    #[prop_or_default]
+    pub reference_strokes: Option<Vec<Stroke>>,
+
+    /// Lowest `SimilarityReport::score` against `reference_strokes` that may
+    /// be confirmed; below it "Подписать" asks the signer to sign again.
+    /// `None` only records the report.
+    #[prop_or_default]
+    pub similarity_threshold: Option<f64>,
+
+    /// Called with the report against `reference_strokes` once the
+    /// threshold (if any) is met; return `false` to block confirmation too
+    /// (e.g. to require a second factor).
+    #[prop_or_default]
+    pub on_similarity: Option<Callback<SimilarityReport, bool>>,
+
+    /// Layout of the exported image: the trimmed ink plus text blocks
+    /// (printed name, date) and stamps. The composed PNG is what gets
+    /// placed, hashed into the envelope and embedded in PDFs; `None` exports
+    /// the bare canvas.
+    #[prop_or_default]
+    pub composition: Option<Composition>,
+
+    /// Who is signing; copied into the emitted envelope.
+    #[prop_or_default]
+    pub signer: SignerMetadata,
+
+    /// Multi-signer session. If present, the pad is enabled only while
+    /// `field_id` is an unfilled field of the active signer, and the active
+    /// signer's metadata goes into the envelope instead of `signer`. The app
+    /// feeds emitted envelopes back via `SigningSession::record_signature`.
+    #[prop_or_default]
+    pub session: Option<Rc<SigningSession>>,
+
+    /// Optional callback called with the signed envelope (document hash,
+    /// signature hashes, timestamp, signer and the PNG data URL).
+    #[prop_or_default]
+    pub on_signed: Option<Callback<SignedEnvelope>>,
+
+    /// Signing modal opened.
+    #[prop_or_default]
+    pub on_open: Option<Callback<()>>,
+
+    /// Signing modal closed without signing.
+    #[prop_or_default]
+    pub on_cancel: Option<Callback<()>>,
+
+    /// Canvas cleared in the modal.
+    #[prop_or_default]
+    pub on_clear: Option<Callback<()>>,
+
+    /// Placed signature removed from the contract (remove or re-sign). A
+    /// previously emitted envelope should be discarded.
+    #[prop_or_default]
+    pub on_removed: Option<Callback<()>>,
+
+    /// Persist in-progress strokes and the placed signature (keyed by
+    /// `contract_container_id` and `field_id`) and restore them on mount,
+    /// e.g. `DraftStore::browser()`. Strokes come back into the pad; a
+    /// placed signature only with `on_restored`.
+    #[prop_or_default]
+    pub drafts: Option<DraftStore>,
+
+    /// Opt-in to putting a placed signature from `drafts` back on the
+    /// contract; called with it after placing. Never goes through
+    /// `on_signed`: the stored envelope is unverified. Without this callback
+    /// only the strokes are restored and the signer confirms again.
+    #[prop_or_default]
+    pub on_restored: Option<Callback<RestoredEnvelope>>,
+
+    /// Saved signature of this signer: offers "use saved signature" in the
+    /// modal, lets them store the drawn one (with explicit consent) and
+    /// delete it. Reuse is recorded in `SignedEnvelope::origin`.
+    #[prop_or_default]
+    pub profile: Option<ProfileStore>,
+
+    /// Optional callback called with every audit entry as it is recorded.
+    /// The full trail is also included in the signed envelope.
+    #[prop_or_default]
+    pub on_audit: Option<Callback<AuditEntry>>
 }
 
 #[cfg(feature = "yew")]
 #[function_component(SignaturePadYew)]
 pub fn signature_pad_yew(props: &SignatureProps) -> Html {
-    // Modal state
+    // Audit log lives for the whole component lifetime (signing session)
+    let audit = use_mut_ref(AuditLog::new);
+    let record = {
+        let audit = audit.clone();
+        let on_audit = props.on_audit.clone();
+        let on_error = props.on_error.clone();
+        Rc::new(move |event: AuditEvent, pointer_type: Option<String>| {
+            let recorded = audit
+                .borrow_mut()
+                .record(event, pointer_type.as_deref())
+                .cloned();
+            match recorded {
+                Ok(entry) => {
+                    if let Some(cb) = &on_audit {
+                        cb.emit(entry);
+                    }
+                }
+                Err(e) => {
+                    if let Some(cb) = &on_error {
+                        cb.emit(e);
+                    }
+                }
+            }
+        })
+    };
+
+    // Initials tracker (initials mode only); dropping it stops tracking
+    let initials = use_mut_ref(|| None::<InitialsHandle>);
+
+    // Keeps an anchored image on its anchor across reflows (fixed mode)
+    let tracker = use_mut_ref(|| None::<AnchorTracker>);
+
+    // Interactive placement: live handle plus the envelope waiting for lock
+    let placement = use_mut_ref(|| None::<PlacementHandle>);
+    let pending = use_mut_ref(|| None::<SignedEnvelope>);
+    let awaiting_lock = use_state(|| false);
+
+    // Signature currently placed on the contract (enables remove / re-sign)
+    let signed = use_state(|| false);
+
+    // Strokes kept across modal close / reload (drafts enabled only)
+    let draft_strokes = use_mut_ref(Vec::<Stroke>::new);
+    let save_draft = {
+        let drafts = props.drafts.clone();
+        let draft_strokes = draft_strokes.clone();
+        let container_id = props.contract_container_id.clone();
+        let field_id = props.field_id.clone();
+        Rc::new(move |draft: Draft| {
+            let Some(store) = drafts.clone() else {
+                return;
+            };
+            *draft_strokes.borrow_mut() = draft.strokes.clone();
+            let (container_id, field_id) = (container_id.clone(), field_id.clone());
+            wasm_bindgen_futures::spawn_local(async move {
+                let _ = store.save(&container_id, field_id.as_deref(), draft).await;
+            });
+        })
+    };
+
+    // Final step of signing: snapshot the audit trail and hand out the envelope
+    let emit_signed = {
+        let audit = audit.clone();
+        let signed = signed.clone();
+        let save_draft = save_draft.clone();
+        let on_signed = props.on_signed.clone();
+        Rc::new(move |mut envelope: SignedEnvelope| {
+            envelope.audit_trail = audit.borrow().entries().to_vec();
+            signed.set(true);
+            save_draft(Draft {
+                strokes: envelope.strokes.clone(),
+                envelope: Some(envelope.clone()),
+                ..Default::default()
+            });
+            if let Some(cb) = &on_signed {
+                cb.emit(envelope);
+            }
+        })
+    };
+
+    // Read gate: unlocked once the tracker reports completion
+    let read_done = use_state(|| props.read_gate == ReadGate::None);
+    let read_tracker = use_mut_ref(|| None::<ReadTracker>);
+    {
+        let read_done = read_done.clone();
+        let read_tracker = read_tracker.clone();
+        let record = record.clone();
+        let container_id = props.contract_container_id.clone();
+        let on_read_progress = props.on_read_progress.clone();
+        let on_error = props.on_error.clone();
+        use_effect_with(props.read_gate, move |gate| {
+            let gate = *gate;
+            read_done.set(gate == ReadGate::None);
+            if gate != ReadGate::None {
+                let read_done = read_done.clone();
+                let tracked = DomBindings::track_reading(&container_id, gate, move |progress| {
+                    if let Some(cb) = &on_read_progress {
+                        cb.emit(progress);
+                    }
+                    if progress.complete {
+                        record(
+                            AuditEvent::ReadingCompleted {
+                                sections:   progress.sections_total,
+                                elapsed_ms: progress.elapsed_ms.round() as u64
+                            },
+                            None
+                        );
+                        read_done.set(true);
+                    }
+                });
+                match tracked {
+                    Ok(tracker) => *read_tracker.borrow_mut() = Some(tracker),
+                    Err(e) => {
+                        if let Some(cb) = &on_error {
+                            cb.emit(e);
+                        }
+                    }
+                }
+            }
+            move || {
+                read_tracker.borrow_mut().take();
+            }
+        });
+    }
+
+    // Saved profile signature, whether the pad holds it unchanged, and the
+    // signer's consent to store the one being drawn
+    let saved = use_state(|| None::<SavedSignature>);
+    let reused = use_mut_ref(|| false);
+    let remember = use_state(|| false);
+    {
+        let profile = props.profile.clone();
+        let saved = saved.clone();
+        use_effect_with((), move |_| {
+            if let Some(store) = profile {
+                wasm_bindgen_futures::spawn_local(async move {
+                    if let Ok(found) = store.load().await {
+                        saved.set(found);
+                    }
+                });
+            }
+            || ()
+        });
+    }
+
+    // Restore a saved draft once on mount
+    {
+        let props = props.clone();
+        let audit = audit.clone();
+        let record = record.clone();
+        let draft_strokes = draft_strokes.clone();
+        let initials = initials.clone();
+        let tracker = tracker.clone();
+        let signed = signed.clone();
+        let style = props.style.clone().unwrap_or_default();
+        let canvas_ratio = style.height as f64 / style.width.max(1) as f64;
+        use_effect_with((), move |_| {
+            if let Some(store) = props.drafts.clone() {
+                wasm_bindgen_futures::spawn_local(async move {
+                    let loaded = store
+                        .load(&props.contract_container_id, props.field_id.as_deref())
+                        .await;
+                    let Ok(Some(mut draft)) = loaded else {
+                        return;
+                    };
+                    if props.on_restored.is_none() {
+                        draft.envelope = None;
+                    }
+                    let key = (&props.contract_container_id, props.field_id.as_deref());
+                    if let Some(envelope) = &draft.envelope {
+                        // A draft of another contract text must not come back
+                        let same_text = DomBindings::contract_text(key.0)
+                            .is_ok_and(|text| envelope.matches_document(&text));
+                        if !same_text {
+                            let _ = store.clear(key.0, key.1).await;
+                            record(
+                                AuditEvent::DraftDiscarded {
+                                    reason: "contract text changed".into()
+                                },
+                                None
+                            );
+                            return;
+                        }
+                        // Carry on the draft's chain; events recorded since
+                        // mount are re-linked after it
+                        let relinked = AuditLog::resume(envelope.audit_trail.clone())
+                            .map_err(|broken| format!("audit trail broken at entry {broken}"))
+                            .and_then(|mut resumed| {
+                                let live = audit.borrow().entries().to_vec();
+                                for entry in live {
+                                    resumed
+                                        .push(entry.timestamp, entry.event, entry.context)
+                                        .map_err(|e| e.to_string())?;
+                                }
+                                Ok(resumed)
+                            });
+                        match relinked {
+                            Ok(resumed) => *audit.borrow_mut() = resumed,
+                            Err(reason) => {
+                                record(
+                                    AuditEvent::DraftDiscarded {
+                                        reason
+                                    },
+                                    None
+                                );
+                                draft.envelope = None;
+                            }
+                        }
+                    }
+                    let placed = draft.envelope.as_ref().is_some_and(|envelope| {
+                        restore_placed(
+                            &props,
+                            envelope,
+                            image_ratio(envelope, canvas_ratio),
+                            &initials,
+                            &tracker
+                        )
+                    });
+                    record(
+                        AuditEvent::DraftRestored {
+                            strokes: draft.strokes.len(),
+                            placed
+                        },
+                        None
+                    );
+                    *draft_strokes.borrow_mut() = draft.strokes;
+                    if let (true, Some(envelope)) = (placed, draft.envelope) {
+                        signed.set(true);
+                        if let Some(cb) = &props.on_restored {
+                            cb.emit(RestoredEnvelope {
+                                envelope,
+                                saved_at: draft.saved_at
+                            });
+                        }
+                    }
+                });
+            }
+            || ()
+        });
+    }
+
+    // Modal state; consent is asked again every time it opens
     let is_open = use_state(|| false);
+    let consent_given = use_state(|| false);
+    // Last confirm was blocked by the similarity check
+    let mismatch = use_state(|| false);
+    let eraser_on = use_state(|| false);
+    // Fullscreen signing: the guard lives while the modal is open
+    let fullscreen = use_mut_ref(|| None::<FullscreenSigning>);
+    let mobile = use_state(|| false);
+    let viewport = use_state(viewport_size);
     let open = {
         let is_open = is_open.clone();
-        move |_| is_open.set(true)
+        let consent_given = consent_given.clone();
+        let mismatch = mismatch.clone();
+        let eraser_on = eraser_on.clone();
+        let fullscreen = fullscreen.clone();
+        let mobile = mobile.clone();
+        let viewport = viewport.clone();
+        let mobile_mode = props.mobile;
+        let style = props.style.clone().unwrap_or_default();
+        let record = record.clone();
+        let on_open = props.on_open.clone();
+        Rc::new(move || {
+            record(AuditEvent::ModalOpened, None);
+            consent_given.set(false);
+            mismatch.set(false);
+            eraser_on.set(false);
+            // Still inside the click, as the Fullscreen API requires
+            let on = wants_mobile(mobile_mode, &style);
+            if on {
+                *fullscreen.borrow_mut() = FullscreenSigning::enter().ok();
+                viewport.set(viewport_size());
+            }
+            mobile.set(on);
+            is_open.set(true);
+            if let Some(cb) = &on_open {
+                cb.emit(());
+            }
+        })
     };
     let close = {
         let is_open = is_open.clone();
-        move |_| is_open.set(false)
+        let record = record.clone();
+        let on_cancel = props.on_cancel.clone();
+        move |_| {
+            record(AuditEvent::Cancelled, None);
+            is_open.set(false);
+            if let Some(cb) = &on_cancel {
+                cb.emit(());
+            }
+        }
+    };
+
+    // Take the placed signature (or initials) off the contract
+    let remove = {
+        let record = record.clone();
+        let initials = initials.clone();
+        let tracker = tracker.clone();
+        let placement = placement.clone();
+        let pending = pending.clone();
+        let awaiting_lock = awaiting_lock.clone();
+        let signed = signed.clone();
+        let container_id = props.contract_container_id.clone();
+        let field_id = props.field_id.clone();
+        let anchor_id = props.anchor_id.clone();
+        let save_draft = save_draft.clone();
+        let on_removed = props.on_removed.clone();
+        Rc::new(move || {
+            // Stop observers first so they don't re-stamp / re-align
+            let had_initials = initials.borrow_mut().take().is_some();
+            tracker.borrow_mut().take();
+            placement.borrow_mut().take();
+            pending.borrow_mut().take();
+            awaiting_lock.set(false);
+            signed.set(false);
+            save_draft(Draft::default());
+
+            let removed = if had_initials {
+                crate::ui_common::DomBindings::remove_initials(&container_id, field_id.as_deref())
+                    .is_ok_and(|n| n > 0)
+            } else {
+                crate::ui_common::DomBindings::remove_signature(
+                    &container_id,
+                    field_id.as_deref(),
+                    anchor_id.as_deref()
+                )
+                .unwrap_or(false)
+            };
+            if removed {
+                record(AuditEvent::Removed, None);
+                if let Some(cb) = &on_removed {
+                    cb.emit(());
+                }
+            }
+        })
     };
 
     // SignaturePad state (appears only while modal open)
     let pad_state: yew::UseStateHandle<Option<Rc<RefCell<SignaturePad>>>> = use_state(|| None);
+    // View zoomed in by a pinch (offers "reset zoom")
+    let zoomed = use_state(|| false);
 
     // Canvas ref to init SignaturePad once the modal is rendered
     let canvas_ref = use_node_ref();
 
     let style = props.style.clone().unwrap_or_default();
 
+    // Pen picked in the modal; kept across modal close
+    let pen = {
+        let initial = props
+            .pen
+            .clone()
+            .unwrap_or_else(|| PenStyle::solid("#000000", style.line_width));
+        use_state(move || initial)
+    };
+    let ids = FieldIds::new(props.field_id.as_deref());
+
     // Init SignaturePad when modal opens and canvas is in DOM
     {
         let canvas_ref = canvas_ref.clone();
         let pad_state = pad_state.clone();
         let style = style.clone();
+        let draft_strokes = draft_strokes.clone();
+        let drafts_enabled = props.drafts.is_some();
+        let pen = (*pen).clone();
+        let decorations = props.decorations.clone();
+        let fullscreen = fullscreen.clone();
+        let zoomed = zoomed.clone();
 
         use_effect_with(*is_open, move |open_now| {
             if *open_now {
@@ -111,8 +661,15 @@ pub fn signature_pad_yew(props: &SignatureProps) -> Html {
                     canvas_el.set_height(style.height);
 
                     match SignaturePad::new(canvas_el.clone()) {
-                        Ok(pad) => {
-                            pad_state.set(Some(Rc::new(RefCell::new(pad))));
+                        Ok(mut pad) => {
+                            pad.set_pen_style(pen);
+                            pad.set_decorations(decorations);
+                            // Pick up where the signer left off
+                            let saved = draft_strokes.borrow().clone();
+                            if drafts_enabled && !saved.is_empty() {
+                                pad.load_strokes(saved);
+                            }
+                            pad_state.set(Some(pad.into_shared()));
                         }
                         Err(_e) => {
                             // noop: без канваса подписывать не будем
@@ -121,8 +678,54 @@ pub fn signature_pad_yew(props: &SignatureProps) -> Html {
                     }
                 }
             } else {
-                // close -> drop pad
+                // close -> drop pad, leave fullscreen
                 pad_state.set(None);
+                zoomed.set(false);
+                fullscreen.borrow_mut().take();
+            }
+            || ()
+        });
+    }
+
+    // Fullscreen: follow viewport changes (rotation, browser bars)
+    {
+        let viewport = viewport.clone();
+        use_effect_with(*is_open && *mobile, move |active| {
+            let listener = active.then(|| {
+                let on_resize = wasm_bindgen::closure::Closure::<dyn FnMut()>::new(move || {
+                    viewport.set(viewport_size())
+                });
+                if let Some(window) = web_sys::window() {
+                    let _ = window.add_event_listener_with_callback(
+                        "resize",
+                        on_resize.as_ref().unchecked_ref()
+                    );
+                }
+                on_resize
+            });
+            move || {
+                if let (Some(on_resize), Some(window)) = (listener, web_sys::window()) {
+                    let _ = window.remove_event_listener_with_callback(
+                        "resize",
+                        on_resize.as_ref().unchecked_ref()
+                    );
+                }
+            }
+        });
+    }
+
+    // Canvas size on screen in fullscreen mode; the backing store follows it
+    // so the enlarged ink stays sharp
+    let display = (*mobile).then(|| fit_canvas(&style, *viewport));
+    {
+        let pad_state = pad_state.clone();
+        let width = style.width.max(1) as f64;
+        use_effect_with((display, pad_state.is_some()), move |(display, _)| {
+            if let Some(pad) = pad_state.as_ref() {
+                let ratio = display.map_or(1.0, |(w, _)| {
+                    w / width * web_sys::window().map_or(1.0, |w| w.device_pixel_ratio())
+                });
+                pad.borrow_mut().set_pixel_ratio(ratio);
             }
             || ()
         });
@@ -132,16 +735,46 @@ pub fn signature_pad_yew(props: &SignatureProps) -> Html {
     // exists)
     let on_down = {
         let pad_state = pad_state.clone();
+        let record = record.clone();
+        let reused = reused.clone();
         Callback::from(move |e: web_sys::PointerEvent| {
             if let Some(pad) = pad_state.as_ref() {
-                if let Some(target) = e
-                    .target()
-                    .and_then(|t| t.dyn_into::<web_sys::HtmlCanvasElement>().ok())
+                if !e.is_trusted() {
+                    pad.borrow_mut().note_untrusted_event();
+                    return;
+                }
+                if e.target()
+                    .is_some_and(|t| t.has_type::<web_sys::HtmlCanvasElement>())
                 {
-                    let rect = target.get_bounding_client_rect();
-                    let x = e.client_x() as f64 - rect.x();
-                    let y = e.client_y() as f64 - rect.y();
-                    pad.borrow_mut().pointer_down(x, y);
+                    let mut pad = pad.borrow_mut();
+                    let (cx, cy) = (e.client_x() as f64, e.client_y() as f64);
+                    // A second finger pinches/pans instead of drawing
+                    if e.pointer_type() == "touch" {
+                        let drawing = pad.is_drawing();
+                        if !pad.touch_down(e.pointer_id(), cx, cy) {
+                            // The stroke this finger started is gone again
+                            if drawing && !pad.is_drawing() {
+                                record(AuditEvent::StrokeCancelled, Some(e.pointer_type()));
+                            }
+                            return;
+                        }
+                    }
+                    // Stroke coordinates, whatever the display size and zoom
+                    let (x, y) = pad.client_to_canvas(cx, cy);
+                    // Stylus eraser end / barrel eraser button
+                    if e.buttons() & 32 != 0 || pad.is_eraser() {
+                        pad.eraser_down(x, y);
+                        return;
+                    }
+                    pad.pointer_down_with_pressure(x, y, e.pressure());
+                    *reused.borrow_mut() = false;
+                    record(
+                        AuditEvent::StrokeStarted {
+                            x,
+                            y
+                        },
+                        Some(e.pointer_type())
+                    );
                 }
             }
         })
@@ -149,16 +782,27 @@ pub fn signature_pad_yew(props: &SignatureProps) -> Html {
 
     let on_move = {
         let pad_state = pad_state.clone();
+        let zoomed = zoomed.clone();
         Callback::from(move |e: web_sys::PointerEvent| {
             if let Some(pad) = pad_state.as_ref() {
-                if let Some(target) = e
-                    .target()
-                    .and_then(|t| t.dyn_into::<web_sys::HtmlCanvasElement>().ok())
+                if !e.is_trusted() {
+                    pad.borrow_mut().note_untrusted_event();
+                    return;
+                }
+                if e.target()
+                    .is_some_and(|t| t.has_type::<web_sys::HtmlCanvasElement>())
                 {
-                    let rect = target.get_bounding_client_rect();
-                    let x = e.client_x() as f64 - rect.x();
-                    let y = e.client_y() as f64 - rect.y();
-                    pad.borrow_mut().pointer_move(x, y);
+                    let mut pad = pad.borrow_mut();
+                    let (cx, cy) = (e.client_x() as f64, e.client_y() as f64);
+                    if e.pointer_type() == "touch" && pad.touch_move(e.pointer_id(), cx, cy) {
+                        let now = pad.view().zoom > 1.0;
+                        if now != *zoomed {
+                            zoomed.set(now);
+                        }
+                        return;
+                    }
+                    let (x, y) = pad.client_to_canvas(cx, cy);
+                    pad.pointer_move_with_pressure(x, y, e.pressure());
                 }
             }
         })
@@ -166,9 +810,213 @@ pub fn signature_pad_yew(props: &SignatureProps) -> Html {
 
     let on_up = {
         let pad_state = pad_state.clone();
-        Callback::from(move |_e: web_sys::PointerEvent| {
+        let record = record.clone();
+        let reused = reused.clone();
+        let save_draft = save_draft.clone();
+        Callback::from(move |e: web_sys::PointerEvent| {
             if let Some(pad) = pad_state.as_ref() {
-                pad.borrow_mut().pointer_up();
+                let mut pad = pad.borrow_mut();
+                if !e.is_trusted() {
+                    pad.note_untrusted_event();
+                    return;
+                }
+                if e.pointer_type() == "touch" {
+                    pad.touch_up(e.pointer_id());
+                }
+                if pad.is_erasing() {
+                    pad.pointer_up();
+                    let points = pad.erased_points();
+                    if points > 0 {
+                        *reused.borrow_mut() = false;
+                        record(
+                            AuditEvent::Erased {
+                                points
+                            },
+                            Some(e.pointer_type())
+                        );
+                        save_draft(Draft {
+                            strokes: pad.strokes().to_vec(),
+                            ..Default::default()
+                        });
+                    }
+                } else if pad.is_drawing() {
+                    pad.pointer_up();
+                    let points = pad.strokes().last().map_or(0, |s| s.points.len());
+                    record(
+                        AuditEvent::StrokeEnded {
+                            points
+                        },
+                        Some(e.pointer_type())
+                    );
+                    save_draft(Draft {
+                        strokes: pad.strokes().to_vec(),
+                        ..Default::default()
+                    });
+                }
+            }
+        })
+    };
+
+    // Put a captured envelope on the contract (initials, anchor or fixed
+    // coordinates), finish the audit trail and emit it
+    let place_signed = {
+        let props = props.clone();
+        let record = record.clone();
+        let initials = initials.clone();
+        let tracker = tracker.clone();
+        let placement = placement.clone();
+        let pending = pending.clone();
+        let awaiting_lock = awaiting_lock.clone();
+        let emit_signed = emit_signed.clone();
+        let canvas_ratio = style.height as f64 / style.width.max(1) as f64;
+        Rc::new(move |mut envelope: SignedEnvelope| {
+            let canvas_ratio = image_ratio(&envelope, canvas_ratio);
+            if let Some(selector) = &props.initials_selector {
+                // Initials mode: one capture, stamped at every anchor
+                let on_initials = props.on_initials.clone();
+                let tracked = crate::ui_common::DomBindings::track_initials(
+                    &props.contract_container_id,
+                    props.field_id.as_deref(),
+                    selector,
+                    &envelope.data_url,
+                    props.initials_width,
+                    move |filled| {
+                        if let Some(cb) = &on_initials {
+                            cb.emit(filled.to_vec());
+                        }
+                    }
+                );
+                if let Ok(handle) = tracked {
+                    envelope.initials_anchors = handle.filled();
+                    record(
+                        AuditEvent::InitialsStamped {
+                            anchors: handle.filled()
+                        },
+                        None
+                    );
+                    *initials.borrow_mut() = Some(handle);
+                }
+            } else {
+                let target = anchor_target(&props);
+                let anchored = target.as_ref().and_then(|t| {
+                    crate::ui_common::DomBindings::place_signature_img_at_anchor(
+                        &props.contract_container_id,
+                        props.field_id.as_deref(),
+                        t,
+                        &envelope.data_url,
+                        props.place_width as f64,
+                        canvas_ratio,
+                        &props.anchor_layout
+                    )
+                    .ok()
+                });
+                let placed = match (&target, anchored) {
+                    (Some(t), Some(g)) => {
+                        record(
+                            AuditEvent::AnchorResolved {
+                                anchor_id: t.label(),
+                                x:         g.x.round() as i32,
+                                y:         g.y.round() as i32
+                            },
+                            None
+                        );
+                        Some(g)
+                    }
+                    _ => crate::ui_common::DomBindings::place_signature_img_for_field(
+                        &props.contract_container_id,
+                        props.field_id.as_deref(),
+                        &envelope.data_url,
+                        props.place_x,
+                        props.place_y,
+                        props.place_width
+                    )
+                    .ok()
+                    .map(|_| PlacementGeometry {
+                        x:      props.place_x as f64,
+                        y:      props.place_y as f64,
+                        width:  props.place_width as f64,
+                        height: props.place_width as f64 * canvas_ratio
+                    })
+                };
+                if let Some(g) = placed {
+                    record(
+                        AuditEvent::Placed {
+                            x:     g.x.round() as i32,
+                            y:     g.y.round() as i32,
+                            width: g.width.round() as i32
+                        },
+                        None
+                    );
+                    envelope.placement = Some(g);
+                }
+
+                if let Some(doc) = web_sys::window().and_then(|w| w.document()) {
+                    let y = placed.map_or(props.place_y, |g| g.y.round() as i32);
+                    if let Some(cont_el) = doc
+                        .get_element_by_id(&props.contract_container_id)
+                        .and_then(|e| e.dyn_into::<web_sys::HtmlElement>().ok())
+                    {
+                        cont_el.set_scroll_top(y.saturating_sub(40));
+                    }
+                    // Id anchors are placeholders: hide them, but keep
+                    // their box so the text (and the tracked offset)
+                    // doesn't reflow under the image
+                    if let Some(AnchorTarget::Id(anchor_id)) = &target {
+                        let _ = crate::ui_common::DomBindings::hide_anchor_placeholder(anchor_id);
+                    }
+                }
+
+                if let (Some(t), Some(_)) = (target, anchored)
+                    && props.placement_mode == PlacementMode::Fixed
+                {
+                    *tracker.borrow_mut() = crate::ui_common::DomBindings::track_anchor(
+                        &props.contract_container_id,
+                        props.field_id.as_deref(),
+                        t,
+                        props.place_width as f64,
+                        canvas_ratio,
+                        props.anchor_layout,
+                        |_| {}
+                    )
+                    .ok();
+                }
+            }
+
+            record(AuditEvent::Confirmed, None);
+            envelope.field_id = props.field_id.clone();
+
+            let interactive = props.placement_mode == PlacementMode::Interactive
+                && props.initials_selector.is_none();
+            if interactive {
+                let record = record.clone();
+                let on_placement = props.on_placement.clone();
+                let handle = crate::ui_common::DomBindings::make_placement_interactive(
+                    &props.contract_container_id,
+                    props.field_id.as_deref(),
+                    props.interactive_options.clone(),
+                    move |g: PlacementGeometry| {
+                        record(
+                            AuditEvent::PlacementAdjusted {
+                                x:     g.x.round() as i32,
+                                y:     g.y.round() as i32,
+                                width: g.width.round() as i32
+                            },
+                            None
+                        );
+                        if let Some(cb) = &on_placement {
+                            cb.emit(g);
+                        }
+                    }
+                );
+                if let Ok(handle) = handle {
+                    *placement.borrow_mut() = Some(handle);
+                    *pending.borrow_mut() = Some(envelope);
+                    awaiting_lock.set(true);
+                } else {
+                    emit_signed(envelope);
+                }
+            } else {
+                emit_signed(envelope);
             }
         })
     };
@@ -178,37 +1026,102 @@ pub fn signature_pad_yew(props: &SignatureProps) -> Html {
         let props = props.clone();
         let pad_state = pad_state.clone();
         let is_open = is_open.clone();
+        let record = record.clone();
+        let place_signed = place_signed.clone();
+        let saved = saved.clone();
+        let reused = reused.clone();
+        let remember = remember.clone();
+        let read_done = read_done.clone();
+        let consent_given = consent_given.clone();
+        let mismatch = mismatch.clone();
+        let canvas_size = (style.width, style.height);
         Callback::from(move |_e: web_sys::MouseEvent| {
-            if !props.enabled {
+            if !is_enabled(&props) || !*read_done {
+                return;
+            }
+            let consent_ok = props
+                .consent
+                .as_ref()
+                .is_none_or(|step| !step.required || *consent_given);
+            if !consent_ok {
                 return;
             }
             if let Some(pad) = pad_state.as_ref() {
-                if let Ok(data_url) = pad.borrow().to_png_data_url() {
-                    let (x, y) = compute_placement(&props);
-                    let _ = crate::ui_common::DomBindings::place_signature_img(
-                        &props.contract_container_id,
-                        &data_url,
-                        x,
-                        y,
-                        props.place_width
+                let similarity = props
+                    .reference_strokes
+                    .as_ref()
+                    .map(|reference| compare_strokes(reference, pad.borrow().strokes()));
+                if let Some(report) = &similarity
+                    && !similarity_accepted(&props, report)
+                {
+                    record(
+                        AuditEvent::SimilarityRejected {
+                            score: report.score
+                        },
+                        None
                     );
-
-                    if let Some(doc) = web_sys::window().and_then(|w| w.document()) {
-                        if let Some(cont_el) = doc
-                            .get_element_by_id(&props.contract_container_id)
-                            .and_then(|e| e.dyn_into::<web_sys::HtmlElement>().ok())
-                        {
-                            cont_el.set_scroll_top(y.saturating_sub(40));
-                        }
-                        if let Some(anchor_id) = &props.anchor_id {
-                            if let Some(ph) = doc.get_element_by_id(anchor_id) {
-                                let _ = ph.set_attribute("style", "display:none");
+                    mismatch.set(true);
+                    return;
+                }
+                mismatch.set(false);
+                let signer = props
+                    .session
+                    .as_ref()
+                    .and_then(|s| s.active_signer())
+                    .map_or_else(|| props.signer.clone(), |s| s.signer.clone());
+                let captured =
+                    SignedEnvelope::capture(&pad.borrow(), &props.contract_container_id, signer);
+                if let Ok(mut envelope) = captured {
+                    envelope.similarity = similarity;
+                    envelope.consent = props
+                        .consent
+                        .as_ref()
+                        .filter(|_| *consent_given)
+                        .map(ConsentStep::record);
+                    if let (Some(store), Some(profile)) = (&props.profile, saved.as_ref())
+                        && *reused.borrow()
+                    {
+                        envelope.origin = SignatureOrigin::Reused {
+                            profile_id:   store.profile_id.clone(),
+                            consented_at: profile.consented_at.clone()
+                        };
+                    } else if let (Some(store), true) = (props.profile.clone(), *remember) {
+                        // Signer ticked the consent box: store this signature
+                        let strokes = envelope.strokes.clone();
+                        let saved = saved.clone();
+                        let record = record.clone();
+                        wasm_bindgen_futures::spawn_local(async move {
+                            let stored = store.save(strokes, canvas_size.0, canvas_size.1).await;
+                            if let Ok(profile) = stored {
+                                record(
+                                    AuditEvent::ProfileSaved {
+                                        profile_id: store.profile_id.clone()
+                                    },
+                                    None
+                                );
+                                saved.set(Some(profile));
                             }
-                        }
+                        });
                     }
+                    remember.set(false);
 
-                    if let Some(cb) = &props.on_signed {
-                        cb.emit(data_url);
+                    match props.composition.clone() {
+                        Some(composition) => {
+                            // Composed image first (async: stamps load)
+                            let place_signed = place_signed.clone();
+                            let on_error = props.on_error.clone();
+                            wasm_bindgen_futures::spawn_local(async move {
+                                match envelope.compose(&composition).await {
+                                    Ok(()) => place_signed(envelope),
+                                    Err(e) => {
+                                        if let Some(cb) = &on_error {
+                                            cb.emit(e);
+                                        }
+                                    }
+                                }
+                            });
+                        }
+                        None => place_signed(envelope)
                     }
                     is_open.set(false);
                 }
@@ -219,52 +1132,434 @@ pub fn signature_pad_yew(props: &SignatureProps) -> Html {
     // Clear click
     let on_clear = {
         let pad_state = pad_state.clone();
+        let record = record.clone();
+        let save_draft = save_draft.clone();
+        let reused = reused.clone();
+        let on_clear = props.on_clear.clone();
         Callback::from(move |_e: web_sys::MouseEvent| {
             if let Some(pad) = pad_state.as_ref() {
                 pad.borrow_mut().clear();
+                *reused.borrow_mut() = false;
+                record(AuditEvent::Cleared, None);
+                save_draft(Draft::default());
+                if let Some(cb) = &on_clear {
+                    cb.emit(());
+                }
             }
         })
     };
 
-    let open_btn_disabled = !props.enabled;
+    // Lock click (interactive placement): freeze geometry and emit envelope
+    let on_lock = {
+        let placement = placement.clone();
+        let pending = pending.clone();
+        let awaiting_lock = awaiting_lock.clone();
+        let record = record.clone();
+        Callback::from(move |_e: web_sys::MouseEvent| {
+            let geometry = placement.borrow_mut().take().map(|h| h.geometry());
+            if let Some(mut envelope) = pending.borrow_mut().take() {
+                if let Some(g) = geometry {
+                    record(
+                        AuditEvent::PlacementLocked {
+                            x:     g.x.round() as i32,
+                            y:     g.y.round() as i32,
+                            width: g.width.round() as i32
+                        },
+                        None
+                    );
+                    envelope.placement = Some(g);
+                }
+                emit_signed(envelope);
+            }
+            awaiting_lock.set(false);
+        })
+    };
+
+    // Undo click: drop the last stroke (or bring back the last erase)
+    let on_undo = {
+        let pad_state = pad_state.clone();
+        let record = record.clone();
+        let reused = reused.clone();
+        let save_draft = save_draft.clone();
+        Callback::from(move |_e: web_sys::MouseEvent| {
+            if let Some(pad) = pad_state.as_ref()
+                && pad.borrow_mut().undo()
+            {
+                *reused.borrow_mut() = false;
+                record(AuditEvent::Undo, None);
+                save_draft(Draft {
+                    strokes: pad.borrow().strokes().to_vec(),
+                    ..Default::default()
+                });
+            }
+        })
+    };
+
+    // Load the saved profile signature into the pad
+    let on_use_saved = {
+        let pad_state = pad_state.clone();
+        let saved = saved.clone();
+        let reused = reused.clone();
+        let record = record.clone();
+        let save_draft = save_draft.clone();
+        let profile_id = props.profile.as_ref().map(|p| p.profile_id.clone());
+        let canvas_size = (style.width, style.height);
+        Callback::from(move |_e: web_sys::MouseEvent| {
+            if let (Some(pad), Some(profile), Some(profile_id)) =
+                (pad_state.as_ref(), saved.as_ref(), &profile_id)
+            {
+                let strokes = profile.fit(canvas_size);
+                pad.borrow_mut().load_strokes(strokes.clone());
+                *reused.borrow_mut() = true;
+                record(
+                    AuditEvent::ProfileUsed {
+                        profile_id: profile_id.clone()
+                    },
+                    None
+                );
+                save_draft(Draft {
+                    strokes,
+                    ..Default::default()
+                });
+            }
+        })
+    };
+
+    // Delete the saved profile signature (withdraws consent)
+    let on_delete_saved = {
+        let saved = saved.clone();
+        let reused = reused.clone();
+        let record = record.clone();
+        let profile = props.profile.clone();
+        Callback::from(move |_e: web_sys::MouseEvent| {
+            if let Some(store) = profile.clone() {
+                let saved = saved.clone();
+                let record = record.clone();
+                *reused.borrow_mut() = false;
+                wasm_bindgen_futures::spawn_local(async move {
+                    if store.delete().await.is_ok() {
+                        record(
+                            AuditEvent::ProfileDeleted {
+                                profile_id: store.profile_id.clone()
+                            },
+                            None
+                        );
+                        saved.set(None);
+                    }
+                });
+            }
+        })
+    };
+
+    let on_consent = {
+        let consent_given = consent_given.clone();
+        let record = record.clone();
+        let text = props.consent.as_ref().map(|c| c.text.clone());
+        Callback::from(move |_e: web_sys::MouseEvent| {
+            let given = !*consent_given;
+            consent_given.set(given);
+            if let (true, Some(text)) = (given, &text) {
+                record(
+                    AuditEvent::ConsentGiven {
+                        text: text.clone()
+                    },
+                    None
+                );
+            }
+        })
+    };
+
+    let on_reset_zoom = {
+        let pad_state = pad_state.clone();
+        let zoomed = zoomed.clone();
+        Callback::from(move |_e: web_sys::MouseEvent| {
+            if let Some(pad) = pad_state.as_ref() {
+                pad.borrow_mut().reset_view();
+            }
+            zoomed.set(false);
+        })
+    };
+
+    // Eraser toggle; the pad starts in pen mode on every open
+    let on_eraser = {
+        let eraser_on = eraser_on.clone();
+        let pad_state = pad_state.clone();
+        Callback::from(move |_e: web_sys::MouseEvent| {
+            let on = !*eraser_on;
+            if let Some(pad) = pad_state.as_ref() {
+                pad.borrow_mut().set_eraser(on);
+            }
+            eraser_on.set(on);
+        })
+    };
+
+    // Pen picker: colour, and width (pressure range scaled along)
+    let set_pen = {
+        let pen = pen.clone();
+        let pad_state = pad_state.clone();
+        Rc::new(move |next: PenStyle| {
+            if let Some(pad) = pad_state.as_ref() {
+                pad.borrow_mut().set_pen_style(next.clone());
+            }
+            pen.set(next);
+        })
+    };
+    let on_pen_color = {
+        let pen = pen.clone();
+        let set_pen = set_pen.clone();
+        Callback::from(move |e: web_sys::InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            set_pen(PenStyle {
+                color: input.value(),
+                ..(*pen).clone()
+            });
+        })
+    };
+    let on_pen_width = {
+        let pen = pen.clone();
+        Callback::from(move |e: web_sys::InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let Ok(width) = input.value().parse::<f64>() else {
+                return;
+            };
+            let ratio = if pen.max_width > 0.0 {
+                pen.min_width / pen.max_width
+            } else {
+                1.0
+            };
+            set_pen(PenStyle {
+                min_width: width * ratio,
+                max_width: width,
+                ..(*pen).clone()
+            });
+        })
+    };
+
+    let on_remember = {
+        let remember = remember.clone();
+        Callback::from(move |_e: web_sys::MouseEvent| remember.set(!*remember))
+    };
+
+    // Re-sign: remove the current signature, then sign again
+    let on_resign = {
+        let remove = remove.clone();
+        let open = open.clone();
+        Callback::from(move |_e: web_sys::MouseEvent| {
+            remove();
+            open();
+        })
+    };
+    let on_remove = Callback::from(move |_e: web_sys::MouseEvent| remove());
+    let on_open = Callback::from(move |_e: web_sys::MouseEvent| open());
+
+    let enabled = is_enabled(props) && *read_done;
+    let confirm_disabled = !enabled
+        || props
+            .consent
+            .as_ref()
+            .is_some_and(|step| step.required && !*consent_given);
+    let open_btn_disabled = !enabled;
 
     html! {
         <>
-          <button id="sign-open-btn"
-                  disabled={open_btn_disabled}
-                  onclick={open}>
-            {"Открыть окно подписи"}
-          </button>
+          {
+            if *signed {
+              html! {
+                <>
+                  <button id={ids.open_btn()}
+                          class="cs-open-btn"
+                          disabled={open_btn_disabled}
+                          onclick={on_resign}>
+                    {"Переподписать"}
+                  </button>
+                  <button class="cs-remove-btn secondary"
+                          disabled={open_btn_disabled}
+                          onclick={on_remove}>
+                    {"Удалить подпись"}
+                  </button>
+                </>
+              }
+            } else {
+              html! {
+                <button id={ids.open_btn()}
+                        class="cs-open-btn"
+                        disabled={open_btn_disabled}
+                        onclick={on_open}>
+                  {"Открыть окно подписи"}
+                </button>
+              }
+            }
+          }
+
+          {
+            if *awaiting_lock {
+              html! {
+                <button class="cs-lock-btn" onclick={on_lock}>{"Зафиксировать подпись"}</button>
+              }
+            } else {
+              Html::default()
+            }
+          }
 
           {
             if *is_open {
               html! {
                 <>
                   <div class="cs-modal-backdrop" onclick={close.clone()}></div>
-                  <div class="cs-modal">
+                  <div class={if *mobile { "cs-modal cs-modal--fullscreen" } else { "cs-modal" }}>
                     <div class="cs-modal__panel"
+                         style={if *mobile { FULLSCREEN_PANEL_STYLE } else { "" }}
                          onclick={Callback::from(|e: web_sys::MouseEvent| { e.stop_propagation(); })}>
                       <h3>{"Подпись"}</h3>
-                      <canvas id="signature-canvas"
+                      {
+                        if *mobile && viewport.1 > viewport.0 {
+                          html! {
+                            <p class="cs-rotate-hint">{"Поверните телефон горизонтально"}</p>
+                          }
+                        } else {
+                          Html::default()
+                        }
+                      }
+                      <canvas id={ids.canvas()}
                               ref={canvas_ref}
                               width={style.width.to_string()}
                               height={style.height.to_string()}
                               style={format!(
-                                "background:{};border:{};touch-action:none;",
-                                style.background, style.border
+                                "background:{};border:{};touch-action:none;{}",
+                                style.background,
+                                style.border,
+                                display.map_or(String::new(), |(w, h)| format!(
+                                  "width:{w:.0}px;height:{h:.0}px;"
+                                ))
                               )}
                               onpointerdown={on_down}
                               onpointermove={on_move}
                               onpointerup={on_up.clone()}
+                              onpointercancel={on_up.clone()}
                               onpointerleave={on_up}
                       />
+                      {
+                        if props.pen_picker {
+                          html! {
+                            <div class="cs-pen">
+                              <label>
+                                {"Цвет "}
+                                <input type="color"
+                                       class="cs-pen-color"
+                                       value={pen.hex()}
+                                       oninput={on_pen_color}/>
+                              </label>
+                              <label>
+                                {"Толщина "}
+                                <input type="range"
+                                       class="cs-pen-width"
+                                       min="1" max="8" step="0.5"
+                                       value={pen.max_width.to_string()}
+                                       oninput={on_pen_width}/>
+                              </label>
+                            </div>
+                          }
+                        } else {
+                          Html::default()
+                        }
+                      }
+                      {
+                        if let Some(step) = &props.consent {
+                          html! {
+                            <label class="cs-consent">
+                              <input type="checkbox"
+                                     checked={*consent_given}
+                                     required={step.required}
+                                     onclick={on_consent}/>
+                              {step.text.clone()}
+                              {
+                                if let Some(link) = &step.link {
+                                  html! {
+                                    <>
+                                      {" "}
+                                      <a href={link.url.clone()} target="_blank" rel="noopener noreferrer">
+                                        {link.label.clone()}
+                                      </a>
+                                    </>
+                                  }
+                                } else {
+                                  Html::default()
+                                }
+                              }
+                            </label>
+                          }
+                        } else {
+                          Html::default()
+                        }
+                      }
+                      {
+                        if *mismatch {
+                          html! {
+                            <div class="cs-similarity-warning" role="alert">
+                              {"Подпись не совпадает с образцом. Очистите поле и распишитесь ещё раз."}
+                            </div>
+                          }
+                        } else {
+                          Html::default()
+                        }
+                      }
                       <div class="controls">
-                        <button id="sign-confirm-btn" onclick={on_confirm.clone()} disabled={!props.enabled}>
+                        <button id={ids.confirm_btn()}
+                                class="cs-confirm-btn"
+                                onclick={on_confirm.clone()}
+                                disabled={confirm_disabled}>
                           {"Подписать"}
                         </button>
-                        <button id="sign-clear-btn" onclick={on_clear}>{"Очистить"}</button>
+                        <button id={ids.clear_btn()} onclick={on_clear}>{"Очистить"}</button>
+                        <button class="secondary" onclick={on_undo}>{"Отменить штрих"}</button>
+                        <button class={if *eraser_on { "cs-eraser-btn active" } else { "cs-eraser-btn secondary" }}
+                                onclick={on_eraser}>
+                          {if *eraser_on { "Перо" } else { "Ластик" }}
+                        </button>
+                        {
+                          if *zoomed {
+                            html! {
+                              <button class="cs-reset-zoom-btn secondary" onclick={on_reset_zoom}>
+                                {"Сбросить масштаб"}
+                              </button>
+                            }
+                          } else {
+                            Html::default()
+                          }
+                        }
                         <button class="secondary" onclick={close}>{"Отмена"}</button>
                       </div>
+                      {
+                        if let Some(store) = &props.profile {
+                          html! {
+                            <div class="cs-profile">
+                              {
+                                if saved.is_some() {
+                                  html! {
+                                    <>
+                                      <button class="cs-use-saved-btn" onclick={on_use_saved}>
+                                        {"Использовать сохранённую подпись"}
+                                      </button>
+                                      <button class="cs-delete-saved-btn secondary"
+                                              onclick={on_delete_saved}>
+                                        {"Удалить сохранённую подпись"}
+                                      </button>
+                                    </>
+                                  }
+                                } else {
+                                  Html::default()
+                                }
+                              }
+                              <label class="cs-profile-consent">
+                                <input type="checkbox" checked={*remember} onclick={on_remember}/>
+                                {store.consent_text.clone()}
+                              </label>
+                            </div>
+                          }
+                        } else {
+                          Html::default()
+                        }
+                      }
                     </div>
                   </div>
                 </>
@@ -277,25 +1572,161 @@ pub fn signature_pad_yew(props: &SignatureProps) -> Html {
     }
 }
 
+/// Inner size of the window, CSS px.
 #[cfg(feature = "yew")]
-fn compute_placement(props: &SignatureProps) -> (i32, i32) {
-    if let Some(anchor_id) = &props.anchor_id {
-        if let Some(doc) = web_sys::window().and_then(|w| w.document()) {
-            let cont_el = doc.get_element_by_id(&props.contract_container_id);
-            let anchor_el = doc.get_element_by_id(anchor_id);
-            if let (Some(cont_el), Some(anchor_el)) = (cont_el, anchor_el) {
-                if let (Ok(cont), Ok(anchor)) = (
-                    cont_el.dyn_into::<web_sys::HtmlElement>(),
-                    anchor_el.dyn_into::<web_sys::HtmlElement>()
-                ) {
-                    let cr = cont.get_bounding_client_rect();
-                    let ar = anchor.get_bounding_client_rect();
-                    let left = (ar.left() - cr.left()) + cont.scroll_left() as f64;
-                    let top = (ar.top() - cr.top()) + cont.scroll_top() as f64;
-                    return (left.round() as i32, top.round() as i32);
+fn viewport_size() -> (f64, f64) {
+    let Some(window) = web_sys::window() else {
+        return (0.0, 0.0);
+    };
+    let px = |v: Result<wasm_bindgen::JsValue, _>| v.ok().and_then(|v| v.as_f64()).unwrap_or(0.0);
+    (px(window.inner_width()), px(window.inner_height()))
+}
+
+/// Whether opening the modal should switch to fullscreen signing.
+#[cfg(feature = "yew")]
+fn wants_mobile(mode: MobileMode, style: &SignatureStyle) -> bool {
+    match mode {
+        MobileMode::Off => false,
+        MobileMode::Always => true,
+        MobileMode::Auto => {
+            let coarse = web_sys::window()
+                .and_then(|w| w.match_media("(pointer: coarse)").ok().flatten())
+                .is_some_and(|m| m.matches());
+            coarse || viewport_size().0 < style.width as f64 + 2.0 * FULLSCREEN_MARGIN
+        }
+    }
+}
+
+/// Largest on-screen canvas size with `style`'s aspect ratio that leaves
+/// room for the controls in a `viewport`-sized window.
+#[cfg(feature = "yew")]
+fn fit_canvas(style: &SignatureStyle, viewport: (f64, f64)) -> (f64, f64) {
+    let (w, h) = (style.width.max(1) as f64, style.height.max(1) as f64);
+    let avail_w = (viewport.0 - 2.0 * FULLSCREEN_MARGIN).max(1.0);
+    let avail_h = (viewport.1 - FULLSCREEN_RESERVED_HEIGHT)
+        .max(viewport.1 * 0.4)
+        .max(1.0);
+    let s = (avail_w / w).min(avail_h / h);
+    (w * s, h * s)
+}
+
+/// `enabled`, narrowed by the signing session (if any) to the active
+/// signer's unfilled fields.
+#[cfg(feature = "yew")]
+fn is_enabled(props: &SignatureProps) -> bool {
+    props.enabled
+        && props.session.as_ref().is_none_or(|session| {
+            props
+                .field_id
+                .as_deref()
+                .is_some_and(|f| session.is_field_enabled(f))
+        })
+}
+
+/// Put a restored envelope's signature back onto the contract: initials at
+/// their anchors, otherwise at the anchor (re-tracked in fixed mode) or the
+/// saved geometry. Returns whether anything was placed.
+#[cfg(feature = "yew")]
+fn restore_placed(
+    props: &SignatureProps,
+    envelope: &SignedEnvelope,
+    canvas_ratio: f64,
+    initials: &RefCell<Option<InitialsHandle>>,
+    tracker: &RefCell<Option<AnchorTracker>>
+) -> bool {
+    if let Some(selector) = &props.initials_selector {
+        if envelope.initials_anchors.is_empty() {
+            return false;
+        }
+        let on_initials = props.on_initials.clone();
+        let handle = DomBindings::track_initials(
+            &props.contract_container_id,
+            props.field_id.as_deref(),
+            selector,
+            &envelope.data_url,
+            props.initials_width,
+            move |filled| {
+                if let Some(cb) = &on_initials {
+                    cb.emit(filled.to_vec());
                 }
             }
+        );
+        let placed = handle.is_ok();
+        *initials.borrow_mut() = handle.ok();
+        return placed;
+    }
+
+    let Some(saved) = envelope.placement else {
+        return false;
+    };
+    let target = anchor_target(props).filter(|_| props.placement_mode == PlacementMode::Fixed);
+    if let Some(t) = target {
+        let anchored = DomBindings::place_signature_img_at_anchor(
+            &props.contract_container_id,
+            props.field_id.as_deref(),
+            &t,
+            &envelope.data_url,
+            props.place_width as f64,
+            canvas_ratio,
+            &props.anchor_layout
+        );
+        if anchored.is_ok() {
+            if let AnchorTarget::Id(anchor_id) = &t {
+                let _ = DomBindings::hide_anchor_placeholder(anchor_id);
+            }
+            *tracker.borrow_mut() = DomBindings::track_anchor(
+                &props.contract_container_id,
+                props.field_id.as_deref(),
+                t,
+                props.place_width as f64,
+                canvas_ratio,
+                props.anchor_layout,
+                |_| {}
+            )
+            .ok();
+            return true;
         }
     }
-    (props.place_x, props.place_y)
+    DomBindings::place_signature_img_for_field(
+        &props.contract_container_id,
+        props.field_id.as_deref(),
+        &envelope.data_url,
+        saved.x.round() as i32,
+        saved.y.round() as i32,
+        saved.width.round() as i32
+    )
+    .is_ok()
+}
+
+/// Does `report` pass `similarity_threshold` and `on_similarity`?
+#[cfg(feature = "yew")]
+fn similarity_accepted(props: &SignatureProps, report: &SimilarityReport) -> bool {
+    props
+        .similarity_threshold
+        .is_none_or(|threshold| report.is_match(threshold))
+        && props
+            .on_similarity
+            .as_ref()
+            .is_none_or(|cb| cb.emit(report.clone()))
+}
+
+/// Height / width of the envelope's image: the canvas ratio, unless a
+/// composition changed the shape.
+#[cfg(feature = "yew")]
+fn image_ratio(envelope: &SignedEnvelope, canvas_ratio: f64) -> f64 {
+    match envelope.image_size() {
+        Ok((w, h)) if envelope.composed => h as f64 / w.max(1) as f64,
+        _ => canvas_ratio
+    }
+}
+
+/// Anchor of this pad: `anchor_id` if set, else the
+/// `data-signature-field` element named after `field_id`.
+#[cfg(feature = "yew")]
+fn anchor_target(props: &SignatureProps) -> Option<AnchorTarget> {
+    props
+        .anchor_id
+        .clone()
+        .map(AnchorTarget::Id)
+        .or_else(|| props.field_id.clone().map(AnchorTarget::Field))
 }